@@ -0,0 +1,1231 @@
+//! # Assemble GNU style RISC-V assembly into a program
+//!
+//! The assembler understands all instructions of [`Opcode`] with their canonical (non-alias)
+//! mnemonics, ABI and `xN`/`fN` register names, CSR names, optional rounding modes, `imm(rs1)`
+//! memory operands and labels. Code is placed
+//! into the `.text` section and data into the `.data` section, which can be filled with the
+//! `.byte`, `.half`, `.word`, `.dword`, `.ascii`, `.asciz` and `.zero` directives and aligned
+//! with `.align`.
+//!
+//! Branch and jump targets are either labels, absolute addresses or addresses relative to the
+//! instruction (`.+8`), which makes the output of the [disassembler](crate::disassemble) valid
+//! input for the assembler.
+//!
+//! ```
+//! use riscu::assemble::assemble;
+//!
+//! let program = assemble(
+//!     "
+//!     _start:
+//!         ld a0, 0(gp)
+//!         addi a7, zero, 93
+//!         ecall
+//!     .data
+//!     value:
+//!         .dword 42
+//!     ",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(program.address_of("value"), Some(program.data.address));
+//! ```
+
+use crate::{
+    elf::{round_up, PAGE_SIZE},
+    emulator::VIRTUAL_MEMORY_SIZE,
+    opcode::{Constructor, OperandError, Operands},
+    types::RoundingMode,
+    Csr, FRegister, Instruction, InstructionClass, Opcode, Program, ProgramSegment, Register,
+    Symbol, SymbolKind,
+};
+use std::{collections::HashMap, convert::TryFrom};
+use thiserror::Error;
+
+/// The address of the code segment in binaries generated by Selfie.
+pub const DEFAULT_CODE_ADDRESS: u64 = 0x10000;
+
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum AssemblerErrorKind {
+    #[error("unknown mnemonic \"{0}\"")]
+    UnknownMnemonic(String),
+
+    #[error("unknown directive \"{0}\"")]
+    UnknownDirective(String),
+
+    #[error("invalid register \"{0}\"")]
+    InvalidRegister(String),
+
+    #[error("invalid operand \"{0}\"")]
+    InvalidOperand(String),
+
+    #[error("expected {expected} operands, found {found}")]
+    OperandCount { expected: usize, found: usize },
+
+    #[error("immediate {0} is out of range")]
+    ImmediateOutOfRange(i64),
+
+    #[error("jump target {0:#x} is not aligned to 2 bytes")]
+    MisalignedTarget(u64),
+
+    #[error("instruction is not aligned to 4 bytes")]
+    MisalignedInstruction,
+
+    #[error("undefined label \"{0}\"")]
+    UndefinedLabel(String),
+
+    #[error("label \"{0}\" is defined twice")]
+    DuplicateLabel(String),
+
+    #[error("program exceeds the 4 GiB address space")]
+    AddressSpaceExceeded,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+#[error("line {line}: {kind}")]
+pub struct AssemblerError {
+    /// The line of the error in the source (starting at 1).
+    pub line: usize,
+    pub kind: AssemblerErrorKind,
+}
+
+type AssemblerResult<T> = Result<T, AssemblerErrorKind>;
+
+/// Assemble the source into a program with the code segment starting at
+/// [`DEFAULT_CODE_ADDRESS`].
+pub fn assemble(source: &str) -> Result<Program, AssemblerError> {
+    assemble_at(source, DEFAULT_CODE_ADDRESS)
+}
+
+/// Assemble the source into a program with the code segment starting at `code_address`. As in
+/// binaries generated by Selfie, the data segment starts a whole number of pages after the code
+/// segment, which is the first page boundary after it if `code_address` is page-aligned. `.align`
+/// aligns offsets within a section, which are only aligned addresses if its start is.
+pub fn assemble_at(source: &str, code_address: u64) -> Result<Program, AssemblerError> {
+    let statements = parse(source)?;
+
+    // first pass: lay out both sections to learn the offsets of all labels
+    let mut offsets = [0_u64; 2];
+    let mut labels = HashMap::new();
+
+    for statement in statements.iter() {
+        let offset = &mut offsets[statement.section as usize];
+
+        for label in statement.labels.iter() {
+            if labels
+                .insert(label.clone(), (statement.section, *offset))
+                .is_some()
+            {
+                return Err(AssemblerError {
+                    line: statement.line,
+                    kind: AssemblerErrorKind::DuplicateLabel(label.clone()),
+                });
+            }
+        }
+
+        *offset += statement.size(*offset).map_err(|kind| AssemblerError {
+            line: statement.line,
+            kind,
+        })?;
+
+        // checked on every statement, so that huge `.zero` directives are never allocated
+        if !matches!(program_end(code_address, offsets), Some(end) if end <= VIRTUAL_MEMORY_SIZE) {
+            return Err(AssemblerError {
+                line: statement.line,
+                kind: AssemblerErrorKind::AddressSpaceExceeded,
+            });
+        }
+    }
+
+    // the first pass checked that both sections fit into the address space
+    let data_address = program_end(code_address, [offsets[Section::Text as usize], 0])
+        .expect("the code segment fits into the address space");
+    let base = |section: Section| match section {
+        Section::Text => code_address,
+        Section::Data => data_address,
+    };
+
+    let labels = labels
+        .into_iter()
+        .map(|(name, (section, offset))| (name, base(section) + offset))
+        .collect::<HashMap<_, _>>();
+
+    // second pass: encode everything with all labels resolved
+    let mut contents = [Vec::new(), Vec::new()];
+
+    for statement in statements.iter() {
+        let content = &mut contents[statement.section as usize];
+        let address = base(statement.section) + content.len() as u64;
+
+        statement
+            .emit(address, &labels, content)
+            .map_err(|kind| AssemblerError {
+                line: statement.line,
+                kind,
+            })?;
+    }
+
+    let [code, data] = contents;
+
+    let mut symbols = labels
+        .into_iter()
+        .map(|(name, address)| Symbol {
+            name,
+            address,
+            size: 0,
+            kind: SymbolKind::NoType,
+        })
+        .collect::<Vec<_>>();
+
+    symbols.sort_by(|a, b| a.address.cmp(&b.address).then(a.name.cmp(&b.name)));
+
+    let mut program = Program::new(
+        ProgramSegment {
+            address: code_address,
+            content: code,
+        },
+        ProgramSegment {
+            address: data_address,
+            content: data,
+        },
+    );
+
+    program.symbols = symbols;
+
+    Ok(program)
+}
+
+/// The end of the data segment given the sizes of both sections.
+fn program_end(code_address: u64, sizes: [u64; 2]) -> Option<u64> {
+    let [code_size, data_size] = sizes;
+
+    code_address
+        .checked_add(round_up(code_size, PAGE_SIZE)?)?
+        .checked_add(data_size)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Section {
+    Text = 0,
+    Data = 1,
+}
+
+#[derive(Debug)]
+struct Statement<'a> {
+    line: usize,
+    section: Section,
+    labels: Vec<String>,
+    kind: StatementKind<'a>,
+}
+
+#[derive(Debug)]
+enum StatementKind<'a> {
+    Empty,
+    Instruction(&'a str, Vec<&'a str>),
+    Directive(&'a str, Vec<&'a str>),
+}
+
+fn parse(source: &str) -> Result<Vec<Statement<'_>>, AssemblerError> {
+    let mut section = Section::Text;
+    let mut statements = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let error = |kind| AssemblerError {
+            line: index + 1,
+            kind,
+        };
+
+        let mut rest = strip_comment(line).trim();
+        let mut labels = Vec::new();
+
+        while let Some(colon) = rest.find(':') {
+            let label = rest[..colon].trim();
+
+            if label.is_empty() || !label.chars().all(is_symbol_char) {
+                break;
+            }
+
+            labels.push(label.to_string());
+            rest = rest[colon + 1..].trim();
+        }
+
+        let (head, tail) = match rest.find(char::is_whitespace) {
+            Some(split) => (&rest[..split], rest[split..].trim()),
+            None => (rest, ""),
+        };
+
+        let kind = match head {
+            "" => StatementKind::Empty,
+            ".text" => {
+                section = Section::Text;
+                StatementKind::Empty
+            }
+            ".data" => {
+                section = Section::Data;
+                StatementKind::Empty
+            }
+            _ if head.starts_with('.') => {
+                StatementKind::Directive(head, split_operands(tail).map_err(error)?)
+            }
+            _ => StatementKind::Instruction(head, split_operands(tail).map_err(error)?),
+        };
+
+        statements.push(Statement {
+            line: index + 1,
+            section,
+            labels,
+            kind,
+        });
+    }
+
+    Ok(statements)
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+fn is_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$'
+}
+
+/// Split operands at commas, which are not part of a string literal.
+fn split_operands(operands: &str) -> AssemblerResult<Vec<&str>> {
+    if operands.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut result = Vec::new();
+    let mut start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in operands.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                result.push(operands[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    result.push(operands[start..].trim());
+
+    if let Some(empty) = result.iter().find(|o| o.is_empty()) {
+        return Err(AssemblerErrorKind::InvalidOperand(empty.to_string()));
+    }
+
+    Ok(result)
+}
+
+impl Statement<'_> {
+    /// The number of bytes this statement occupies when placed at `offset` in its section.
+    fn size(&self, offset: u64) -> AssemblerResult<u64> {
+        match &self.kind {
+            StatementKind::Empty => Ok(0),
+            StatementKind::Instruction(_, _) => Ok(4),
+            StatementKind::Directive(directive, operands) => {
+                directive_size(directive, operands, offset)
+            }
+        }
+    }
+
+    fn emit(
+        &self,
+        address: u64,
+        labels: &HashMap<String, u64>,
+        content: &mut Vec<u8>,
+    ) -> AssemblerResult<()> {
+        match &self.kind {
+            StatementKind::Empty => Ok(()),
+            StatementKind::Instruction(mnemonic, operands) => {
+                if address & 0b11 != 0 {
+                    return Err(AssemblerErrorKind::MisalignedInstruction);
+                }
+
+                let instruction = assemble_instruction(mnemonic, operands, address, labels)?;
+
+                content.extend_from_slice(&u32::from(instruction).to_le_bytes());
+
+                Ok(())
+            }
+            StatementKind::Directive(directive, operands) => {
+                emit_directive(directive, operands, labels, content)
+            }
+        }
+    }
+}
+
+fn directive_size(directive: &str, operands: &[&str], offset: u64) -> AssemblerResult<u64> {
+    let count = operands.len() as u64;
+
+    match directive {
+        ".byte" => Ok(count),
+        ".half" => Ok(2 * count),
+        ".word" => Ok(4 * count),
+        ".dword" => Ok(8 * count),
+        ".ascii" => Ok(parse_strings(operands)?
+            .iter()
+            .map(|s| s.len() as u64)
+            .sum()),
+        ".asciz" => Ok(parse_strings(operands)?
+            .iter()
+            .map(|s| s.len() as u64 + 1)
+            .sum()),
+        ".zero" => parse_size(operands),
+        ".align" => round_up(offset, parse_alignment(operands)?)
+            .map(|aligned| aligned - offset)
+            .ok_or(AssemblerErrorKind::AddressSpaceExceeded),
+        _ => Err(AssemblerErrorKind::UnknownDirective(directive.to_string())),
+    }
+}
+
+fn emit_directive(
+    directive: &str,
+    operands: &[&str],
+    labels: &HashMap<String, u64>,
+    content: &mut Vec<u8>,
+) -> AssemblerResult<()> {
+    let value = |operand: &str, bits: u32| -> AssemblerResult<u64> {
+        let value = match labels.get(operand) {
+            Some(address) => *address as i64,
+            None => parse_number(operand)?,
+        };
+
+        if bits < 64 && (value < -(1 << (bits - 1)) || value >= (1 << bits)) {
+            return Err(AssemblerErrorKind::ImmediateOutOfRange(value));
+        }
+
+        Ok(value as u64)
+    };
+
+    match directive {
+        ".byte" => operands.iter().try_for_each(|o| {
+            content.push(value(o, 8)? as u8);
+            Ok(())
+        }),
+        ".half" => operands.iter().try_for_each(|o| {
+            content.extend_from_slice(&(value(o, 16)? as u16).to_le_bytes());
+            Ok(())
+        }),
+        ".word" => operands.iter().try_for_each(|o| {
+            content.extend_from_slice(&(value(o, 32)? as u32).to_le_bytes());
+            Ok(())
+        }),
+        ".dword" => operands.iter().try_for_each(|o| {
+            content.extend_from_slice(&value(o, 64)?.to_le_bytes());
+            Ok(())
+        }),
+        ".ascii" | ".asciz" => {
+            for string in parse_strings(operands)? {
+                content.extend_from_slice(&string);
+
+                if directive == ".asciz" {
+                    content.push(0);
+                }
+            }
+            Ok(())
+        }
+        _ => {
+            // padding depends on the offset in the section, as in the first pass
+            let size = directive_size(directive, operands, content.len() as u64)?;
+            content.resize(content.len() + size as usize, 0);
+            Ok(())
+        }
+    }
+}
+
+fn parse_size(operands: &[&str]) -> AssemblerResult<u64> {
+    match operands {
+        [size] => match parse_number(size)? {
+            size if size >= 0 => Ok(size as u64),
+            size => Err(AssemblerErrorKind::ImmediateOutOfRange(size)),
+        },
+        _ => Err(AssemblerErrorKind::OperandCount {
+            expected: 1,
+            found: operands.len(),
+        }),
+    }
+}
+
+/// As in the GNU assembler for RISC-V, `.align n` aligns to `2^n` bytes.
+fn parse_alignment(operands: &[&str]) -> AssemblerResult<u64> {
+    match parse_size(operands)? {
+        exponent if exponent < 16 => Ok(1 << exponent),
+        exponent => Err(AssemblerErrorKind::ImmediateOutOfRange(exponent as i64)),
+    }
+}
+
+fn parse_strings(operands: &[&str]) -> AssemblerResult<Vec<Vec<u8>>> {
+    operands.iter().map(|o| parse_string(o)).collect()
+}
+
+fn parse_string(operand: &str) -> AssemblerResult<Vec<u8>> {
+    let invalid = || AssemblerErrorKind::InvalidOperand(operand.to_string());
+
+    if operand.len() < 2 || !operand.starts_with('"') || !operand.ends_with('"') {
+        return Err(invalid());
+    }
+
+    let mut result = Vec::new();
+    let mut chars = operand[1..operand.len() - 1].chars();
+
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next().ok_or_else(invalid)? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                '\\' => '\\',
+                '"' => '"',
+                _ => return Err(invalid()),
+            },
+            '"' => return Err(invalid()),
+            c => c,
+        };
+
+        let mut buffer = [0; 4];
+        result.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+    }
+
+    Ok(result)
+}
+
+fn parse_number(operand: &str) -> AssemblerResult<i64> {
+    let invalid = || AssemblerErrorKind::InvalidOperand(operand.to_string());
+
+    let (negative, digits) = match operand.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, operand),
+    };
+
+    let magnitude = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse::<u64>(),
+    }
+    .map_err(|_| invalid())?;
+
+    match (negative, magnitude) {
+        (true, m) if m <= 1 << 63 => Ok((m as i64).wrapping_neg()),
+        (false, m) if m < 1 << 63 => Ok(m as i64),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_register(operand: &str) -> AssemblerResult<Register> {
+    const ABI_NAMES: [&str; 32] = [
+        "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+        "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+        "t5", "t6",
+    ];
+
+    let index = match operand {
+        "fp" => Some(8),
+        _ => match operand.strip_prefix('x') {
+            Some(number) if !number.starts_with('+') => {
+                number.parse::<u32>().ok().filter(|n| *n < 32)
+            }
+            _ => ABI_NAMES
+                .iter()
+                .position(|name| *name == operand)
+                .map(|n| n as u32),
+        },
+    };
+
+    index
+        .map(Register::from)
+        .ok_or_else(|| AssemblerErrorKind::InvalidRegister(operand.to_string()))
+}
+
+/// Parse a memory operand of the form `imm(rs1)`, where the immediate is optional.
+fn parse_memory(operand: &str) -> AssemblerResult<(i32, Register)> {
+    let invalid = || AssemblerErrorKind::InvalidOperand(operand.to_string());
+
+    let open = operand.find('(').ok_or_else(invalid)?;
+
+    if !operand.ends_with(')') {
+        return Err(invalid());
+    }
+
+    let offset = match operand[..open].trim() {
+        "" => 0,
+        offset => immediate(parse_number(offset)?)?,
+    };
+
+    let base = parse_register(operand[open + 1..operand.len() - 1].trim())?;
+
+    Ok((offset, base))
+}
+
+/// Parse an address operand of the form `(rs1)` as used by atomic instructions.
+fn parse_address(operand: &str) -> AssemblerResult<Register> {
+    match parse_memory(operand)? {
+        (0, base) if operand.starts_with('(') => Ok(base),
+        _ => Err(AssemblerErrorKind::InvalidOperand(operand.to_string())),
+    }
+}
+
+/// Resolve a branch or jump target to an offset relative to `address`.
+fn parse_target(
+    operand: &str,
+    address: u64,
+    labels: &HashMap<String, u64>,
+) -> AssemblerResult<i32> {
+    let target = if let Some(target) = labels.get(operand) {
+        *target
+    } else if let Some(offset) = operand
+        .strip_prefix('.')
+        .filter(|o| o.starts_with('+') || o.starts_with('-'))
+    {
+        let offset = parse_number(offset.strip_prefix('+').unwrap_or(offset))?;
+        address.wrapping_add(offset as u64)
+    } else if operand.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+        parse_number(operand)? as u64
+    } else {
+        return Err(AssemblerErrorKind::UndefinedLabel(operand.to_string()));
+    };
+
+    if target & 0b1 != 0 {
+        return Err(AssemblerErrorKind::MisalignedTarget(target));
+    }
+
+    immediate(target.wrapping_sub(address) as i64)
+}
+
+/// Immediates are range checked by [`Instruction::from_parts`], which takes them as `i32`.
+fn immediate(value: i64) -> AssemblerResult<i32> {
+    i32::try_from(value).map_err(|_| AssemblerErrorKind::ImmediateOutOfRange(value))
+}
+
+/// An operand in the assembly syntax of an instruction.
+#[derive(Clone, Copy)]
+enum Syntax {
+    /// An integer register in the given field
+    X(Field),
+    /// A floating-point register in the given field
+    F(Field),
+    /// An immediate
+    Imm,
+    /// `imm(rs1)`
+    Memory,
+    /// `(rs1)` of atomic instructions
+    Address,
+    /// A branch or jump target
+    Target,
+    /// A CSR by its name or address
+    Csr,
+    /// The unsigned 5 bit immediate of CSR instructions, which is stored in `rs1`
+    Zimm,
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Rd,
+    Rs1,
+    Rs2,
+    Rs3,
+}
+
+impl Field {
+    fn of(self, operands: &mut Operands) -> &mut u8 {
+        match self {
+            Field::Rd => &mut operands.rd,
+            Field::Rs1 => &mut operands.rs1,
+            Field::Rs2 => &mut operands.rs2,
+            Field::Rs3 => &mut operands.rs3,
+        }
+    }
+}
+
+/// The operand syntax of instructions with this opcode and whether it ends with an optional
+/// rounding mode.
+fn syntax(opcode: Opcode) -> (&'static [Syntax], bool) {
+    use Constructor::*;
+    use Field::*;
+    use Syntax::*;
+
+    // an instance with all operands zero, which tells apart e.g. loads from other immediates
+    let class = Instruction::from_parts(opcode, Operands::default())
+        .expect("zero is a valid value of all operands")
+        .kind();
+
+    match opcode.constructor() {
+        Register(_) if matches!(opcode, Opcode::Lrw | Opcode::Lrd) => (&[X(Rd), Address], false),
+        Register(_) if class == InstructionClass::Atomic => (&[X(Rd), X(Rs2), Address], false),
+        Register(_) => (&[X(Rd), X(Rs1), X(Rs2)], false),
+        Immediate(_, _) if class == InstructionClass::Load => (&[X(Rd), Memory], false),
+        Immediate(_, _) => (&[X(Rd), X(Rs1), Imm], false),
+        Unary(_) => (&[X(Rd), X(Rs1)], false),
+        SourcesImmediate(_, _) if class == InstructionClass::Branch => {
+            (&[X(Rs1), X(Rs2), Target], false)
+        }
+        SourcesImmediate(_, _) => (&[X(Rs2), Memory], false),
+        Sources(_) => (&[X(Rs1), X(Rs2)], false),
+        Upper(_, _) if opcode == Opcode::Jal => (&[X(Rd), Target], false),
+        Upper(_, _) => (&[X(Rd), Imm], false),
+        Nullary(_) => (&[], false),
+        CsrRegister(_) => (&[X(Rd), Csr, X(Rs1)], false),
+        CsrImmediate(_) => (&[X(Rd), Csr, Zimm], false),
+        FloatLoad(_) => (&[F(Rd), Memory], false),
+        FloatStore(_) => (&[F(Rs2), Memory], false),
+        FusedMultiplyAdd(_) => (&[F(Rd), F(Rs1), F(Rs2), F(Rs3)], true),
+        FloatRounded(_) => (&[F(Rd), F(Rs1), F(Rs2)], true),
+        Float(_) => (&[F(Rd), F(Rs1), F(Rs2)], false),
+        FloatUnaryRounded(_) => (&[F(Rd), F(Rs1)], true),
+        FloatUnary(_) => (&[F(Rd), F(Rs1)], false),
+        FloatToIntRounded(_) => (&[X(Rd), F(Rs1)], true),
+        IntToFloatRounded(_) => (&[F(Rd), X(Rs1)], true),
+        FloatCompare(_) => (&[X(Rd), F(Rs1), F(Rs2)], false),
+        FloatToInt(_) => (&[X(Rd), F(Rs1)], false),
+        IntToFloat(_) => (&[F(Rd), X(Rs1)], false),
+    }
+}
+
+/// Split the memory ordering suffix (`.aq`, `.rl` or `.aqrl`) off a mnemonic and return the
+/// corresponding aq and rl bits.
+fn split_ordering(mnemonic: &str) -> (&str, bool, bool) {
+    [
+        (".aqrl", true, true),
+        (".aq", true, false),
+        (".rl", false, true),
+    ]
+    .iter()
+    .find_map(|(suffix, aq, rl)| mnemonic.strip_suffix(suffix).map(|m| (m, *aq, *rl)))
+    .unwrap_or((mnemonic, false, false))
+}
+
+fn assemble_instruction(
+    mnemonic: &str,
+    operands: &[&str],
+    address: u64,
+    labels: &HashMap<String, u64>,
+) -> AssemblerResult<Instruction> {
+    let unknown = || AssemblerErrorKind::UnknownMnemonic(mnemonic.to_string());
+
+    let (base_mnemonic, aq, rl) = split_ordering(mnemonic);
+    let opcode = base_mnemonic.parse::<Opcode>().map_err(|_| unknown())?;
+
+    // the shorthands of the GNU assembler without a link register or with the offset as operand
+    let parts = match (opcode, operands) {
+        (Opcode::Jal, [target]) => parse_operands(opcode, &["ra", target], address, labels)?,
+        (Opcode::Jalr, [base]) => parse_operands(opcode, &["ra", base, "0"], address, labels)?,
+        (Opcode::Jalr, [rd, target]) => {
+            let (offset, base) = parse_memory(target)?;
+
+            Operands {
+                rd: register_number(parse_register(rd)?),
+                rs1: register_number(base),
+                imm: offset,
+                ..Operands::default()
+            }
+        }
+        (Opcode::Fence, _) => Operands {
+            imm: parse_fence(operands)?,
+            ..Operands::default()
+        },
+        _ => parse_operands(opcode, operands, address, labels)?,
+    };
+
+    Instruction::from_parts(opcode, Operands { aq, rl, ..parts }).map_err(|error| match error {
+        OperandError::ImmediateOutOfRange { imm, .. } => {
+            AssemblerErrorKind::ImmediateOutOfRange(imm.into())
+        }
+        OperandError::InvalidRegister(n) => AssemblerErrorKind::InvalidRegister(n.to_string()),
+        OperandError::UnexpectedOrdering(_) => unknown(),
+    })
+}
+
+/// Parse the operands following the [`syntax`] of the opcode.
+fn parse_operands(
+    opcode: Opcode,
+    operands: &[&str],
+    address: u64,
+    labels: &HashMap<String, u64>,
+) -> AssemblerResult<Operands> {
+    let (syntax, rounding) = syntax(opcode);
+
+    let (operands, rm) = match operands.split_last() {
+        Some((rm, rest)) if rounding && rest.len() == syntax.len() => {
+            (rest, parse_rounding_mode(rm)?)
+        }
+        _ => (operands, RoundingMode::Dyn),
+    };
+
+    if operands.len() != syntax.len() {
+        return Err(AssemblerErrorKind::OperandCount {
+            expected: syntax.len(),
+            found: operands.len(),
+        });
+    }
+
+    let mut parts = Operands {
+        rm,
+        ..Operands::default()
+    };
+
+    for (syntax, operand) in syntax.iter().zip(operands.iter()) {
+        match syntax {
+            Syntax::X(field) => *field.of(&mut parts) = register_number(parse_register(operand)?),
+            Syntax::F(field) => *field.of(&mut parts) = parse_fregister(operand)?,
+            Syntax::Imm => parts.imm = immediate(parse_number(operand)?)?,
+            Syntax::Memory => {
+                let (offset, base) = parse_memory(operand)?;
+                parts.imm = offset;
+                parts.rs1 = register_number(base);
+            }
+            Syntax::Address => parts.rs1 = register_number(parse_address(operand)?),
+            Syntax::Target => parts.imm = parse_target(operand, address, labels)?,
+            Syntax::Csr => parts.imm = parse_csr(operand)?,
+            Syntax::Zimm => parts.rs1 = parse_zimm(operand)?,
+        }
+    }
+
+    Ok(parts)
+}
+
+fn register_number(register: Register) -> u8 {
+    u32::from(register) as u8
+}
+
+fn parse_fregister(operand: &str) -> AssemblerResult<u8> {
+    let index = match operand.strip_prefix('f') {
+        Some(number) if number.starts_with(|c: char| c.is_ascii_digit()) => {
+            number.parse::<u8>().ok().filter(|n| *n < 32)
+        }
+        _ => (0..32).find(|n| FRegister::from(u32::from(*n)).to_string() == operand),
+    };
+
+    index.ok_or_else(|| AssemblerErrorKind::InvalidRegister(operand.to_string()))
+}
+
+fn parse_rounding_mode(operand: &str) -> AssemblerResult<RoundingMode> {
+    (0..8)
+        .filter_map(|raw| RoundingMode::try_from(raw).ok())
+        .find(|rm| rm.to_string() == operand)
+        .ok_or_else(|| AssemblerErrorKind::InvalidOperand(operand.to_string()))
+}
+
+fn parse_csr(operand: &str) -> AssemblerResult<i32> {
+    match Csr::from_name(operand) {
+        Some(csr) => Ok(u32::from(csr) as i32),
+        None => immediate(parse_number(operand)?),
+    }
+}
+
+fn parse_zimm(operand: &str) -> AssemblerResult<u8> {
+    match parse_number(operand)? {
+        zimm if (0..32).contains(&zimm) => Ok(zimm as u8),
+        zimm => Err(AssemblerErrorKind::ImmediateOutOfRange(zimm)),
+    }
+}
+
+/// Parse the predecessor and successor set of a fence, which are both `iorw` if omitted.
+fn parse_fence(operands: &[&str]) -> AssemblerResult<i32> {
+    match operands {
+        [] => Ok(0b1111_1111),
+        [predecessor, successor] => {
+            Ok((parse_fence_set(predecessor)? << 4) | parse_fence_set(successor)?)
+        }
+        _ => Err(AssemblerErrorKind::OperandCount {
+            expected: 2,
+            found: operands.len(),
+        }),
+    }
+}
+
+fn parse_fence_set(operand: &str) -> AssemblerResult<i32> {
+    if operand == "0" {
+        return Ok(0);
+    }
+
+    let mut set = 0;
+    let mut remaining = operand;
+
+    for (bit, c) in "iorw".chars().enumerate() {
+        if let Some(rest) = remaining.strip_prefix(c) {
+            set |= 0b1000 >> bit;
+            remaining = rest;
+        }
+    }
+
+    if set == 0 || !remaining.is_empty() {
+        return Err(AssemblerErrorKind::InvalidOperand(operand.to_string()));
+    }
+
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode;
+    use crate::Register::*;
+
+    fn words(program: &Program) -> Vec<u32> {
+        program
+            .code
+            .content
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect()
+    }
+
+    #[test]
+    fn assemble_riscu() {
+        let program = assemble(
+            "
+            # a small RISC-U program
+            _start:
+                lui t0, 0x32
+                addi a0, sp, 8       # comment after an instruction
+                ld a3, 24(a0)
+                sd ra, 0(sp)
+                add a2, a1, a2
+                sub x10, x10, x11
+                mul ra, ra, ra
+                divu t5, ra, sp
+                remu t5, ra, sp
+                sltu t5, ra, sp
+            loop: beq a5, zero, loop
+                jal ra, _start
+                jalr zero, 0(ra)
+                ecall
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            words(&program),
+            vec![
+                0x000322b7, 0x00810513, 0x01853683, 0x00113023, 0x00c58633, 0x40b50533, 0x021080b3,
+                0x0220df33, 0x0220ff33, 0x0020bf33, 0x00078063, 0xfd5ff0ef, 0x00008067, 0x00000073,
+            ]
+        );
+
+        assert_eq!(program.address_of("_start"), Some(DEFAULT_CODE_ADDRESS));
+        assert_eq!(program.address_of("loop"), Some(DEFAULT_CODE_ADDRESS + 40));
+    }
+
+    #[test]
+    fn assemble_rv64ima() {
+        let program = assemble(
+            "
+                slli a5, a5, 0x3
+                srai s0, fp, 63
+                addiw a5, a5, -1
+                auipc a0, 0x1
+                fence iorw, iorw
+                fence rw, rw
+                lr.w a3, (a0)
+                sc.w a4, a2, (a0)
+                amoswap.d.aq a3, a1, (a3)
+                ebreak
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            words(&program),
+            vec![
+                0x00379793, 0x43f45413, 0xfff7879b, 0x00001517, 0x0ff0000f, 0x0330000f, 0x100526af,
+                0x18c5272f, 0x0cb6b6af, 0x00100073,
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_targets() {
+        let program = assemble(
+            "
+                beq a0, a1, .+8
+                bne a0, a1, 0x10000
+                jal zero, .-8
+                jal end
+            end:
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            words(&program),
+            vec![
+                u32::from(Instruction::new_beq(A0, A1, 8)),
+                u32::from(Instruction::new_bne(A0, A1, -4)),
+                u32::from(Instruction::new_jal(Zero, -8)),
+                u32::from(Instruction::new_jal(Ra, 4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn disassembly_can_be_assembled() {
+        let source = [
+            0x000322b7, 0x00810513, 0x01853683, 0x00113023, 0x00078463, 0xfe1ff06f, 0x00008067,
+            0x43f45413, 0x0ff0000f, 0x0cb6b6af, 0x00000073,
+        ]
+        .iter()
+        .enumerate()
+        .map(|(n, i)| {
+            decode(*i)
+                .unwrap()
+                .disassemble(DEFAULT_CODE_ADDRESS + 4 * n as u64)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        let program = assemble(&source).unwrap();
+
+        assert_eq!(
+            words(&program),
+            vec![
+                0x000322b7, 0x00810513, 0x01853683, 0x00113023, 0x00078463, 0xfe1ff06f, 0x00008067,
+                0x43f45413, 0x0ff0000f, 0x0cb6b6af, 0x00000073,
+            ]
+        );
+    }
+
+    #[test]
+    fn disassembly_of_extensions_can_be_assembled() {
+        let instructions = [
+            // system
+            0x30002573, 0x30551073, 0xc00027f3, 0x30046073, 0x7c051073, 0x0000100f, 0x30200073,
+            0x10200073, 0x10500073, 0x12000073, 0x12b50073, // bit manipulation
+            0x08b5053b, 0x20b52533, 0x0a05151b, 0x40b57533, 0x60059513, 0x0805c53b, 0x61f5551b,
+            0x28755513, 0x6b855513, 0x0ab51533, 0x2bf51513, // floating-point
+            0x00452507, 0x00813427, 0x6ac5f543, 0x6ac5a543, 0x02b57553, 0x22b58553, 0xa2b52553,
+            0xc2051553, 0xd2078753, 0x42050553, 0x40157553, 0xe2050553, 0xf2078753,
+        ];
+
+        let source = instructions
+            .iter()
+            .map(|i| decode(*i).unwrap().disassemble(DEFAULT_CODE_ADDRESS))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert_eq!(words(&assemble(&source).unwrap()), instructions.to_vec());
+    }
+
+    #[test]
+    fn operand_syntax() {
+        for opcode in Opcode::ALL.iter() {
+            syntax(*opcode);
+        }
+
+        let program = assemble(
+            "
+                fadd.s f10, f11, f12, rtz
+                csrrw a0, 0x7c0, a1
+                jalr t0
+                jalr ra, t0, 8
+                amoadd.w.aqrl a0, a1, (a2)
+            ",
+        )
+        .unwrap();
+
+        let mut expected = [
+            Instruction::new_fadds(
+                FRegister::Fa0,
+                FRegister::Fa1,
+                FRegister::Fa2,
+                RoundingMode::Rtz,
+            ),
+            Instruction::new_csrrw(A0, Csr::from(0x7c0), A1),
+            Instruction::new_jalr(Ra, T0, 0),
+            Instruction::new_jalr(Ra, T0, 8),
+        ]
+        .iter()
+        .map(|i| u32::from(*i))
+        .collect::<Vec<_>>();
+        expected.push(0x06b6252f); // amoadd.w.aqrl a0,a1,(a2)
+
+        assert_eq!(words(&program), expected);
+
+        let error = |source: &str| assemble(source).unwrap_err().kind;
+
+        assert_eq!(
+            error("add.aq a0, a1, a2"),
+            AssemblerErrorKind::UnknownMnemonic("add.aq".to_string())
+        );
+        assert_eq!(
+            error("fadd.d fa0, fa1, fa2, up"),
+            AssemblerErrorKind::InvalidOperand("up".to_string())
+        );
+        assert_eq!(
+            error("fadd.d fa0, fa1, a2"),
+            AssemblerErrorKind::InvalidRegister("a2".to_string())
+        );
+        assert_eq!(
+            error("fsgnj.d fa0, fa1, fa2, rne"),
+            AssemblerErrorKind::OperandCount {
+                expected: 3,
+                found: 4
+            }
+        );
+        assert_eq!(
+            error("csrrwi a0, mstatus, 32"),
+            AssemblerErrorKind::ImmediateOutOfRange(32)
+        );
+        assert_eq!(
+            error("csrrw a0, 0x1000, a1"),
+            AssemblerErrorKind::ImmediateOutOfRange(0x1000)
+        );
+        assert_eq!(
+            error("slli a0, a1, 64"),
+            AssemblerErrorKind::ImmediateOutOfRange(64)
+        );
+    }
+
+    #[test]
+    fn data_directives() {
+        let program = assemble(
+            "
+                ld a0, 0(gp)
+            .data
+            bytes: .byte 1, 0xff
+            .align 2
+            words: .word -1, 0x12345678
+                .half 0x0201
+                .zero 2
+            pointer: .dword bytes, -2
+                .ascii \"ab\\n\", \"#c\" # a comment
+                .asciz \"\"
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(program.data.address, DEFAULT_CODE_ADDRESS + PAGE_SIZE);
+        assert_eq!(program.address_of("bytes"), Some(program.data.address));
+        assert_eq!(program.address_of("words"), Some(program.data.address + 4));
+        assert_eq!(
+            program.address_of("pointer"),
+            Some(program.data.address + 16)
+        );
+
+        let mut expected = vec![
+            1, 0xff, 0, 0, 0xff, 0xff, 0xff, 0xff, 0x78, 0x56, 0x34, 0x12,
+        ];
+        expected.extend_from_slice(&[1, 2, 0, 0]);
+        expected.extend_from_slice(&program.data.address.to_le_bytes());
+        expected.extend_from_slice(&(-2_i64).to_le_bytes());
+        expected.extend_from_slice(b"ab\n#c\0");
+
+        assert_eq!(program.data.content, expected);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source: &str| assemble(source).unwrap_err();
+
+        assert_eq!(
+            error("nop"),
+            AssemblerError {
+                line: 1,
+                kind: AssemblerErrorKind::UnknownMnemonic("nop".to_string())
+            }
+        );
+        assert_eq!(
+            error("\naddi a0, a1, 2048").kind,
+            AssemblerErrorKind::ImmediateOutOfRange(2048)
+        );
+        assert_eq!(
+            error("add a0, a1, x32").kind,
+            AssemblerErrorKind::InvalidRegister("x32".to_string())
+        );
+        assert_eq!(
+            error("add a0, a1").kind,
+            AssemblerErrorKind::OperandCount {
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!(
+            error("beq a0, a1, nowhere").kind,
+            AssemblerErrorKind::UndefinedLabel("nowhere".to_string())
+        );
+        assert_eq!(
+            error("a: a: ecall").kind,
+            AssemblerErrorKind::DuplicateLabel("a".to_string())
+        );
+        assert_eq!(
+            error(".byte 1\necall").kind,
+            AssemblerErrorKind::MisalignedInstruction
+        );
+        assert_eq!(
+            error(".quad 1").kind,
+            AssemblerErrorKind::UnknownDirective(".quad".to_string())
+        );
+    }
+
+    #[test]
+    fn address_space_exceeded() {
+        let error = |source: &str| assemble(source).unwrap_err();
+
+        assert_eq!(
+            error(".data\n.zero 9223372036854775807"),
+            AssemblerError {
+                line: 2,
+                kind: AssemblerErrorKind::AddressSpaceExceeded
+            }
+        );
+        assert_eq!(
+            error(".zero 0x80000000\n.data\n.zero 0x80000000"),
+            AssemblerError {
+                line: 3,
+                kind: AssemblerErrorKind::AddressSpaceExceeded
+            }
+        );
+        assert_eq!(
+            assemble_at("ecall", u64::MAX - 2).unwrap_err().kind,
+            AssemblerErrorKind::AddressSpaceExceeded
+        );
+
+        let program = assemble(".data\n.zero 0x1000").unwrap();
+
+        assert_eq!(program.data.content, vec![0; 0x1000]);
+    }
+
+    #[test]
+    fn align_at_unaligned_code_address() {
+        let program = assemble_at(
+            "addi a0, zero, 1\n.align 3\ntarget: jal zero, target",
+            0x10004,
+        )
+        .unwrap();
+
+        assert_eq!(program.code.content.len(), 12);
+        assert_eq!(
+            program
+                .symbols
+                .iter()
+                .find(|s| s.name == "target")
+                .unwrap()
+                .address,
+            0x1000c
+        );
+        assert_eq!(
+            program.code.content[8..],
+            u32::from(Instruction::new_jal(Zero, 0)).to_le_bytes()
+        );
+        assert_eq!(program.data.address, 0x11004);
+    }
+}
@@ -0,0 +1,416 @@
+//! # Function discovery and call graphs
+//!
+//! Binaries generated by Selfie have no symbol table, so functions are recovered from the code:
+//! a function starts at the beginning of the code, at the target of every direct call and at
+//! every prologue which allocates a stack frame and saves the return address (`addi sp,sp,-N`
+//! followed by `sd ra,...(sp)`), which finds functions that are never called directly. The blocks
+//! of a function are those reachable from its entry in the [`Cfg`] without following calls or
+//! entering another function.
+//!
+//! ```
+//! use riscu::{assemble::assemble, callgraph::CallGraph};
+//!
+//! let program = assemble(
+//!     "
+//!         jal ra, main
+//!         ecall
+//!     main:
+//!         addi sp, sp, -8
+//!         sd ra, 0(sp)
+//!         ld ra, 0(sp)
+//!         addi sp, sp, 8
+//!         jalr zero, 0(ra)
+//!     ",
+//! )
+//! .unwrap()
+//! .decode()
+//! .unwrap();
+//!
+//! let call_graph = CallGraph::new(&program);
+//!
+//! assert_eq!(call_graph.functions().len(), 2);
+//! assert_eq!(call_graph.functions()[0].callees, vec![0x10008]);
+//! assert_eq!(call_graph.function_containing(0x10010).unwrap().entry, 0x10008);
+//! ```
+
+use crate::{
+    cfg::{BlockId, Cfg, EdgeKind},
+    iterators::DecodedInstruction,
+    DecodedProgram, Instruction, Register,
+};
+use core::fmt::Write;
+use std::collections::BTreeSet;
+
+/// A function recovered from the code.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Function {
+    /// The address of the first instruction.
+    pub entry: u64,
+    /// The blocks of the function in the [`Cfg`] of its call graph, ordered by address.
+    pub blocks: Vec<BlockId>,
+    /// The entries of all functions calling this function, ordered by address.
+    pub callers: Vec<u64>,
+    /// The entries of all functions called by this function, including jumps to the entry of
+    /// another function (tail calls), ordered by address.
+    pub callees: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CallGraph {
+    cfg: Cfg,
+    functions: Vec<Function>,
+}
+
+impl CallGraph {
+    /// Recover the functions of `program` and the calls between them.
+    pub fn new(program: &DecodedProgram) -> CallGraph {
+        let instructions = program
+            .try_iter_instructions()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+
+        // functions which are never called directly may follow the previous one without a block
+        // boundary, e.g. after the `ecall` of the exit syscall
+        let prologues = instructions
+            .windows(2)
+            .filter(|pair| pair[0].next_address() == pair[1].address && is_prologue(pair))
+            .map(|pair| pair[0].address);
+
+        CallGraph::from_cfg(Cfg::with_leaders(program, prologues))
+    }
+
+    /// Recover the functions from an already built control-flow graph. Only prologues at the
+    /// start of a block are found, see [`Cfg::with_leaders`].
+    pub fn from_cfg(cfg: Cfg) -> CallGraph {
+        let entries = find_entries(&cfg);
+        let entry_blocks = entries
+            .iter()
+            .filter_map(|entry| cfg.block_starting_at(*entry))
+            .collect::<BTreeSet<_>>();
+
+        let mut functions = entries
+            .iter()
+            .filter_map(|entry| cfg.block_starting_at(*entry))
+            .map(|entry| {
+                let mut blocks = BTreeSet::new();
+                let mut callees = BTreeSet::new();
+                let mut worklist = vec![entry];
+
+                while let Some(id) = worklist.pop() {
+                    if !blocks.insert(id) {
+                        continue;
+                    }
+
+                    for edge in cfg.successors(id) {
+                        let enters_other = edge.to != entry && entry_blocks.contains(&edge.to);
+
+                        match edge.kind {
+                            EdgeKind::Call => {}
+                            // jumping or branching to another function is a tail call
+                            EdgeKind::Jump | EdgeKind::Branch if enters_other => {}
+                            _ if enters_other => continue,
+                            _ => {
+                                worklist.push(edge.to);
+                                continue;
+                            }
+                        }
+
+                        callees.insert(cfg.block(edge.to).start());
+                    }
+                }
+
+                Function {
+                    entry: cfg.block(entry).start(),
+                    blocks: blocks.into_iter().collect(),
+                    callers: Vec::new(),
+                    callees: callees.into_iter().collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let calls = functions
+            .iter()
+            .flat_map(|caller| {
+                caller
+                    .callees
+                    .iter()
+                    .map(move |callee| (*callee, caller.entry))
+            })
+            .collect::<BTreeSet<_>>();
+
+        for (callee, caller) in calls {
+            if let Ok(index) = functions.binary_search_by_key(&callee, |f| f.entry) {
+                functions[index].callers.push(caller);
+            }
+        }
+
+        CallGraph { cfg, functions }
+    }
+
+    /// The control-flow graph the functions refer to.
+    pub fn cfg(&self) -> &Cfg {
+        &self.cfg
+    }
+
+    /// All functions ordered by their entry.
+    pub fn functions(&self) -> &[Function] {
+        &self.functions
+    }
+
+    /// The function starting at `entry`.
+    pub fn function(&self, entry: u64) -> Option<&Function> {
+        self.functions
+            .binary_search_by_key(&entry, |f| f.entry)
+            .ok()
+            .map(|index| &self.functions[index])
+    }
+
+    /// The function which contains the instruction at `address`. If the instruction is shared by
+    /// several functions, the one with the highest entry before `address` is preferred.
+    pub fn function_containing(&self, address: u64) -> Option<&Function> {
+        let block = self.cfg.block_at(address)?;
+
+        self.functions
+            .iter()
+            .filter(|f| f.blocks.binary_search(&block).is_ok())
+            .max_by_key(|f| (f.entry <= address, f.entry))
+    }
+
+    /// Render the call graph in the DOT language of Graphviz.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+
+        self.write_dot(&mut dot).expect("writing to a string");
+
+        dot
+    }
+
+    fn write_dot(&self, f: &mut String) -> core::fmt::Result {
+        writeln!(f, "digraph callgraph {{")?;
+        writeln!(f, "    node [shape=box, fontname=\"monospace\"];")?;
+
+        for function in self.functions.iter() {
+            writeln!(f, "    \"{:#x}\";", function.entry)?;
+        }
+
+        for function in self.functions.iter() {
+            for callee in function.callees.iter() {
+                writeln!(f, "    \"{:#x}\" -> \"{:#x}\";", function.entry, callee)?;
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+
+    /// Render the functions as JSON: an object with a `functions` array, in which every function
+    /// has its `entry`, the start addresses of its `blocks` and the entries of its `callers` and
+    /// `callees`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+
+        self.write_json(&mut json).expect("writing to a string");
+
+        json
+    }
+
+    fn write_json(&self, f: &mut String) -> core::fmt::Result {
+        fn array(f: &mut String, values: impl Iterator<Item = u64>) -> core::fmt::Result {
+            write!(f, "[")?;
+
+            for (index, value) in values.enumerate() {
+                if index > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", value)?;
+            }
+
+            write!(f, "]")
+        }
+
+        write!(f, "{{\"functions\":[")?;
+
+        for (index, function) in self.functions.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+
+            write!(f, "{{\"entry\":{},\"blocks\":", function.entry)?;
+            array(
+                f,
+                function.blocks.iter().map(|id| self.cfg.block(*id).start()),
+            )?;
+            write!(f, ",\"callers\":")?;
+            array(f, function.callers.iter().copied())?;
+            write!(f, ",\"callees\":")?;
+            array(f, function.callees.iter().copied())?;
+            write!(f, "}}")?;
+        }
+
+        write!(f, "]}}")
+    }
+}
+
+impl DecodedProgram {
+    /// Recover the functions of the code, see [`CallGraph::new`].
+    pub fn call_graph(&self) -> CallGraph {
+        CallGraph::new(self)
+    }
+}
+
+/// The start of the code, the targets of all calls and all blocks starting with a prologue.
+fn find_entries(cfg: &Cfg) -> BTreeSet<u64> {
+    let mut entries = BTreeSet::new();
+
+    let blocks = cfg.blocks();
+
+    if let Some(first) = blocks.first() {
+        entries.insert(first.start());
+    }
+
+    for edge in cfg.edges().filter(|edge| edge.kind == EdgeKind::Call) {
+        entries.insert(blocks[edge.to].start());
+    }
+
+    for block in blocks.iter().filter(|b| is_prologue(&b.instructions)) {
+        entries.insert(block.start());
+    }
+
+    entries
+}
+
+/// Returns true for `addi sp,sp,-N` followed by a store of `ra` relative to `sp`.
+fn is_prologue(instructions: &[DecodedInstruction]) -> bool {
+    match instructions {
+        [first, second, ..] => {
+            let allocates = matches!(
+                first.instruction,
+                Instruction::Addi(i)
+                    if i.rd() == Register::Sp && i.rs1() == Register::Sp && i.imm() < 0
+            );
+            let saves_ra = matches!(
+                second.instruction,
+                Instruction::Sd(s) | Instruction::Sw(s)
+                    if s.rs1() == Register::Sp && s.rs2() == Register::Ra
+            );
+
+            allocates && saves_ra
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assemble::assemble;
+
+    fn call_graph(source: &str) -> CallGraph {
+        assemble(source).unwrap().decode().unwrap().call_graph()
+    }
+
+    const PROGRAM: &str = "
+        _start:
+            jal ra, main
+            addi a7, zero, 93
+            ecall
+        unused:
+            addi sp, sp, -8
+            sd ra, 0(sp)
+            jal ra, leaf
+            ld ra, 0(sp)
+            addi sp, sp, 8
+            jalr zero, 0(ra)
+        main:
+            addi sp, sp, -8
+            sd ra, 0(sp)
+            jal ra, leaf
+            beq a0, zero, skip
+            jal ra, leaf
+        skip:
+            ld ra, 0(sp)
+            addi sp, sp, 8
+            jalr zero, 0(ra)
+        leaf:
+            addi a0, zero, 1
+            jal zero, tail
+        tail:
+            jalr zero, 0(ra)
+        ";
+
+    #[test]
+    fn functions() {
+        let call_graph = call_graph(PROGRAM);
+
+        let summary = call_graph
+            .functions()
+            .iter()
+            .map(|f| (f.entry, f.callers.clone(), f.callees.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                (0x10000, vec![], vec![0x10024]),
+                (0x1000c, vec![], vec![0x10044]),
+                (0x10024, vec![0x10000], vec![0x10044]),
+                (0x10044, vec![0x1000c, 0x10024], vec![]),
+            ]
+        );
+
+        let main = call_graph.function(0x10024).unwrap();
+        let starts = main
+            .blocks
+            .iter()
+            .map(|id| call_graph.cfg().block(*id).start())
+            .collect::<Vec<_>>();
+
+        assert_eq!(starts, vec![0x10024, 0x10030, 0x10034, 0x10038]);
+
+        // the exit of _start does not fall through into the next function
+        let start = call_graph.function(0x10000).unwrap();
+
+        assert_eq!(start.blocks, vec![0, 1]);
+        assert!(call_graph.cfg().successors(1).is_empty());
+        assert_eq!(
+            call_graph.function_containing(0x10038).unwrap().entry,
+            0x10024
+        );
+        assert_eq!(
+            call_graph.function_containing(0x10008).unwrap().entry,
+            0x10000
+        );
+        assert_eq!(
+            call_graph.function_containing(0x1004c).unwrap().entry,
+            0x10044
+        );
+    }
+
+    #[test]
+    fn exports() {
+        let call_graph = call_graph(
+            "
+                jal ra, f
+                jalr zero, 0(ra)
+            f:
+                jalr zero, 0(ra)
+            ",
+        );
+
+        assert_eq!(
+            call_graph.to_dot(),
+            "digraph callgraph {
+    node [shape=box, fontname=\"monospace\"];
+    \"0x10000\";
+    \"0x10008\";
+    \"0x10000\" -> \"0x10008\";
+}
+"
+        );
+        assert_eq!(
+            call_graph.to_json(),
+            "{\"functions\":[\
+             {\"entry\":65536,\"blocks\":[65536,65540],\"callers\":[],\"callees\":[65544]},\
+             {\"entry\":65544,\"blocks\":[65544],\"callers\":[65536],\"callees\":[]}]}"
+        );
+    }
+}
@@ -0,0 +1,640 @@
+//! # Control-flow graphs
+//!
+//! A [`Cfg`] splits the code of a [`DecodedProgram`] into basic blocks and connects them by the
+//! branches, jumps and calls at their ends. Blocks start at the beginning of the code, at every
+//! target of a direct branch, jump or call and after every instruction which transfers control.
+//!
+//! Calls (`jal ra` or `jal t0`) end a block with an edge to the entry of the callee and an edge to
+//! the following block, which is where the callee returns to. Returns (`jalr zero,0(ra)`) have no
+//! successors, and neither have `ecall`s of the `exit` system call, whose number is set by an
+//! `addi a7,zero,93` in the same block. Targets of indirect jumps are listed as
+//! [`UnresolvedJump`]s instead, as are direct targets which do not point to an instruction, unless
+//! they are passed to [`Cfg::with_targets`], e.g. as resolved by
+//! [`resolve_indirect_jumps`](crate::dataflow::resolve_indirect_jumps).
+//!
+//! ```
+//! use riscu::{assemble::assemble, cfg::Cfg};
+//!
+//! let program = assemble(
+//!     "
+//!         beq a0, zero, done
+//!         addi a0, a0, -1
+//!     done:
+//!         jalr zero, 0(ra)
+//!     ",
+//! )
+//! .unwrap()
+//! .decode()
+//! .unwrap();
+//!
+//! let cfg = Cfg::new(&program);
+//!
+//! assert_eq!(cfg.blocks().len(), 3);
+//! assert_eq!(cfg.successors(0).len(), 2);
+//! assert_eq!(cfg.predecessors(2).len(), 2);
+//! ```
+
+use crate::{
+    iterators::DecodedInstruction, ControlFlow, DecodedProgram, Instruction, Register,
+    SelfieSyscall,
+};
+use core::fmt::Write;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The index of a basic block in [`Cfg::blocks`].
+pub type BlockId = usize;
+
+/// A maximal sequence of instructions which is only entered at the first and only left at the
+/// last instruction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BasicBlock {
+    pub instructions: Vec<DecodedInstruction>,
+    pub successors: Vec<Edge>,
+    pub predecessors: Vec<Edge>,
+}
+
+impl BasicBlock {
+    /// The address of the first instruction.
+    pub fn start(&self) -> u64 {
+        self.instructions[0].address
+    }
+
+    /// The address after the last instruction.
+    pub fn end(&self) -> u64 {
+        self.last().next_address()
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        self.start() <= address && address < self.end()
+    }
+
+    /// The last instruction, which decides where execution continues.
+    pub fn last(&self) -> &DecodedInstruction {
+        self.instructions
+            .last()
+            .expect("basic blocks are never empty")
+    }
+
+    /// How execution continues after the last instruction of this block.
+    pub fn terminator(&self) -> ControlFlow {
+        let last = self.last();
+
+        last.instruction.control_flow(last.address)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Edge {
+    pub from: BlockId,
+    pub to: BlockId,
+    pub kind: EdgeKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EdgeKind {
+    /// To the block following in memory, either after a block which is split because its
+    /// successor is a target or after a branch which is not taken.
+    FallThrough,
+    /// A taken conditional branch.
+    Branch,
+    /// An unconditional direct jump, or an indirect one with a known target.
+    Jump,
+    /// From a call to the entry of the callee.
+    Call,
+    /// From a call to the block following it, where the callee returns to.
+    CallReturn,
+}
+
+impl EdgeKind {
+    /// Returns true if this edge stays within the calling function.
+    pub fn is_intraprocedural(&self) -> bool {
+        *self != EdgeKind::Call
+    }
+}
+
+/// A branch, jump or call at `address` whose target is not known or not an instruction.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct UnresolvedJump {
+    pub address: u64,
+    pub flow: ControlFlow,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cfg {
+    blocks: Vec<BasicBlock>,
+    unresolved: Vec<UnresolvedJump>,
+}
+
+impl Cfg {
+    /// Recover the basic blocks and edges of the code of `program`. Instructions which can not be
+    /// decoded end the preceding block without an edge to the next one.
+    pub fn new(program: &DecodedProgram) -> Cfg {
+        Cfg::with_leaders(program, core::iter::empty())
+    }
+
+    /// Like [`Cfg::new`], but additionally start blocks at the instructions at `leaders`, e.g.
+    /// at entries of functions which are only reached by falling through.
+    pub fn with_leaders(program: &DecodedProgram, leaders: impl IntoIterator<Item = u64>) -> Cfg {
+        Cfg::build(program, leaders, &BTreeMap::new())
+    }
+
+    /// Like [`Cfg::new`], but connect the indirect jumps, calls and returns in `targets`, by their
+    /// address, to the instruction at their target, which starts a block. Jumps whose target is
+    /// `None` or not an instruction are unresolved, including returns.
+    pub fn with_targets(program: &DecodedProgram, targets: &BTreeMap<u64, Option<u64>>) -> Cfg {
+        Cfg::build(program, targets.values().filter_map(|t| *t), targets)
+    }
+
+    fn build(
+        program: &DecodedProgram,
+        leaders: impl IntoIterator<Item = u64>,
+        targets: &BTreeMap<u64, Option<u64>>,
+    ) -> Cfg {
+        let instructions = program
+            .try_iter_instructions()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+
+        let is_instruction = |address: u64| {
+            instructions
+                .binary_search_by_key(&address, |i| i.address)
+                .is_ok()
+        };
+
+        let mut leaders = leaders
+            .into_iter()
+            .filter(|address| is_instruction(*address))
+            .collect::<BTreeSet<_>>();
+
+        for (index, decoded) in instructions.iter().enumerate() {
+            let contiguous = index > 0 && instructions[index - 1].next_address() == decoded.address;
+
+            if !contiguous {
+                leaders.insert(decoded.address);
+            }
+
+            let flow = decoded.instruction.control_flow(decoded.address);
+
+            if flow != ControlFlow::FallThrough {
+                leaders.insert(decoded.next_address());
+            }
+
+            if let Some(target) = flow.target().filter(|t| is_instruction(*t)) {
+                leaders.insert(target);
+            }
+        }
+
+        let exits = instructions
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| is_exit(&instructions, *index, &leaders))
+            .map(|(_, decoded)| decoded.address)
+            .collect::<BTreeSet<_>>();
+
+        leaders.extend(exits.iter().map(|address| address + 4));
+
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+
+        for decoded in instructions.iter() {
+            match blocks.last_mut() {
+                Some(block) if !leaders.contains(&decoded.address) => {
+                    block.instructions.push(*decoded)
+                }
+                _ => blocks.push(BasicBlock {
+                    instructions: vec![*decoded],
+                    successors: Vec::new(),
+                    predecessors: Vec::new(),
+                }),
+            }
+        }
+
+        let mut cfg = Cfg {
+            blocks,
+            unresolved: Vec::new(),
+        };
+
+        cfg.connect(&exits, targets);
+
+        cfg
+    }
+
+    fn connect(&mut self, exits: &BTreeSet<u64>, targets: &BTreeMap<u64, Option<u64>>) {
+        let mut edges = Vec::new();
+        let mut unresolved_jumps = Vec::new();
+
+        for (from, block) in self.blocks.iter().enumerate() {
+            let next = self
+                .blocks
+                .get(from + 1)
+                .filter(|next| next.start() == block.end())
+                .map(|_| from + 1);
+
+            let flow = block.terminator();
+            let address = block.last().address;
+            let indirect = targets.get(&address).copied();
+            let target = flow.target().or_else(|| indirect.flatten());
+
+            let mut edge = |to, kind| edges.push(Edge { from, to, kind });
+            let mut unresolved = || unresolved_jumps.push(UnresolvedJump { address, flow });
+
+            let (target_kind, next_kind) = match flow {
+                ControlFlow::FallThrough if exits.contains(&address) => (None, None),
+                ControlFlow::FallThrough => (None, Some(EdgeKind::FallThrough)),
+                ControlFlow::Branch { .. } => (Some(EdgeKind::Branch), Some(EdgeKind::FallThrough)),
+                ControlFlow::Jump { .. } => (Some(EdgeKind::Jump), None),
+                ControlFlow::Call { .. } => (Some(EdgeKind::Call), Some(EdgeKind::CallReturn)),
+                ControlFlow::IndirectJump { .. } | ControlFlow::Return if target.is_some() => {
+                    (Some(EdgeKind::Jump), None)
+                }
+                ControlFlow::IndirectJump { .. } => {
+                    unresolved();
+                    (None, None)
+                }
+                ControlFlow::IndirectCall { .. } if target.is_some() => {
+                    (Some(EdgeKind::Call), Some(EdgeKind::CallReturn))
+                }
+                ControlFlow::IndirectCall { .. } => {
+                    unresolved();
+                    (None, Some(EdgeKind::CallReturn))
+                }
+                ControlFlow::Return if indirect.is_some() => {
+                    unresolved();
+                    (None, None)
+                }
+                ControlFlow::Return | ControlFlow::TrapReturn => (None, None),
+            };
+
+            if let (Some(kind), Some(target)) = (target_kind, target) {
+                match self.block_starting_at(target) {
+                    Some(to) => edge(to, kind),
+                    None => unresolved(),
+                }
+            }
+
+            if let (Some(kind), Some(to)) = (next_kind, next) {
+                edge(to, kind);
+            }
+        }
+
+        self.unresolved = unresolved_jumps;
+
+        for edge in edges {
+            self.blocks[edge.from].successors.push(edge);
+            self.blocks[edge.to].predecessors.push(edge);
+        }
+    }
+
+    /// All basic blocks ordered by address. The first block is the one at the start of the code.
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    pub fn block(&self, id: BlockId) -> &BasicBlock {
+        &self.blocks[id]
+    }
+
+    /// The block which contains the instruction at `address`.
+    pub fn block_at(&self, address: u64) -> Option<BlockId> {
+        let index = self
+            .blocks
+            .partition_point(|block| block.start() <= address)
+            .checked_sub(1)?;
+
+        Some(index).filter(|index| self.blocks[*index].contains(address))
+    }
+
+    /// The block which starts at `address`.
+    pub fn block_starting_at(&self, address: u64) -> Option<BlockId> {
+        self.blocks
+            .binary_search_by_key(&address, |block| block.start())
+            .ok()
+    }
+
+    pub fn successors(&self, id: BlockId) -> &[Edge] {
+        &self.blocks[id].successors
+    }
+
+    pub fn predecessors(&self, id: BlockId) -> &[Edge] {
+        &self.blocks[id].predecessors
+    }
+
+    /// All edges ordered by their source block.
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> {
+        self.blocks.iter().flat_map(|block| block.successors.iter())
+    }
+
+    /// The indirect jumps and calls of the program and the direct ones whose target is not an
+    /// instruction, ordered by address.
+    pub fn unresolved(&self) -> &[UnresolvedJump] {
+        &self.unresolved
+    }
+
+    /// Render the graph in the DOT language of Graphviz. Every block is labeled with its
+    /// disassembly.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+
+        self.write_dot(&mut dot).expect("writing to a string");
+
+        dot
+    }
+
+    fn write_dot(&self, f: &mut String) -> core::fmt::Result {
+        writeln!(f, "digraph cfg {{")?;
+        writeln!(f, "    node [shape=box, fontname=\"monospace\"];")?;
+
+        for block in self.blocks.iter() {
+            write!(f, "    \"{:#x}\" [label=\"", block.start())?;
+
+            for decoded in block.instructions.iter() {
+                write!(
+                    f,
+                    "{:#x}: {}\\l",
+                    decoded.address,
+                    decoded.instruction.disassemble(decoded.address)
+                )?;
+            }
+
+            writeln!(f, "\"];")?;
+        }
+
+        for edge in self.edges() {
+            let style = match edge.kind {
+                EdgeKind::FallThrough => "",
+                EdgeKind::Branch => " [label=\"taken\"]",
+                EdgeKind::Jump => " [label=\"jump\"]",
+                EdgeKind::Call => " [label=\"call\", style=dashed]",
+                EdgeKind::CallReturn => " [style=dotted]",
+            };
+
+            writeln!(
+                f,
+                "    \"{:#x}\" -> \"{:#x}\"{};",
+                self.blocks[edge.from].start(),
+                self.blocks[edge.to].start(),
+                style
+            )?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+/// Returns true if the instruction at `index` is an `ecall` of `exit`, i.e. if the last write of
+/// `a7` before it, after the last leader, is an `addi a7,zero,93`.
+fn is_exit(instructions: &[DecodedInstruction], index: usize, leaders: &BTreeSet<u64>) -> bool {
+    if !matches!(instructions[index].instruction, Instruction::Ecall(_))
+        || leaders.contains(&instructions[index].address)
+    {
+        return false;
+    }
+
+    for decoded in instructions[..index].iter().rev() {
+        if decoded.instruction.rd() == Some(Register::A7) {
+            return matches!(
+                decoded.instruction,
+                Instruction::Addi(i) if i.rs1() == Register::Zero
+                    && i64::from(i.imm()) as u64 == SelfieSyscall::Exit.number()
+            );
+        }
+
+        if leaders.contains(&decoded.address) {
+            break;
+        }
+    }
+
+    false
+}
+
+impl DecodedProgram {
+    /// Build the control-flow graph of the code, see [`Cfg::new`].
+    pub fn cfg(&self) -> Cfg {
+        Cfg::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble::assemble, Register};
+
+    fn cfg(source: &str) -> Cfg {
+        assemble(source).unwrap().decode().unwrap().cfg()
+    }
+
+    fn successors(cfg: &Cfg, id: BlockId) -> Vec<(u64, EdgeKind)> {
+        cfg.successors(id)
+            .iter()
+            .map(|edge| (cfg.block(edge.to).start(), edge.kind))
+            .collect()
+    }
+
+    #[test]
+    fn blocks_and_edges() {
+        let cfg = cfg("
+            _start:
+                jal ra, function
+                addi a7, zero, 93
+                ecall
+            function:
+                addi a0, zero, 10
+            loop:
+                addi a0, a0, -1
+                bne a0, zero, loop
+                jal zero, end
+                addi a0, zero, 1
+            end:
+                jalr zero, 0(ra)
+            ");
+
+        let starts = cfg
+            .blocks()
+            .iter()
+            .map(|block| block.start())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            starts,
+            vec![0x10000, 0x10004, 0x1000c, 0x10010, 0x10018, 0x1001c, 0x10020]
+        );
+
+        assert_eq!(
+            successors(&cfg, 0),
+            vec![(0x1000c, EdgeKind::Call), (0x10004, EdgeKind::CallReturn)]
+        );
+        assert_eq!(successors(&cfg, 1), vec![]);
+        assert_eq!(
+            successors(&cfg, 3),
+            vec![
+                (0x10010, EdgeKind::Branch),
+                (0x10018, EdgeKind::FallThrough)
+            ]
+        );
+        assert_eq!(successors(&cfg, 4), vec![(0x10020, EdgeKind::Jump)]);
+        assert_eq!(successors(&cfg, 6), vec![]);
+
+        assert_eq!(cfg.block(6).terminator(), ControlFlow::Return);
+        assert_eq!(cfg.predecessors(6).len(), 2);
+        assert_eq!(cfg.predecessors(5).len(), 0);
+        assert!(cfg.unresolved().is_empty());
+
+        assert_eq!(cfg.block_at(0x10014), Some(3));
+        assert_eq!(cfg.block_at(0x10024), None);
+    }
+
+    #[test]
+    fn exit_has_no_successors() {
+        let cfg = cfg("
+                addi a7, zero, 93
+                addi a0, zero, 0
+                ecall
+                addi a7, zero, 64
+                ecall
+                beq a0, zero, exit
+                addi a7, zero, 93
+            exit:
+                ecall
+                addi a7, zero, 93
+                jalr zero, 0(ra)
+            ");
+
+        let starts = cfg
+            .blocks()
+            .iter()
+            .map(|block| block.start())
+            .collect::<Vec<_>>();
+
+        assert_eq!(starts, vec![0x10000, 0x1000c, 0x10018, 0x1001c]);
+
+        assert_eq!(successors(&cfg, 0), vec![]);
+        assert_eq!(
+            successors(&cfg, 1),
+            vec![
+                (0x1001c, EdgeKind::Branch),
+                (0x10018, EdgeKind::FallThrough)
+            ]
+        );
+        // a7 is only 93 on one of the paths to the ecall, which does not end its block
+        assert_eq!(cfg.block(3).instructions.len(), 3);
+    }
+
+    #[test]
+    fn unresolved_jumps() {
+        let cfg = cfg("
+                jalr ra, 0(a0)
+                jalr zero, 0(t1)
+                jal zero, .+64
+            ");
+
+        assert_eq!(
+            cfg.unresolved(),
+            &[
+                UnresolvedJump {
+                    address: 0x10000,
+                    flow: ControlFlow::IndirectCall {
+                        base: Register::A0,
+                        offset: 0
+                    }
+                },
+                UnresolvedJump {
+                    address: 0x10004,
+                    flow: ControlFlow::IndirectJump {
+                        base: Register::T1,
+                        offset: 0
+                    }
+                },
+                UnresolvedJump {
+                    address: 0x10008,
+                    flow: ControlFlow::Jump { target: 0x10048 }
+                },
+            ]
+        );
+        assert_eq!(successors(&cfg, 0), vec![(0x10004, EdgeKind::CallReturn)]);
+    }
+
+    #[test]
+    fn resolved_targets() {
+        let program = assemble(
+            "
+                jalr ra, 0(a0)
+                addi a0, zero, 1
+                jalr zero, 0(t1)
+                jalr zero, 0(t2)
+                jalr zero, 0(ra)
+                jalr zero, 0(t0)
+            ",
+        )
+        .unwrap()
+        .decode()
+        .unwrap();
+
+        let targets = [
+            (0x10000, Some(0x10008)),
+            (0x10008, Some(0x10004)),
+            (0x1000c, Some(0x10002)),
+            (0x10010, Some(0x10000)),
+            (0x10014, None),
+        ]
+        .iter()
+        .copied()
+        .collect::<BTreeMap<_, _>>();
+
+        let cfg = Cfg::with_targets(&program, &targets);
+
+        let starts = cfg
+            .blocks()
+            .iter()
+            .map(|block| block.start())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            starts,
+            vec![0x10000, 0x10004, 0x10008, 0x1000c, 0x10010, 0x10014]
+        );
+
+        assert_eq!(
+            successors(&cfg, 0),
+            vec![(0x10008, EdgeKind::Call), (0x10004, EdgeKind::CallReturn)]
+        );
+        assert_eq!(successors(&cfg, 2), vec![(0x10004, EdgeKind::Jump)]);
+        assert_eq!(successors(&cfg, 3), vec![]);
+        assert_eq!(successors(&cfg, 4), vec![(0x10000, EdgeKind::Jump)]);
+        assert_eq!(
+            cfg.unresolved(),
+            &[
+                UnresolvedJump {
+                    address: 0x1000c,
+                    flow: ControlFlow::IndirectJump {
+                        base: Register::T2,
+                        offset: 0
+                    }
+                },
+                UnresolvedJump {
+                    address: 0x10014,
+                    flow: ControlFlow::Return
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn dot() {
+        let cfg = cfg("
+            loop:
+                beq a0, zero, loop
+                jalr zero, 0(ra)
+            ");
+
+        assert_eq!(
+            cfg.to_dot(),
+            "digraph cfg {
+    node [shape=box, fontname=\"monospace\"];
+    \"0x10000\" [label=\"0x10000: beq a0,zero,0x10000\\l\"];
+    \"0x10004\" [label=\"0x10004: jalr zero,0(ra)\\l\"];
+    \"0x10000\" -> \"0x10000\" [label=\"taken\"];
+    \"0x10000\" -> \"0x10004\";
+}
+"
+        );
+    }
+}
@@ -0,0 +1,478 @@
+//! Compression of 32-bit instructions into their RVC (`C.*`) equivalents.
+//!
+//! This is the inverse of the [`decompress`](crate::decompress) module: an instruction is only
+//! compressed if the compressed form expands to exactly the same 32-bit encoding again.
+
+use crate::{decompress::decompress, Instruction, Register};
+
+/// Compress an instruction into its 16-bit encoding.
+///
+/// Returns `None` if the instruction has no compressed form, e.g. because an immediate is out
+/// of range or a register is not one of the 8 registers (`x8`-`x15`) compressed forms are
+/// restricted to.
+pub fn compress(instruction: Instruction) -> Option<u16> {
+    let compressed = match instruction {
+        Instruction::Addi(i) => compress_addi(i.rd(), i.rs1(), i.imm()),
+        Instruction::Addiw(i) => {
+            let imm = signed(i.imm(), 6)?;
+
+            (i.rd() == i.rs1() && i.rd() != Register::Zero)
+                .then(|| ci(0b001, reg(i.rd()), imm, 0b01))
+        }
+        Instruction::Andi(i) => {
+            let rd = compressed_reg(i.rd())?;
+            let imm = signed(i.imm(), 6)?;
+
+            (i.rd() == i.rs1()).then(|| cb_alu(0b10, rd, imm))
+        }
+        Instruction::Slli(i) => {
+            let shamt = shamt(instruction)?;
+
+            (i.rd() == i.rs1() && i.rd() != Register::Zero)
+                .then(|| ci(0b000, reg(i.rd()), shamt, 0b10))
+        }
+        Instruction::Srli(i) | Instruction::Srai(i) => {
+            let rd = compressed_reg(i.rd())?;
+            let shamt = shamt(instruction)?;
+            let funct2 = if matches!(instruction, Instruction::Srli(_)) {
+                0b00
+            } else {
+                0b01
+            };
+
+            (i.rd() == i.rs1()).then(|| cb_alu(funct2, rd, shamt))
+        }
+        Instruction::Lui(u) => {
+            // The 6-bit immediate is sign-extended to the 20-bit upper immediate.
+            let imm = u.imm();
+            let nonzero = imm != 0 && !(0x20..0xfffe0).contains(&imm);
+
+            (nonzero && u.rd() != Register::Zero && u.rd() != Register::Sp)
+                .then(|| ci(0b011, reg(u.rd()), (imm & 0b11_1111) as u16, 0b01))
+        }
+        Instruction::Lw(i) => compress_load(i.rd(), i.rs1(), i.imm(), 0b010, 4),
+        Instruction::Ld(i) => compress_load(i.rd(), i.rs1(), i.imm(), 0b011, 8),
+        Instruction::Sw(s) => compress_store(s.rs1(), s.rs2(), s.imm(), 0b110, 4),
+        Instruction::Sd(s) => compress_store(s.rs1(), s.rs2(), s.imm(), 0b111, 8),
+        Instruction::Fld(i) => compress_load(i.frd(), i.rs1(), i.imm(), 0b001, 8),
+        Instruction::Fsd(s) => compress_store(s.rs1(), s.frs2(), s.imm(), 0b101, 8),
+        Instruction::Jal(j) => {
+            let offset = signed(j.imm(), 12)?;
+
+            (j.rd() == Register::Zero && j.imm() % 2 == 0).then(|| {
+                let field = permute(offset, &[11, 4, 9, 8, 10, 6, 7, 3, 2, 1, 5]);
+
+                (0b101 << 13) | (field << 2) | 0b01
+            })
+        }
+        Instruction::Jalr(i) => {
+            let funct4 = match i.rd() {
+                Register::Zero => 0b1000,
+                Register::Ra => 0b1001,
+                _ => return None,
+            };
+
+            (i.rs1() != Register::Zero && i.imm() == 0).then(|| cr(funct4, reg(i.rs1()), 0))
+        }
+        // C.EBREAK
+        Instruction::Ebreak(_) => Some(cr(0b1001, 0, 0)),
+        Instruction::Beq(b) | Instruction::Bne(b) => {
+            let rs1 = compressed_reg(b.rs1())?;
+            let offset = signed(b.imm(), 9)?;
+            let funct3 = if matches!(instruction, Instruction::Beq(_)) {
+                0b110
+            } else {
+                0b111
+            };
+
+            (b.rs2() == Register::Zero && b.imm() % 2 == 0).then(|| {
+                let field = permute(offset, &[8, 4, 3, 7, 6, 2, 1, 5]);
+
+                (funct3 << 13)
+                    | ((field >> 5) << 10)
+                    | (rs1 << 7)
+                    | ((field & 0b1_1111) << 2)
+                    | 0b01
+            })
+        }
+        Instruction::Add(r) => {
+            let (rd, rs1, rs2) = (r.rd(), r.rs1(), r.rs2());
+
+            if rd == Register::Zero || rs2 == Register::Zero {
+                None
+            } else if rs1 == Register::Zero {
+                Some(cr(0b1000, reg(rd), reg(rs2)))
+            } else if rs1 == rd {
+                Some(cr(0b1001, reg(rd), reg(rs2)))
+            } else {
+                None
+            }
+        }
+        Instruction::Sub(r) => compress_arithmetic(r.rd(), r.rs1(), r.rs2(), 0, 0b00),
+        Instruction::Xor(r) => compress_arithmetic(r.rd(), r.rs1(), r.rs2(), 0, 0b01),
+        Instruction::Or(r) => compress_arithmetic(r.rd(), r.rs1(), r.rs2(), 0, 0b10),
+        Instruction::And(r) => compress_arithmetic(r.rd(), r.rs1(), r.rs2(), 0, 0b11),
+        Instruction::Subw(r) => compress_arithmetic(r.rd(), r.rs1(), r.rs2(), 1, 0b00),
+        Instruction::Addw(r) => compress_arithmetic(r.rd(), r.rs1(), r.rs2(), 1, 0b01),
+        _ => None,
+    }?;
+
+    // Reject anything which does not expand to the exact same encoding, e.g. because of fields
+    // which are ignored by the decoder.
+    match decompress(compressed) {
+        Ok(expanded) if expanded == u32::from(instruction) => Some(compressed),
+        _ => None,
+    }
+}
+
+fn compress_addi(rd: Register, rs1: Register, imm: i32) -> Option<u16> {
+    let small = signed(imm, 6);
+
+    if rd == Register::Zero && rs1 == Register::Zero && imm == 0 {
+        // C.NOP
+        Some(ci(0b000, 0, 0, 0b01))
+    } else if rd == Register::Zero {
+        None
+    } else if rs1 == Register::Zero {
+        // C.LI
+        small.map(|imm| ci(0b010, reg(rd), imm, 0b01))
+    } else if rd == rs1 && imm != 0 && small.is_some() {
+        // C.ADDI
+        small.map(|imm| ci(0b000, reg(rd), imm, 0b01))
+    } else if rd == rs1 && rd == Register::Sp && imm != 0 && imm % 16 == 0 {
+        // C.ADDI16SP
+        signed(imm, 10).map(|imm| ci(0b011, reg(rd), permute(imm, &[9, 4, 6, 8, 7, 5]), 0b01))
+    } else if rs1 == Register::Sp && imm > 0 && imm % 4 == 0 {
+        // C.ADDI4SPN
+        let rd = compressed_reg(rd)?;
+        let imm = unsigned(imm, 8, 4)?;
+
+        Some((permute(imm, &[5, 4, 9, 8, 7, 6, 2, 3]) << 5) | (rd << 2))
+    } else {
+        None
+    }
+}
+
+fn compress_load(
+    rd: impl Into<u32>,
+    rs1: Register,
+    imm: i32,
+    funct3: u16,
+    width: i32,
+) -> Option<u16> {
+    if rs1 == Register::Sp {
+        // C.LWSP, C.LDSP, C.FLDSP
+        let imm = unsigned(imm, 6, width)?;
+        let field = match width {
+            4 => permute(imm, &[5, 4, 3, 2, 7, 6]),
+            _ => permute(imm, &[5, 4, 3, 8, 7, 6]),
+        };
+
+        // rd == 0 is reserved for C.LWSP and C.LDSP, which is rejected by decompression.
+        Some(ci(funct3, reg(rd), field, 0b10))
+    } else {
+        // C.LW, C.LD, C.FLD
+        let rd = compressed_reg(rd)?;
+        let rs1 = compressed_reg(rs1)?;
+        let imm = unsigned(imm, 5, width)?;
+
+        Some(cl_cs(funct3, rs1, rd, imm, width))
+    }
+}
+
+fn compress_store(
+    rs1: Register,
+    rs2: impl Into<u32>,
+    imm: i32,
+    funct3: u16,
+    width: i32,
+) -> Option<u16> {
+    if rs1 == Register::Sp {
+        // C.SWSP, C.SDSP, C.FSDSP
+        let imm = unsigned(imm, 6, width)?;
+        let field = match width {
+            4 => permute(imm, &[5, 4, 3, 2, 7, 6]),
+            _ => permute(imm, &[5, 4, 3, 8, 7, 6]),
+        };
+
+        Some((funct3 << 13) | (field << 7) | (reg(rs2) << 2) | 0b10)
+    } else {
+        // C.SW, C.SD, C.FSD
+        let rs1 = compressed_reg(rs1)?;
+        let rs2 = compressed_reg(rs2)?;
+        let imm = unsigned(imm, 5, width)?;
+
+        Some(cl_cs(funct3, rs1, rs2, imm, width))
+    }
+}
+
+fn compress_arithmetic(
+    rd: Register,
+    rs1: Register,
+    rs2: Register,
+    funct1: u16,
+    funct2: u16,
+) -> Option<u16> {
+    let rd_rs1 = compressed_reg(rd)?;
+    let rs2 = compressed_reg(rs2)?;
+
+    if rd != rs1 {
+        return None;
+    }
+
+    Some(
+        (0b100 << 13)
+            | (funct1 << 12)
+            | (0b11 << 10)
+            | (rd_rs1 << 7)
+            | (funct2 << 5)
+            | (rs2 << 2)
+            | 0b01,
+    )
+}
+
+/// CI format: `funct3 | imm[5] | rd/rs1 | imm[4:0] | op`.
+fn ci(funct3: u16, rd: u16, imm: u16, op: u16) -> u16 {
+    (funct3 << 13) | (((imm >> 5) & 0b1) << 12) | (rd << 7) | ((imm & 0b1_1111) << 2) | op
+}
+
+/// CR format: `funct4 | rd/rs1 | rs2 | op`.
+fn cr(funct4: u16, rd_rs1: u16, rs2: u16) -> u16 {
+    (funct4 << 12) | (rd_rs1 << 7) | (rs2 << 2) | 0b10
+}
+
+/// CB format as used by C.SRLI, C.SRAI and C.ANDI.
+fn cb_alu(funct2: u16, rd_rs1: u16, imm: u16) -> u16 {
+    (0b100 << 13)
+        | (((imm >> 5) & 0b1) << 12)
+        | (funct2 << 10)
+        | (rd_rs1 << 7)
+        | ((imm & 0b1_1111) << 2)
+        | 0b01
+}
+
+/// CL and CS formats: `funct3 | imm | rs1' | imm | rd'/rs2' | op`.
+fn cl_cs(funct3: u16, rs1: u16, rd_rs2: u16, imm: u16, width: i32) -> u16 {
+    let field = match width {
+        4 => permute(imm, &[5, 4, 3, 2, 6]),
+        _ => permute(imm, &[5, 4, 3, 7, 6]),
+    };
+
+    (funct3 << 13) | ((field >> 2) << 10) | (rs1 << 7) | ((field & 0b11) << 5) | (rd_rs2 << 2)
+}
+
+/// Scatter the bits of `imm` into an instruction field, `perm` lists the immediate bit for each
+/// field bit starting at the most significant one.
+fn permute(imm: u16, perm: &[u16]) -> u16 {
+    perm.iter()
+        .rev()
+        .enumerate()
+        .map(|(bit, offset)| ((imm >> offset) & 0b1) << bit)
+        .sum()
+}
+
+fn reg(register: impl Into<u32>) -> u16 {
+    register.into() as u16
+}
+
+/// The 3-bit encoding of one of the registers `x8`-`x15`.
+fn compressed_reg(register: impl Into<u32>) -> Option<u16> {
+    match reg(register) {
+        r @ 8..=15 => Some(r - 8),
+        _ => None,
+    }
+}
+
+/// Truncate `imm` to `bits` bits if it is representable as a signed immediate of that size.
+fn signed(imm: i32, bits: u32) -> Option<u16> {
+    let bound = 1 << (bits - 1);
+
+    (-bound <= imm && imm < bound).then(|| (imm as u16) & ((1 << bits) - 1))
+}
+
+/// Return `imm` if it is an unsigned multiple of `scale` representable in `bits` bits after
+/// scaling.
+fn unsigned(imm: i32, bits: u32, scale: i32) -> Option<u16> {
+    if 0 <= imm && imm % scale == 0 && imm / scale < (1 << bits) {
+        Some(imm as u16)
+    } else {
+        None
+    }
+}
+
+fn shamt(instruction: Instruction) -> Option<u16> {
+    let shamt = ((u32::from(instruction) >> 20) & 0b11_1111) as u16;
+
+    if shamt != 0 {
+        Some(shamt)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, FRegister, FRegister::*, Register::*};
+
+    const REGISTERS: [Register; 8] = [Zero, Ra, Sp, Fp, S1, A0, A5, T6];
+    const FREGISTERS: [FRegister; 8] = [Ft0, Ft1, Fs0, Fs1, Fa0, Fa5, Ft8, Ft11];
+
+    fn compressible() -> Vec<Instruction> {
+        let mut instructions = vec![Instruction::new_nop()];
+
+        for &rd in REGISTERS.iter() {
+            for &rs in REGISTERS.iter() {
+                for imm in -1100..1100 {
+                    instructions.push(Instruction::new_addi(rd, rs, imm));
+                    instructions.push(Instruction::new_addiw(rd, rs, imm));
+                    instructions.push(Instruction::new_andi(rd, rs, imm));
+                    instructions.push(Instruction::new_lw(rd, rs, imm));
+                    instructions.push(Instruction::new_ld(rd, rs, imm));
+                    instructions.push(Instruction::new_sw(rs, rd, imm));
+                    instructions.push(Instruction::new_sd(rs, rd, imm));
+                    instructions.push(Instruction::new_fld(FREGISTERS[rd as usize % 8], rs, imm));
+                    instructions.push(Instruction::new_fsd(rs, FREGISTERS[rd as usize % 8], imm));
+                }
+
+                for imm in (-600..600).step_by(2) {
+                    instructions.push(Instruction::new_beq(rd, rs, imm));
+                    instructions.push(Instruction::new_bne(rd, rs, imm));
+                }
+
+                for shamt in 0..64 {
+                    instructions.push(Instruction::new_slli(rd, rs, shamt));
+                    instructions.push(Instruction::new_srli(rd, rs, shamt));
+                    instructions.push(Instruction::new_srai(rd, rs, shamt));
+                }
+
+                for &rs2 in REGISTERS.iter() {
+                    instructions.push(Instruction::new_add(rd, rs, rs2));
+                    instructions.push(Instruction::new_sub(rd, rs, rs2));
+                    instructions.push(Instruction::new_xor(rd, rs, rs2));
+                    instructions.push(Instruction::new_or(rd, rs, rs2));
+                    instructions.push(Instruction::new_and(rd, rs, rs2));
+                    instructions.push(Instruction::new_addw(rd, rs, rs2));
+                    instructions.push(Instruction::new_subw(rd, rs, rs2));
+                }
+
+                instructions.push(Instruction::new_jalr(rd, rs, 0));
+                instructions.push(Instruction::new_jalr(rd, rs, 4));
+            }
+
+            for imm in -40..40 {
+                instructions.push(Instruction::new_lui(rd, imm));
+            }
+
+            for imm in (-2100..2100).step_by(2) {
+                instructions.push(Instruction::new_jal(rd, imm));
+            }
+        }
+
+        instructions
+    }
+
+    #[test]
+    fn compressed_instructions_decode_to_the_original() {
+        let mut count = 0;
+
+        for instruction in compressible() {
+            if let Some(compressed) = compress(instruction) {
+                assert_eq!(
+                    decode(compressed.into()),
+                    Ok(instruction),
+                    "{:#06x} does not decode to {:?}",
+                    compressed,
+                    instruction
+                );
+
+                count += 1;
+            }
+        }
+
+        assert!(count > 10000, "only {} instructions were compressed", count);
+    }
+
+    #[test]
+    fn compression_of_all_compressed_encodings() {
+        for compressed in (0..=u16::MAX).filter(|i| i & 0b11 != 0b11) {
+            let instruction = match decode(compressed.into()) {
+                Ok(instruction) => instruction,
+                Err(_) => continue,
+            };
+
+            // HINTs and instructions with several compressed forms may be compressed to another
+            // encoding, but it has to expand to the same instruction.
+            if let Some(recompressed) = compress(instruction) {
+                assert_eq!(decode(recompressed.into()), Ok(instruction));
+            }
+        }
+    }
+
+    #[test]
+    fn compress_selects_the_compressed_form() {
+        // Encodings emitted by gcc, see the tests of the decompress module.
+        let expected = [
+            (0x002c, Instruction::new_addi(A1, Sp, 8)),
+            (0x0001, Instruction::new_nop()),
+            (0x4581, Instruction::new_addi(A1, Zero, 0)),
+            (0x17e1, Instruction::new_addi(A5, A5, -8)),
+            (0x1141, Instruction::new_addi(Sp, Sp, -16)),
+            (0x7139, Instruction::new_addi(Sp, Sp, -64)),
+            (0x37fd, Instruction::new_addiw(A5, A5, -1)),
+            (0x77fd, Instruction::new_lui(A5, -1)),
+            (0x9bf1, Instruction::new_andi(A5, A5, -4)),
+            (0x830d, Instruction::new_srli(A4, A4, 3)),
+            (0x947d, Instruction::new_srai(Fp, Fp, 63)),
+            (0x078e, Instruction::new_slli(A5, A5, 3)),
+            (0x8e09, Instruction::new_sub(A2, A2, A0)),
+            (0x8f3d, Instruction::new_xor(A4, A4, A5)),
+            (0x8f5d, Instruction::new_or(A4, A4, A5)),
+            (0x9f01, Instruction::new_subw(A4, A4, Fp)),
+            (0x9f21, Instruction::new_addw(A4, A4, Fp)),
+            (0xb761, Instruction::new_jal(Zero, -120)),
+            (0xc781, Instruction::new_beq(A5, Zero, 8)),
+            (0xfff5, Instruction::new_bne(A5, Zero, -4)),
+            (0x5ffc, Instruction::new_lw(A5, A5, 124)),
+            (0x7ffc, Instruction::new_ld(A5, A5, 248)),
+            (0xdffc, Instruction::new_sw(A5, A5, 124)),
+            (0xee98, Instruction::new_sd(A3, A4, 24)),
+            (0x4502, Instruction::new_lw(A0, Sp, 0)),
+            (0x60e2, Instruction::new_ld(Ra, Sp, 24)),
+            (0xd03e, Instruction::new_sw(Sp, A5, 32)),
+            (0xec06, Instruction::new_sd(Sp, Ra, 24)),
+            (0x2108, Instruction::new_fld(Fa0, A0, 0)),
+            (0xa508, Instruction::new_fsd(A0, Fa0, 8)),
+            (0x27a2, Instruction::new_fld(Fa5, Sp, 8)),
+            (0xa422, Instruction::new_fsd(Sp, Fs0, 8)),
+            (0x8782, Instruction::new_jalr(Zero, A5, 0)),
+            (0x9782, Instruction::new_jalr(Ra, A5, 0)),
+            (0x853e, Instruction::new_add(A0, Zero, A5)),
+            (0x97ba, Instruction::new_add(A5, A5, A4)),
+            (0x9002, Instruction::new_ebreak()),
+        ];
+
+        for (compressed, instruction) in expected.iter() {
+            assert_eq!(
+                compress(*instruction),
+                Some(*compressed),
+                "{:?}",
+                instruction
+            );
+        }
+    }
+
+    #[test]
+    fn incompressible_instructions() {
+        assert_eq!(compress(Instruction::new_addi(A0, A1, 1)), None);
+        assert_eq!(compress(Instruction::new_addi(A0, A0, 0)), None);
+        assert_eq!(compress(Instruction::new_addi(A0, A0, 32)), None);
+        assert_eq!(compress(Instruction::new_ld(A0, T6, 0)), None);
+        assert_eq!(compress(Instruction::new_ld(A0, A1, 4)), None);
+        assert_eq!(compress(Instruction::new_sub(A0, A1, A2)), None);
+        assert_eq!(compress(Instruction::new_jal(Ra, 8)), None);
+        assert_eq!(compress(Instruction::new_beq(A0, A1, 8)), None);
+        assert_eq!(compress(Instruction::new_lui(Sp, 1)), None);
+        assert_eq!(compress(Instruction::new_mul(A0, A0, A1)), None);
+    }
+}
@@ -0,0 +1,491 @@
+//! # Dataflow analysis
+//!
+//! A generic worklist solver for dataflow problems over the basic blocks of a [`Cfg`], together
+//! with the classic register analyses built on top of it: [`Liveness`], [`ReachingDefinitions`],
+//! [`DefUseChains`] and [`ConstantPropagation`], which resolves the addresses of memory accesses
+//! and indirect jumps.
+//!
+//! An [`Analysis`] defines a [`Lattice`] of facts, a direction and the effect of a single
+//! instruction. The solver propagates facts along intraprocedural edges only: calls are left via
+//! the edge to the instruction after them, so that every function is analysed separately and the
+//! effect of a callee is approximated by the calling convention (see [`effects`]).
+//!
+//! ```
+//! use riscu::{assemble::assemble, dataflow, Register};
+//!
+//! let program = assemble(
+//!     "
+//!         addi a0, zero, 1
+//!         addi a2, zero, 2
+//!         add a0, a0, a2
+//!         jalr zero, 0(ra)
+//!     ",
+//! )
+//! .unwrap()
+//! .decode()
+//! .unwrap();
+//!
+//! let cfg = program.cfg();
+//! let liveness = dataflow::liveness(&cfg);
+//! let live = liveness.before(0x10008).unwrap();
+//!
+//! assert!(live.contains(Register::A0) && live.contains(Register::A2));
+//! assert!(!liveness.after(0x10008).unwrap().contains(Register::A2));
+//! ```
+
+mod constants;
+mod liveness;
+mod reaching;
+
+pub use constants::{
+    constant_propagation, resolve_indirect_jumps, ConstantPropagation, RegisterValues, Value,
+};
+pub use liveness::{liveness, Liveness};
+pub use reaching::{reaching_definitions, DefUseChains, Definition, ReachingDefinitions, Use};
+
+use crate::{
+    callgraph::Function,
+    cfg::{BasicBlock, BlockId, Cfg, EdgeKind},
+    iterators::DecodedInstruction,
+    ControlFlow, Instruction, Register,
+};
+use core::fmt;
+use std::collections::{BTreeSet, VecDeque};
+
+/// The facts of an analysis, which form a join-semilattice.
+pub trait Lattice: Clone + Eq {
+    /// The least element, i.e. no information.
+    fn bottom() -> Self;
+
+    /// Join `other` into `self` and return true if `self` changed.
+    fn join(&mut self, other: &Self) -> bool;
+}
+
+impl<T: Clone + Ord> Lattice for BTreeSet<T> {
+    fn bottom() -> Self {
+        BTreeSet::new()
+    }
+
+    fn join(&mut self, other: &Self) -> bool {
+        let size = self.len();
+
+        self.extend(other.iter().cloned());
+
+        self.len() != size
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    /// Facts flow from the entry of a function in the direction of execution.
+    Forward,
+    /// Facts flow from the exits of a function against the direction of execution.
+    Backward,
+}
+
+/// A dataflow problem over the instructions of a program.
+pub trait Analysis {
+    type Domain: Lattice;
+
+    const DIRECTION: Direction;
+
+    /// The facts at the boundary of a function, i.e. at blocks without intraprocedural
+    /// predecessors (forward) or successors (backward). Forward analyses also start from the
+    /// boundary at the first block of the code and at the targets of calls, which may be entered
+    /// by loops as well.
+    fn boundary(&self, _block: &BasicBlock) -> Self::Domain {
+        Self::Domain::bottom()
+    }
+
+    /// Apply the effect of `instruction` to `state`, which holds the facts before the instruction
+    /// in the direction of the analysis.
+    fn transfer(&self, instruction: &DecodedInstruction, state: &mut Self::Domain);
+}
+
+/// The fixpoint of an analysis, with the facts at the start and end of every block.
+#[derive(Clone, Debug)]
+pub struct Results<'a, A: Analysis> {
+    cfg: &'a Cfg,
+    analysis: A,
+    entry: Vec<A::Domain>,
+    exit: Vec<A::Domain>,
+}
+
+impl<'a, A: Analysis> Results<'a, A> {
+    pub fn cfg(&self) -> &'a Cfg {
+        self.cfg
+    }
+
+    pub fn analysis(&self) -> &A {
+        &self.analysis
+    }
+
+    /// The facts at the start of the block, before its first instruction is executed.
+    pub fn entry(&self, id: BlockId) -> &A::Domain {
+        &self.entry[id]
+    }
+
+    /// The facts at the end of the block, after its last instruction is executed.
+    pub fn exit(&self, id: BlockId) -> &A::Domain {
+        &self.exit[id]
+    }
+
+    /// The facts right before the instruction at `address` is executed.
+    pub fn before(&self, address: u64) -> Option<A::Domain> {
+        self.at(address, true)
+    }
+
+    /// The facts right after the instruction at `address` is executed.
+    pub fn after(&self, address: u64) -> Option<A::Domain> {
+        self.at(address, false)
+    }
+
+    fn at(&self, address: u64, before: bool) -> Option<A::Domain> {
+        let id = self.cfg.block_at(address)?;
+        let instructions = &self.cfg.block(id).instructions;
+        let index = instructions.iter().position(|i| i.address == address)?;
+        let split = if before { index } else { index + 1 };
+
+        let state = match A::DIRECTION {
+            Direction::Forward => instructions[..split].iter().fold(
+                self.entry[id].clone(),
+                |mut state, instruction| {
+                    self.analysis.transfer(instruction, &mut state);
+                    state
+                },
+            ),
+            Direction::Backward => instructions[split..].iter().rev().fold(
+                self.exit[id].clone(),
+                |mut state, instruction| {
+                    self.analysis.transfer(instruction, &mut state);
+                    state
+                },
+            ),
+        };
+
+        Some(state)
+    }
+}
+
+/// Solve `analysis` over `cfg` with a worklist algorithm.
+pub fn solve<A: Analysis>(cfg: &Cfg, analysis: A) -> Results<'_, A> {
+    let blocks = (0..cfg.blocks().len()).collect::<Vec<_>>();
+
+    solve_blocks(cfg, analysis, &blocks, |id| {
+        id == 0
+            || cfg
+                .predecessors(id)
+                .iter()
+                .any(|e| e.kind == EdgeKind::Call)
+    })
+}
+
+/// Solve `analysis` over the blocks of `function` only. Edges from and to other blocks are
+/// ignored and a forward analysis starts from the boundary at the entry of the function. The
+/// facts of all other blocks are bottom.
+pub fn solve_function<'a, A: Analysis>(
+    cfg: &'a Cfg,
+    function: &Function,
+    analysis: A,
+) -> Results<'a, A> {
+    let entry = cfg.block_starting_at(function.entry);
+
+    solve_blocks(cfg, analysis, &function.blocks, |id| Some(id) == entry)
+}
+
+/// The worklist algorithm over the sorted `blocks`, where `is_entry` tells the blocks at which a
+/// forward analysis starts from the boundary in addition to those without predecessors.
+fn solve_blocks<'a, A: Analysis>(
+    cfg: &'a Cfg,
+    analysis: A,
+    blocks: &[BlockId],
+    is_entry: impl Fn(BlockId) -> bool,
+) -> Results<'a, A> {
+    let count = cfg.blocks().len();
+    let forward = A::DIRECTION == Direction::Forward;
+    let member = |id: BlockId| blocks.binary_search(&id).is_ok();
+
+    // `input` holds the facts where the analysis enters a block, `output` where it leaves it
+    let mut input = vec![A::Domain::bottom(); count];
+    let mut output = vec![A::Domain::bottom(); count];
+
+    let mut worklist = if forward {
+        blocks.iter().copied().collect::<VecDeque<_>>()
+    } else {
+        blocks.iter().rev().copied().collect::<VecDeque<_>>()
+    };
+    let mut queued = vec![false; count];
+
+    blocks.iter().for_each(|id| queued[*id] = true);
+
+    while let Some(id) = worklist.pop_front() {
+        queued[id] = false;
+
+        let (incoming, outgoing) = if forward {
+            (cfg.predecessors(id), cfg.successors(id))
+        } else {
+            (cfg.successors(id), cfg.predecessors(id))
+        };
+
+        let mut incoming = incoming
+            .iter()
+            .filter(|edge| edge.kind.is_intraprocedural())
+            .map(|edge| if forward { edge.from } else { edge.to })
+            .filter(|other| member(*other))
+            .peekable();
+
+        let mut state = if (forward && is_entry(id)) || incoming.peek().is_none() {
+            analysis.boundary(cfg.block(id))
+        } else {
+            A::Domain::bottom()
+        };
+
+        incoming.for_each(|other| {
+            state.join(&output[other]);
+        });
+
+        input[id] = state.clone();
+
+        let instructions = &cfg.block(id).instructions;
+
+        if forward {
+            instructions
+                .iter()
+                .for_each(|i| analysis.transfer(i, &mut state));
+        } else {
+            instructions
+                .iter()
+                .rev()
+                .for_each(|i| analysis.transfer(i, &mut state));
+        }
+
+        if state != output[id] {
+            output[id] = state;
+
+            for edge in outgoing.iter().filter(|e| e.kind.is_intraprocedural()) {
+                let next = if forward { edge.to } else { edge.from };
+
+                if member(next) && !queued[next] {
+                    queued[next] = true;
+                    worklist.push_back(next);
+                }
+            }
+        }
+    }
+
+    let (entry, exit) = if forward {
+        (input, output)
+    } else {
+        (output, input)
+    };
+
+    Results {
+        cfg,
+        analysis,
+        entry,
+        exit,
+    }
+}
+
+/// A set of integer registers. The `zero` register is never part of a set, as it holds no
+/// value.
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct RegisterSet(u32);
+
+impl RegisterSet {
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    pub fn insert(&mut self, register: Register) {
+        if register != Register::Zero {
+            self.0 |= 1 << register as u32;
+        }
+    }
+
+    pub fn remove(&mut self, register: Register) {
+        self.0 &= !(1 << register as u32);
+    }
+
+    pub fn contains(&self, register: Register) -> bool {
+        self.0 & (1 << register as u32) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn union(&self, other: &RegisterSet) -> RegisterSet {
+        RegisterSet(self.0 | other.0)
+    }
+
+    pub fn difference(&self, other: &RegisterSet) -> RegisterSet {
+        RegisterSet(self.0 & !other.0)
+    }
+
+    /// The registers in the set in the order of their numbers.
+    pub fn iter(&self) -> impl Iterator<Item = Register> {
+        let bits = self.0;
+
+        (1..32_u32)
+            .filter(move |n| bits & (1 << n) != 0)
+            .map(Register::from)
+    }
+}
+
+impl core::iter::FromIterator<Register> for RegisterSet {
+    fn from_iter<I: IntoIterator<Item = Register>>(iter: I) -> Self {
+        let mut set = RegisterSet::new();
+
+        iter.into_iter().for_each(|register| set.insert(register));
+
+        set
+    }
+}
+
+impl fmt::Debug for RegisterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl Lattice for RegisterSet {
+    fn bottom() -> Self {
+        RegisterSet::new()
+    }
+
+    fn join(&mut self, other: &Self) -> bool {
+        let joined = self.union(other);
+        let changed = joined != *self;
+
+        *self = joined;
+
+        changed
+    }
+}
+
+/// The registers which pass arguments to a function.
+pub(crate) const ARGUMENTS: [Register; 8] = {
+    use Register::*;
+
+    [A0, A1, A2, A3, A4, A5, A6, A7]
+};
+
+/// The pointers which are valid in every function.
+const POINTERS: [Register; 3] = [Register::Sp, Register::Gp, Register::Tp];
+
+/// The callee-saved registers of the calling convention, besides `sp`.
+pub(crate) const CALLEE_SAVED: [Register; 12] = {
+    use Register::*;
+
+    [Fp, S1, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11]
+};
+
+/// The registers a call may overwrite.
+const CALLER_SAVED: [Register; 16] = {
+    use Register::*;
+
+    [
+        Ra, T0, T1, T2, A0, A1, A2, A3, A4, A5, A6, A7, T3, T4, T5, T6,
+    ]
+};
+
+/// The registers read and written by an instruction.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Effects {
+    /// The registers which may be read.
+    pub reads: RegisterSet,
+    /// The registers which are always overwritten.
+    pub writes: RegisterSet,
+    /// The registers which may be overwritten, but keep their value otherwise.
+    pub clobbers: RegisterSet,
+}
+
+/// The registers `instruction` reads and writes. Besides its operands, the implicit effects of
+/// calls, returns and `ecall` are included according to the calling convention:
+///
+/// - a call reads the argument registers `a0`-`a7` and the pointers `sp`, `gp` and `tp`, and may
+///   overwrite all caller-saved registers
+/// - a return reads the return values `a0` and `a1`, the pointers `sp`, `gp` and `tp` and all
+///   callee-saved registers
+/// - `ecall` reads `a0`-`a7` and writes the result to `a0`
+pub fn effects(instruction: &Instruction) -> Effects {
+    let mut effects = Effects {
+        reads: instruction.sources().collect(),
+        writes: instruction.rd().into_iter().collect(),
+        clobbers: RegisterSet::new(),
+    };
+
+    let mut read = |registers: &[Register]| {
+        registers
+            .iter()
+            .for_each(|register| effects.reads.insert(*register))
+    };
+
+    match instruction.control_flow(0) {
+        ControlFlow::Call { .. } | ControlFlow::IndirectCall { .. } => {
+            read(&ARGUMENTS);
+            read(&POINTERS);
+            effects.clobbers = CALLER_SAVED
+                .iter()
+                .copied()
+                .collect::<RegisterSet>()
+                .difference(&effects.writes);
+        }
+        ControlFlow::Return => {
+            read(&[Register::A0, Register::A1]);
+            read(&POINTERS);
+            read(&CALLEE_SAVED);
+        }
+        _ if matches!(instruction, Instruction::Ecall(_)) => {
+            read(&ARGUMENTS);
+            effects.writes.insert(Register::A0);
+        }
+        _ => {}
+    }
+
+    effects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Register::*;
+
+    #[test]
+    fn register_sets() {
+        let mut set = [A0, Zero, Sp].iter().copied().collect::<RegisterSet>();
+
+        assert_eq!(set.len(), 2);
+        assert!(!set.contains(Zero));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Sp, A0]);
+
+        set.remove(Sp);
+        assert!(set.join(&[T0].iter().copied().collect()));
+        assert!(!set.join(&RegisterSet::new()));
+        assert_eq!(format!("{:?}", set), "{t0, a0}");
+    }
+
+    #[test]
+    fn implicit_effects() {
+        let call = effects(&Instruction::new_jal(Ra, 8));
+        assert!(call.reads.contains(A0) && call.reads.contains(Sp));
+        assert!(call.writes.contains(Ra) && !call.clobbers.contains(Ra));
+        assert!(call.clobbers.contains(T0));
+
+        let ecall = effects(&Instruction::new_ecall());
+        assert!(ecall.reads.contains(A7));
+        assert_eq!(ecall.writes.iter().collect::<Vec<_>>(), vec![A0]);
+
+        let ret = effects(&Instruction::new_jalr(Zero, Ra, 0));
+        assert!(ret.reads.contains(Ra) && ret.reads.contains(S11));
+        assert!(ret.writes.is_empty());
+
+        let add = effects(&Instruction::new_add(A0, A1, Zero));
+        assert_eq!(add.reads.iter().collect::<Vec<_>>(), vec![A1]);
+    }
+}
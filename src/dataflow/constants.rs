@@ -0,0 +1,523 @@
+use super::{effects, solve, Analysis, Direction, Lattice, Results};
+use crate::{
+    cfg::{BasicBlock, Cfg},
+    emulator::Emulator,
+    isa::Xlen,
+    iterators::DecodedInstruction,
+    ControlFlow, DecodedProgram, Instruction, Register,
+};
+use core::fmt;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The value of an integer register.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Value {
+    /// No execution reaching this point has been found (yet).
+    Undefined,
+    /// The register holds the same value on all executions.
+    Constant(u64),
+    /// The register may hold different values, or its value is not known.
+    Unknown,
+}
+
+impl Value {
+    pub fn constant(self) -> Option<u64> {
+        match self {
+            Value::Constant(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn map(self, f: impl FnOnce(u64) -> u64) -> Value {
+        match self {
+            Value::Constant(value) => Value::Constant(f(value)),
+            other => other,
+        }
+    }
+
+    fn zip(self, other: Value, f: impl FnOnce(u64, u64) -> u64) -> Value {
+        match (self, other) {
+            (Value::Constant(a), Value::Constant(b)) => Value::Constant(f(a, b)),
+            (Value::Unknown, _) | (_, Value::Unknown) => Value::Unknown,
+            _ => Value::Undefined,
+        }
+    }
+}
+
+/// The values of all integer registers, where `zero` is always the constant 0.
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct RegisterValues([Value; 32]);
+
+impl RegisterValues {
+    /// All registers hold `value`.
+    pub fn new(value: Value) -> Self {
+        Self([value; 32])
+    }
+
+    pub fn get(&self, register: Register) -> Value {
+        match register {
+            Register::Zero => Value::Constant(0),
+            _ => self.0[register as usize],
+        }
+    }
+
+    pub fn set(&mut self, register: Register, value: Value) {
+        if register != Register::Zero {
+            self.0[register as usize] = value;
+        }
+    }
+
+    pub fn constant(&self, register: Register) -> Option<u64> {
+        self.get(register).constant()
+    }
+
+    /// The registers holding a constant other than `zero` in the order of their numbers.
+    pub fn constants(&self) -> impl Iterator<Item = (Register, u64)> + '_ {
+        (1..32_u32)
+            .map(Register::from)
+            .filter_map(move |register| Some((register, self.constant(register)?)))
+    }
+}
+
+impl fmt::Debug for RegisterValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Hex(u64);
+
+        impl fmt::Debug for Hex {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{:#x}", self.0)
+            }
+        }
+
+        f.debug_map()
+            .entries(
+                self.constants()
+                    .map(|(register, value)| (register, Hex(value))),
+            )
+            .finish()
+    }
+}
+
+impl Lattice for RegisterValues {
+    fn bottom() -> Self {
+        RegisterValues::new(Value::Undefined)
+    }
+
+    fn join(&mut self, other: &Self) -> bool {
+        let mut changed = false;
+
+        for (value, other) in self.0.iter_mut().zip(other.0.iter()) {
+            let joined = match (*value, *other) {
+                (a, Value::Undefined) => a,
+                (Value::Undefined, b) => b,
+                (a, b) if a == b => a,
+                _ => Value::Unknown,
+            };
+
+            changed |= joined != *value;
+            *value = joined;
+        }
+
+        changed
+    }
+}
+
+/// The integer registers which hold a constant value, e.g. an address materialised with
+/// `lui`/`addi` or `auipc`/`addi`. Only register-to-register computations are folded, values
+/// loaded from memory are unknown.
+#[derive(Clone, Debug)]
+pub struct ConstantPropagation {
+    xlen: Xlen,
+    entry: u64,
+    /// The values at the start of the code.
+    initial: RegisterValues,
+    /// The values at the entries of all other functions.
+    assumed: RegisterValues,
+}
+
+impl ConstantPropagation {
+    /// Propagate constants through the code of `program`. Execution starts with the registers
+    /// the [`Emulator`] loads the program with: all registers are zero except `sp`, which points
+    /// to `argc` on the stack. At the entries of other functions nothing is known.
+    pub fn new(program: &DecodedProgram) -> Self {
+        let emulator = Emulator::new(program);
+
+        let mut initial = RegisterValues::new(Value::Unknown);
+
+        (1..32_u32).map(Register::from).for_each(|register| {
+            initial.set(register, Value::Constant(emulator.register(register)))
+        });
+
+        ConstantPropagation {
+            xlen: program.xlen,
+            entry: program.code.address,
+            initial,
+            assumed: RegisterValues::new(Value::Unknown),
+        }
+    }
+
+    /// Assume that `register` holds `value` at the start of the code and at the entry of every
+    /// function, e.g. the global pointer.
+    pub fn with_register(mut self, register: Register, value: u64) -> Self {
+        self.initial.set(register, Value::Constant(value));
+        self.assumed.set(register, Value::Constant(value));
+        self
+    }
+
+    /// The absolute address accessed by a load, store or atomic instruction, or the target of a
+    /// `jalr`, if its base register holds a constant in `state` before the instruction.
+    pub fn resolve(&self, instruction: &DecodedInstruction, state: &RegisterValues) -> Option<u64> {
+        let (base, offset) = match instruction.instruction {
+            Instruction::Jalr(i) => (i.rs1(), i.imm()),
+            other => {
+                let access = other.memory_access()?;
+                (access.base, access.offset)
+            }
+        };
+
+        let address = self.truncate(state.constant(base)?.wrapping_add(offset as i64 as u64));
+
+        match instruction.instruction {
+            Instruction::Jalr(_) => Some(address & !1),
+            _ => Some(address),
+        }
+    }
+
+    /// The value `instruction` writes to its destination register.
+    fn evaluate(&self, instruction: &DecodedInstruction, state: &RegisterValues) -> Value {
+        use Instruction::*;
+
+        let imm = |imm: i32| imm as i64 as u64;
+        let upper = |imm: u32| (imm << 12) as i32 as i64 as u64;
+        let shamt = |amount: u64| (amount & (self.xlen.bits() as u64 - 1)) as u32;
+        let word = |value: u64| value as u32 as i32 as i64 as u64;
+
+        let signed = |register: Register| self.signed(state.get(register));
+        let unary = |register: Register, f: &dyn Fn(u64) -> u64| state.get(register).map(f);
+        let binary = |a: Register, b: Register, f: &dyn Fn(u64, u64) -> u64| {
+            state.get(a).zip(state.get(b), f)
+        };
+
+        let value = match instruction.instruction {
+            Lui(u) => Value::Constant(upper(u.imm())),
+            Auipc(u) => Value::Constant(instruction.address.wrapping_add(upper(u.imm()))),
+            Jal(_) | Jalr(_) => Value::Constant(instruction.next_address()),
+            Addi(i) => unary(i.rs1(), &|a| a.wrapping_add(imm(i.imm()))),
+            Slti(i) => signed(i.rs1()).map(|a| ((a as i64) < i.imm() as i64) as u64),
+            Sltiu(i) => unary(i.rs1(), &|a| (a < self.truncate(imm(i.imm()))) as u64),
+            Xori(i) => unary(i.rs1(), &|a| a ^ imm(i.imm())),
+            Ori(i) => unary(i.rs1(), &|a| a | imm(i.imm())),
+            Andi(i) => unary(i.rs1(), &|a| a & imm(i.imm())),
+            Slli(i) => unary(i.rs1(), &|a| a << shamt(imm(i.imm()))),
+            Srli(i) => unary(i.rs1(), &|a| a >> shamt(imm(i.imm()))),
+            Srai(i) => signed(i.rs1()).map(|a| ((a as i64) >> shamt(imm(i.imm()))) as u64),
+            Addiw(i) => unary(i.rs1(), &|a| word(a.wrapping_add(imm(i.imm())))),
+            Slliw(i) => unary(i.rs1(), &|a| word(a << (i.imm() & 0x1f))),
+            Srliw(i) => unary(i.rs1(), &|a| word(((a as u32) >> (i.imm() & 0x1f)) as u64)),
+            Sraiw(i) => unary(i.rs1(), &|a| ((a as i32) >> (i.imm() & 0x1f)) as i64 as u64),
+            Add(r) => binary(r.rs1(), r.rs2(), &|a, b| a.wrapping_add(b)),
+            Sub(r) => binary(r.rs1(), r.rs2(), &|a, b| a.wrapping_sub(b)),
+            Sll(r) => binary(r.rs1(), r.rs2(), &|a, b| a << shamt(b)),
+            Slt(r) => signed(r.rs1()).zip(signed(r.rs2()), |a, b| ((a as i64) < b as i64) as u64),
+            Sltu(r) => binary(r.rs1(), r.rs2(), &|a, b| (a < b) as u64),
+            Xor(r) => binary(r.rs1(), r.rs2(), &|a, b| a ^ b),
+            Srl(r) => binary(r.rs1(), r.rs2(), &|a, b| a >> shamt(b)),
+            Sra(r) => {
+                signed(r.rs1()).zip(state.get(r.rs2()), |a, b| ((a as i64) >> shamt(b)) as u64)
+            }
+            Or(r) => binary(r.rs1(), r.rs2(), &|a, b| a | b),
+            And(r) => binary(r.rs1(), r.rs2(), &|a, b| a & b),
+            Mul(r) => binary(r.rs1(), r.rs2(), &|a, b| a.wrapping_mul(b)),
+            Addw(r) => binary(r.rs1(), r.rs2(), &|a, b| word(a.wrapping_add(b))),
+            Subw(r) => binary(r.rs1(), r.rs2(), &|a, b| word(a.wrapping_sub(b))),
+            Mulw(r) => binary(r.rs1(), r.rs2(), &|a, b| word(a.wrapping_mul(b))),
+            _ => Value::Unknown,
+        };
+
+        value.map(|value| self.truncate(value))
+    }
+
+    /// Registers of RV32 hold the lower 32 bits of a value, zero-extended to 64 bits.
+    fn truncate(&self, value: u64) -> u64 {
+        match self.xlen {
+            Xlen::Rv32 => value as u32 as u64,
+            Xlen::Rv64 => value,
+        }
+    }
+
+    /// Sign-extend the value of an RV32 register for signed comparisons and shifts.
+    fn signed(&self, value: Value) -> Value {
+        match self.xlen {
+            Xlen::Rv32 => value.map(|value| value as u32 as i32 as i64 as u64),
+            Xlen::Rv64 => value,
+        }
+    }
+}
+
+impl Analysis for ConstantPropagation {
+    type Domain = RegisterValues;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn boundary(&self, block: &BasicBlock) -> RegisterValues {
+        if block.start() == self.entry {
+            self.initial.clone()
+        } else {
+            self.assumed.clone()
+        }
+    }
+
+    fn transfer(&self, instruction: &DecodedInstruction, state: &mut RegisterValues) {
+        let value = self.evaluate(instruction, state);
+        let effects = effects(&instruction.instruction);
+
+        effects
+            .writes
+            .union(&effects.clobbers)
+            .iter()
+            .for_each(|register| state.set(register, Value::Unknown));
+
+        if let Some(rd) = instruction.instruction.rd() {
+            state.set(rd, value);
+        }
+    }
+}
+
+impl<'a> Results<'a, ConstantPropagation> {
+    /// The absolute addresses accessed by loads, stores and atomic instructions and the targets
+    /// of `jalr`, by the address of the instruction, wherever they are constant.
+    pub fn resolved_addresses(&self) -> BTreeMap<u64, u64> {
+        self.states()
+            .filter_map(|(instruction, state)| {
+                let address = self.analysis().resolve(instruction, &state)?;
+
+                Some((instruction.address, address))
+            })
+            .collect()
+    }
+
+    /// The targets of all `jalr`s, by the address of the instruction, which are undefined if the
+    /// `jalr` is not reached.
+    fn indirect_targets(&self) -> impl Iterator<Item = (u64, Value)> + '_ {
+        self.states().filter_map(move |(instruction, state)| {
+            let base = match instruction.instruction {
+                Instruction::Jalr(i) => i.rs1(),
+                _ => return None,
+            };
+
+            let target = match self.analysis().resolve(instruction, &state) {
+                Some(target) => Value::Constant(target),
+                None => state.get(base),
+            };
+
+            Some((instruction.address, target))
+        })
+    }
+
+    /// Every instruction together with the values before it.
+    fn states(&self) -> impl Iterator<Item = (&'a DecodedInstruction, RegisterValues)> + '_ {
+        self.cfg()
+            .blocks()
+            .iter()
+            .enumerate()
+            .flat_map(move |(id, block)| {
+                let mut state = self.entry(id).clone();
+
+                block.instructions.iter().map(move |instruction| {
+                    let before = state.clone();
+                    self.analysis().transfer(instruction, &mut state);
+                    (instruction, before)
+                })
+            })
+    }
+}
+
+/// Propagate constants through `cfg` of `program`, see [`ConstantPropagation::new`]. If all calls
+/// at which the global pointer `gp` is known see the same value, as when it is set once by the
+/// start-up code of Selfie or of the C runtime, `gp` is assumed to hold it in every function.
+pub fn constant_propagation<'a>(
+    cfg: &'a Cfg,
+    program: &DecodedProgram,
+) -> Results<'a, ConstantPropagation> {
+    let analysis = ConstantPropagation::new(program);
+    let results = solve(cfg, analysis.clone());
+
+    let global_pointers = results
+        .states()
+        .filter(|(instruction, _)| {
+            matches!(
+                instruction.instruction.control_flow(instruction.address),
+                ControlFlow::Call { .. } | ControlFlow::IndirectCall { .. }
+            )
+        })
+        .filter_map(|(_, state)| state.constant(Register::Gp))
+        .collect::<BTreeSet<_>>();
+
+    match global_pointers.iter().next() {
+        Some(gp) if global_pointers.len() == 1 => {
+            solve(cfg, analysis.with_register(Register::Gp, *gp))
+        }
+        _ => results,
+    }
+}
+
+/// The control-flow graph of `program` in which the `jalr`s whose target is constant are
+/// connected to it, see [`Cfg::with_targets`], including returns whose link register holds a
+/// constant. The new edges may change the constants at other `jalr`s, so constants are propagated
+/// again until no target changes. A `jalr` whose target is no longer constant after it has been
+/// connected stays unresolved.
+pub fn resolve_indirect_jumps(program: &DecodedProgram) -> Cfg {
+    let mut targets = BTreeMap::new();
+
+    loop {
+        let cfg = Cfg::with_targets(program, &targets);
+        let previous = targets.clone();
+
+        for (address, target) in constant_propagation(&cfg, program).indirect_targets() {
+            let resolved = match (target, targets.get(&address)) {
+                (Value::Undefined, _) | (Value::Unknown, None) => continue,
+                (Value::Constant(target), Some(Some(known))) if target == *known => continue,
+                (Value::Constant(target), None) => Some(target),
+                _ => None,
+            };
+
+            targets.insert(address, resolved);
+        }
+
+        if targets == previous {
+            return cfg;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble::assemble, Register::*};
+
+    #[test]
+    fn resolved_addresses() {
+        let program = assemble(
+            "
+            _start:
+                lui gp, 0x11
+                addi gp, gp, 8
+                jal ra, main
+                addi a7, zero, 93
+                ecall
+            main:
+                ld a0, 0(gp)
+                auipc t0, 0
+                addi t0, t0, 12
+                jalr zero, 0(t0)
+                addi a1, zero, 8
+                beq a0, zero, skip
+                addi a1, zero, 16
+            skip:
+                add a2, gp, a1
+                sd a0, 0(a2)
+                sd a0, -8(gp)
+                jalr zero, 0(ra)
+            .data
+                .dword 0
+                .dword 42
+            ",
+        )
+        .unwrap()
+        .decode()
+        .unwrap();
+
+        let cfg = program.cfg();
+        let constants = constant_propagation(&cfg, &program);
+
+        assert_eq!(
+            constants
+                .resolved_addresses()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![(0x10014, 0x11008), (0x10020, 0x10024), (0x10038, 0x11000)]
+        );
+
+        let before_call = constants.before(0x10008).unwrap();
+        assert_eq!(before_call.constant(Gp), Some(0x11008));
+        assert_eq!(before_call.constant(A0), Some(0));
+        assert_eq!(constants.after(0x10008).unwrap().get(A0), Value::Unknown);
+        assert_eq!(
+            constants.after(0x10008).unwrap().constant(Ra),
+            Some(0x1000c)
+        );
+
+        let at_join = constants.before(0x10030).unwrap();
+        assert_eq!(at_join.get(A1), Value::Unknown);
+        assert_eq!(at_join.constant(Zero), Some(0));
+    }
+
+    #[test]
+    fn indirect_jumps() {
+        let program = assemble(
+            "
+                addi a7, zero, 64
+                ecall
+                addi a7, zero, 63
+                lui t1, 0x10
+                auipc t2, 0
+                addi t2, t2, 12
+                jalr zero, 0(t2)
+                jalr zero, 4(t1)
+            ",
+        )
+        .unwrap()
+        .decode()
+        .unwrap();
+
+        let cfg = resolve_indirect_jumps(&program);
+
+        let starts = cfg
+            .blocks()
+            .iter()
+            .map(|block| block.start())
+            .collect::<Vec<_>>();
+
+        assert_eq!(starts, vec![0x10000, 0x10004, 0x1001c]);
+        assert!(cfg.unresolved().is_empty());
+
+        let constants = constant_propagation(&cfg, &program);
+
+        assert_eq!(
+            constants.before(0x1001c).unwrap().constant(T1),
+            Some(0x10000)
+        );
+        assert_eq!(constants.before(0x10004).unwrap().get(A7), Value::Unknown);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let program = assemble(
+            "
+                addi t0, zero, -1
+                srli t1, t0, 60
+                srai t2, t0, 60
+                slli t3, t1, 4
+                addiw t4, t0, 0
+                sltu t5, t1, t0
+                slt t6, t1, t0
+                jalr zero, 0(ra)
+            ",
+        )
+        .unwrap()
+        .decode()
+        .unwrap();
+
+        let cfg = program.cfg();
+        let constants = constant_propagation(&cfg, &program);
+        let values = constants.before(0x1001c).unwrap();
+
+        assert_eq!(values.constant(T0), Some(u64::MAX));
+        assert_eq!(values.constant(T1), Some(0xf));
+        assert_eq!(values.constant(T2), Some(u64::MAX));
+        assert_eq!(values.constant(T3), Some(0xf0));
+        assert_eq!(values.constant(T4), Some(u64::MAX));
+        assert_eq!(values.constant(T5), Some(1));
+        assert_eq!(values.constant(T6), Some(0));
+    }
+}
@@ -0,0 +1,78 @@
+use super::{effects, solve, Analysis, Direction, RegisterSet, Results};
+use crate::{cfg::Cfg, iterators::DecodedInstruction};
+
+/// The registers whose current value may be read later, before it is overwritten.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Liveness;
+
+impl Analysis for Liveness {
+    type Domain = RegisterSet;
+
+    const DIRECTION: Direction = Direction::Backward;
+
+    fn transfer(&self, instruction: &DecodedInstruction, state: &mut RegisterSet) {
+        let effects = effects(&instruction.instruction);
+
+        *state = state.difference(&effects.writes).union(&effects.reads);
+    }
+}
+
+/// The live registers at every block and instruction of `cfg`.
+pub fn liveness(cfg: &Cfg) -> Results<'_, Liveness> {
+    solve(cfg, Liveness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble::assemble, Register, Register::*};
+
+    #[test]
+    fn live_registers() {
+        let program = assemble(
+            "
+                addi t0, zero, 10
+                addi t1, zero, 0
+            loop:
+                add t1, t1, t0
+                addi t0, t0, -1
+                bne t0, zero, loop
+                addi a0, t1, 0
+                jalr zero, 0(ra)
+            ",
+        )
+        .unwrap()
+        .decode()
+        .unwrap();
+
+        let cfg = program.cfg();
+        let liveness = liveness(&cfg);
+
+        // the return reads the return values, the pointers and all callee-saved registers
+        let returned = [
+            A0, A1, Sp, Gp, Tp, Fp, S1, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11,
+        ];
+        let live = |registers: &[Register]| {
+            registers
+                .iter()
+                .chain(returned[1..].iter())
+                .copied()
+                .collect::<RegisterSet>()
+        };
+
+        assert_eq!(liveness.before(0x10000), Some(live(&[Ra])));
+        assert_eq!(liveness.before(0x10004), Some(live(&[Ra, T0])));
+        assert_eq!(liveness.before(0x10008), Some(live(&[Ra, T0, T1])));
+        assert_eq!(liveness.before(0x10014), Some(live(&[Ra, T1])));
+        assert_eq!(liveness.before(0x10018), Some(live(&[Ra, A0])));
+        assert_eq!(
+            liveness.after(0x10014),
+            Some(returned.iter().copied().chain(Some(Ra)).collect())
+        );
+        assert_eq!(liveness.after(0x10018), Some(RegisterSet::new()));
+
+        let loop_block = cfg.block_at(0x10008).unwrap();
+        assert_eq!(liveness.entry(loop_block), &live(&[Ra, T0, T1]));
+        assert_eq!(liveness.exit(loop_block), &live(&[Ra, T0, T1]));
+    }
+}
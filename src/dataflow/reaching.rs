@@ -0,0 +1,180 @@
+use super::{effects, solve, Analysis, Direction, Results};
+use crate::{cfg::Cfg, iterators::DecodedInstruction, Register};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A write of `register` by the instruction at `address`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Definition {
+    pub address: u64,
+    pub register: Register,
+}
+
+/// A read of `register` by the instruction at `address`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Use {
+    pub address: u64,
+    pub register: Register,
+}
+
+/// The definitions which may reach an instruction without being overwritten on the way. Values
+/// which are defined before the entry of a function have no definition.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ReachingDefinitions;
+
+impl Analysis for ReachingDefinitions {
+    type Domain = BTreeSet<Definition>;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn transfer(&self, instruction: &DecodedInstruction, state: &mut Self::Domain) {
+        let effects = effects(&instruction.instruction);
+        let address = instruction.address;
+
+        state.retain(|definition| !effects.writes.contains(definition.register));
+
+        for register in effects.writes.iter().chain(effects.clobbers.iter()) {
+            state.insert(Definition { address, register });
+        }
+    }
+}
+
+/// The reaching definitions at every block and instruction of `cfg`.
+pub fn reaching_definitions(cfg: &Cfg) -> Results<'_, ReachingDefinitions> {
+    solve(cfg, ReachingDefinitions)
+}
+
+/// The links between the definitions of registers and their uses.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DefUseChains {
+    uses: BTreeMap<Definition, Vec<Use>>,
+    definitions: BTreeMap<Use, Vec<Definition>>,
+}
+
+impl DefUseChains {
+    pub fn new(cfg: &Cfg) -> DefUseChains {
+        let reaching = reaching_definitions(cfg);
+        let mut chains = DefUseChains::default();
+
+        for (id, block) in cfg.blocks().iter().enumerate() {
+            let mut state = reaching.entry(id).clone();
+
+            for instruction in block.instructions.iter() {
+                let address = instruction.address;
+                let effects = effects(&instruction.instruction);
+
+                for register in effects.reads.iter() {
+                    let used = Use { address, register };
+
+                    let definitions = state
+                        .iter()
+                        .filter(|definition| definition.register == register)
+                        .copied()
+                        .collect::<Vec<_>>();
+
+                    for definition in definitions.iter() {
+                        chains.uses.entry(*definition).or_default().push(used);
+                    }
+
+                    chains.definitions.insert(used, definitions);
+                }
+
+                for register in effects.writes.iter().chain(effects.clobbers.iter()) {
+                    chains
+                        .uses
+                        .entry(Definition { address, register })
+                        .or_default();
+                }
+
+                reaching.analysis().transfer(instruction, &mut state);
+            }
+        }
+
+        chains.uses.values_mut().for_each(|uses| uses.sort());
+
+        chains
+    }
+
+    /// The uses which may read the value written by `definition`.
+    pub fn uses(&self, definition: Definition) -> &[Use] {
+        self.uses.get(&definition).map_or(&[], Vec::as_slice)
+    }
+
+    /// The definitions whose value may be read by `used`. Empty if the value is defined before
+    /// the entry of the function.
+    pub fn definitions(&self, used: Use) -> &[Definition] {
+        self.definitions.get(&used).map_or(&[], Vec::as_slice)
+    }
+
+    /// All definitions, ordered by address.
+    pub fn iter_definitions(&self) -> impl Iterator<Item = &Definition> {
+        self.uses.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble::assemble, Register::*};
+
+    #[test]
+    fn def_use_chains() {
+        let program = assemble(
+            "
+                addi a0, zero, 1
+                beq a1, zero, skip
+                addi a0, zero, 2
+            skip:
+                add s1, a0, a0
+                jal ra, function
+                add a3, s1, a0
+            function:
+                jalr zero, 0(ra)
+            ",
+        )
+        .unwrap()
+        .decode()
+        .unwrap();
+
+        let cfg = program.cfg();
+        let chains = DefUseChains::new(&cfg);
+
+        let definition = |address, register| Definition { address, register };
+        let used = |address, register| Use { address, register };
+
+        assert_eq!(
+            chains.definitions(used(0x1000c, A0)),
+            &[definition(0x10000, A0), definition(0x10008, A0)]
+        );
+        assert_eq!(
+            chains.uses(definition(0x10000, A0)),
+            &[
+                used(0x1000c, A0),
+                used(0x10010, A0),
+                used(0x10014, A0),
+                used(0x10018, A0)
+            ]
+        );
+        assert_eq!(chains.definitions(used(0x10004, A1)), &[]);
+
+        // the call may overwrite a0, but not s1
+        assert_eq!(
+            chains.definitions(used(0x10014, A0)),
+            &[
+                definition(0x10000, A0),
+                definition(0x10008, A0),
+                definition(0x10010, A0)
+            ]
+        );
+        assert_eq!(
+            chains.definitions(used(0x10014, S1)),
+            &[definition(0x1000c, S1)]
+        );
+
+        let reaching = reaching_definitions(&cfg);
+        assert!(reaching
+            .after(0x10008)
+            .unwrap()
+            .iter()
+            .all(|d| d.address != 0x10000));
+    }
+}
@@ -15,8 +15,10 @@
 // This module was modified by the Selfie authors.
 
 use crate::decompress::*;
+use crate::isa::{Extension, IsaProfile, Xlen};
 use crate::{types::*, Instruction};
 use log::trace;
+use std::convert::TryFrom;
 use thiserror::Error;
 
 pub const INSTRUCTION_SIZE: usize = 4;
@@ -50,6 +52,14 @@ pub enum DecodingError {
     /// Instruction is illegal
     #[error("Instruction is illegal")]
     Illegal,
+
+    /// Instruction is well defined but not part of the ISA profile of the decoder
+    #[error("Instruction `{0}` is not part of the ISA profile")]
+    OutsideProfile(Instruction),
+
+    /// Instruction only exists in RV64 but is decoded for RV32
+    #[error("Instruction `{0}` requires RV64")]
+    RequiresRv64(Instruction),
 }
 
 type DecodingResult = Result<Instruction, DecodingError>;
@@ -86,7 +96,7 @@ pub fn decode(i: u32) -> DecodingResult {
     match i & 0b11 {
         0b11 => match (i >> 2) & 0b11111 {
             0b00000 => decode_load(i),
-            0b00001 => Err(DecodingError::Unimplemented), // Load-FP
+            0b00001 => decode_load_fp(i),
             0b00010 => Err(DecodingError::Custom),
             0b00011 => decode_fence(i), // misc mem instruction
             0b00100 => decode_op_imm(i),
@@ -95,7 +105,7 @@ pub fn decode(i: u32) -> DecodingResult {
             0b00111 => Err(DecodingError::Reserved),     // 48bit instruction
 
             0b01000 => decode_store(i),
-            0b01001 => Err(DecodingError::Unimplemented), // Store-FP
+            0b01001 => decode_store_fp(i),
             0b01010 => Err(DecodingError::Custom),
             0b01011 => decode_amo(i),
             0b01100 => decode_op(i),
@@ -103,11 +113,11 @@ pub fn decode(i: u32) -> DecodingResult {
             0b01110 => decode_op32(i),                 // op32 instruction
             0b01111 => Err(DecodingError::Reserved),   // 64bit instruction
 
-            0b10000 => Err(DecodingError::Unimplemented), // MADD
-            0b10001 => Err(DecodingError::Unimplemented), // MSUB
-            0b10010 => Err(DecodingError::Unimplemented), // NMSUB
-            0b10011 => Err(DecodingError::Unimplemented), // NMADD
-            0b10100 => Err(DecodingError::Unimplemented), // OP-FP
+            0b10000 => decode_fused(i, Instruction::Fmadds, Instruction::Fmaddd),
+            0b10001 => decode_fused(i, Instruction::Fmsubs, Instruction::Fmsubd),
+            0b10010 => decode_fused(i, Instruction::Fnmsubs, Instruction::Fnmsubd),
+            0b10011 => decode_fused(i, Instruction::Fnmadds, Instruction::Fnmaddd),
+            0b10100 => decode_op_fp(i),
             0b10101 => Err(DecodingError::Reserved),
             0b10110 => Err(DecodingError::Custom),
             0b10111 => Err(DecodingError::Reserved), // 48bit instruction
@@ -129,6 +139,82 @@ pub fn decode(i: u32) -> DecodingResult {
     }
 }
 
+/// Decode the given instruction for a base ISA with integer registers of width `xlen`.
+///
+/// On RV32, instructions which only exist in RV64 are reported as [`DecodingError::RequiresRv64`],
+/// shift amounts above 31 are reserved and compressed instructions are decompressed as RV32C.
+pub fn decode_xlen(i: u32, xlen: Xlen) -> DecodingResult {
+    if xlen == Xlen::Rv64 {
+        return decode(i);
+    }
+
+    if i & 0b11 != 0b11 {
+        return decompress_xlen((i & 0xffff) as u16, xlen).and_then(|i| decode_xlen(i, xlen));
+    }
+
+    // encodings of rev8 and zext.h differ between RV32 and RV64
+    match i & 0xfff0_707f {
+        0x6980_5013 => return Ok(Instruction::Rev8(IType(i))),
+        0x0800_4033 => return Ok(Instruction::Zexth(RType(i))),
+        _ => {}
+    }
+
+    let instruction = decode(i)?;
+    let shamt_above_31 = (i >> 25) & 0b1 == 1;
+
+    match instruction {
+        Instruction::Slli(_)
+        | Instruction::Srli(_)
+        | Instruction::Srai(_)
+        | Instruction::Rori(_)
+        | Instruction::Bclri(_)
+        | Instruction::Bexti(_)
+        | Instruction::Binvi(_)
+        | Instruction::Bseti(_)
+        | Instruction::Rev8(_)
+            if shamt_above_31 =>
+        {
+            Err(DecodingError::Reserved)
+        }
+        // the RV64 encoding of zext.h, which lives in the OP-32 opcode space
+        Instruction::Zexth(_) => Err(DecodingError::RequiresRv64(instruction)),
+        _ if instruction.requires_rv64() => Err(DecodingError::RequiresRv64(instruction)),
+        _ => Ok(instruction),
+    }
+}
+
+/// A decoder which only accepts the instructions of an [`IsaProfile`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Decoder {
+    profile: IsaProfile,
+}
+
+impl Decoder {
+    pub fn new(profile: IsaProfile) -> Self {
+        Self { profile }
+    }
+
+    pub fn profile(&self) -> IsaProfile {
+        self.profile
+    }
+
+    /// Decode the given instruction. Instructions outside of the profile, including compressed
+    /// instructions if [`Extension::C`] is not part of it, are reported as
+    /// [`DecodingError::OutsideProfile`].
+    pub fn decode(&self, i: u32) -> DecodingResult {
+        let instruction = decode_xlen(i, self.profile.xlen())?;
+        let compressed = i & 0b11 != 0b11;
+
+        if self.profile.accepts(&instruction)
+            && (!compressed || self.profile.contains(Extension::C))
+        {
+            Ok(instruction)
+        } else {
+            Err(DecodingError::OutsideProfile(instruction))
+        }
+    }
+}
+
 #[inline(always)]
 fn decode_load(i: u32) -> DecodingResult {
     match (i >> 12) & 0b111 {
@@ -156,6 +242,22 @@ fn decode_op_imm(i: u32) -> DecodingResult {
         (0b000000, 0b001) => Ok(Instruction::Slli(IType(i))),
         (0b000000, 0b101) => Ok(Instruction::Srli(IType(i))),
         (0b010000, 0b101) => Ok(Instruction::Srai(IType(i))),
+        // bit-manipulation extensions
+        (0b011000, 0b001) => match (i >> 20) & 0b111111 {
+            0b000000 => Ok(Instruction::Clz(IType(i))),
+            0b000001 => Ok(Instruction::Ctz(IType(i))),
+            0b000010 => Ok(Instruction::Cpop(IType(i))),
+            0b000100 => Ok(Instruction::Sextb(IType(i))),
+            0b000101 => Ok(Instruction::Sexth(IType(i))),
+            _ => Err(DecodingError::Unknown),
+        },
+        (0b011000, 0b101) => Ok(Instruction::Rori(IType(i))),
+        (0b001010, 0b101) if (i >> 20) & 0b111111 == 0b000111 => Ok(Instruction::Orcb(IType(i))),
+        (0b011010, 0b101) if (i >> 20) & 0b111111 == 0b111000 => Ok(Instruction::Rev8(IType(i))),
+        (0b010010, 0b001) => Ok(Instruction::Bclri(IType(i))),
+        (0b010010, 0b101) => Ok(Instruction::Bexti(IType(i))),
+        (0b011010, 0b001) => Ok(Instruction::Binvi(IType(i))),
+        (0b001010, 0b001) => Ok(Instruction::Bseti(IType(i))),
         _ => Err(DecodingError::Unknown),
     }
 }
@@ -167,6 +269,15 @@ fn decode_op_imm32(i: u32) -> DecodingResult {
         (0b0000000, 0b001) => Ok(Instruction::Slliw(IType(i))),
         (0b0000000, 0b101) => Ok(Instruction::Srliw(IType(i))),
         (0b0100000, 0b101) => Ok(Instruction::Sraiw(IType(i))),
+        // bit-manipulation extensions
+        (0b0000100, 0b001) | (0b0000101, 0b001) => Ok(Instruction::Slliuw(IType(i))),
+        (0b0110000, 0b001) => match (i >> 20) & 0b11111 {
+            0b00000 => Ok(Instruction::Clzw(IType(i))),
+            0b00001 => Ok(Instruction::Ctzw(IType(i))),
+            0b00010 => Ok(Instruction::Cpopw(IType(i))),
+            _ => Err(DecodingError::Unknown),
+        },
+        (0b0110000, 0b101) => Ok(Instruction::Roriw(IType(i))),
         _ => Err(DecodingError::Unknown),
     }
 }
@@ -203,6 +314,26 @@ fn decode_op(i: u32) -> DecodingResult {
         (0b0000001, 0b101) => Ok(Instruction::Divu(RType(i))),
         (0b0000001, 0b110) => Ok(Instruction::Rem(RType(i))),
         (0b0000001, 0b111) => Ok(Instruction::Remu(RType(i))),
+        // bit-manipulation extensions
+        (0b0010000, 0b010) => Ok(Instruction::Sh1add(RType(i))),
+        (0b0010000, 0b100) => Ok(Instruction::Sh2add(RType(i))),
+        (0b0010000, 0b110) => Ok(Instruction::Sh3add(RType(i))),
+        (0b0100000, 0b111) => Ok(Instruction::Andn(RType(i))),
+        (0b0100000, 0b110) => Ok(Instruction::Orn(RType(i))),
+        (0b0100000, 0b100) => Ok(Instruction::Xnor(RType(i))),
+        (0b0000101, 0b110) => Ok(Instruction::Max(RType(i))),
+        (0b0000101, 0b111) => Ok(Instruction::Maxu(RType(i))),
+        (0b0000101, 0b100) => Ok(Instruction::Min(RType(i))),
+        (0b0000101, 0b101) => Ok(Instruction::Minu(RType(i))),
+        (0b0110000, 0b001) => Ok(Instruction::Rol(RType(i))),
+        (0b0110000, 0b101) => Ok(Instruction::Ror(RType(i))),
+        (0b0000101, 0b001) => Ok(Instruction::Clmul(RType(i))),
+        (0b0000101, 0b011) => Ok(Instruction::Clmulh(RType(i))),
+        (0b0000101, 0b010) => Ok(Instruction::Clmulr(RType(i))),
+        (0b0100100, 0b001) => Ok(Instruction::Bclr(RType(i))),
+        (0b0100100, 0b101) => Ok(Instruction::Bext(RType(i))),
+        (0b0110100, 0b001) => Ok(Instruction::Binv(RType(i))),
+        (0b0010100, 0b001) => Ok(Instruction::Bset(RType(i))),
         _ => Err(DecodingError::Unknown),
     }
 }
@@ -220,6 +351,14 @@ fn decode_op32(i: u32) -> DecodingResult {
         (0b0000001, 0b101) => Ok(Instruction::Divuw(RType(i))),
         (0b0000001, 0b110) => Ok(Instruction::Remw(RType(i))),
         (0b0000001, 0b111) => Ok(Instruction::Remuw(RType(i))),
+        // bit-manipulation extensions
+        (0b0000100, 0b000) => Ok(Instruction::Adduw(RType(i))),
+        (0b0010000, 0b010) => Ok(Instruction::Sh1adduw(RType(i))),
+        (0b0010000, 0b100) => Ok(Instruction::Sh2adduw(RType(i))),
+        (0b0010000, 0b110) => Ok(Instruction::Sh3adduw(RType(i))),
+        (0b0000100, 0b100) if (i >> 20) & 0b11111 == 0 => Ok(Instruction::Zexth(RType(i))),
+        (0b0110000, 0b001) => Ok(Instruction::Rolw(RType(i))),
+        (0b0110000, 0b101) => Ok(Instruction::Rorw(RType(i))),
         _ => Err(DecodingError::Unknown),
     }
 }
@@ -237,10 +376,32 @@ fn decode_branch(i: u32) -> DecodingResult {
 }
 
 fn decode_system(i: u32) -> DecodingResult {
+    match (i >> 12) & 0b111 {
+        0b000 => decode_privileged(i),
+        0b001 => Ok(Instruction::Csrrw(IType(i))),
+        0b010 => Ok(Instruction::Csrrs(IType(i))),
+        0b011 => Ok(Instruction::Csrrc(IType(i))),
+        0b101 => Ok(Instruction::Csrrwi(IType(i))),
+        0b110 => Ok(Instruction::Csrrsi(IType(i))),
+        0b111 => Ok(Instruction::Csrrci(IType(i))),
+        _ => Err(DecodingError::Unknown),
+    }
+}
+
+#[inline(always)]
+fn decode_privileged(i: u32) -> DecodingResult {
     match i {
         // Environment Call and Breakpoint
         0b0000_0000_0000_0000_0000_0000_0111_0011 => Ok(Instruction::Ecall(IType(i))),
         0b0000_0000_0001_0000_0000_0000_0111_0011 => Ok(Instruction::Ebreak(IType(i))),
+        // Trap-Return and Interrupt-Management
+        0b0001_0000_0010_0000_0000_0000_0111_0011 => Ok(Instruction::Sret(IType(i))),
+        0b0011_0000_0010_0000_0000_0000_0111_0011 => Ok(Instruction::Mret(IType(i))),
+        0b0001_0000_0101_0000_0000_0000_0111_0011 => Ok(Instruction::Wfi(IType(i))),
+        // Supervisor Memory-Management
+        _ if i >> 25 == 0b0001001 && (i >> 7) & 0b11111 == 0 => {
+            Ok(Instruction::SfenceVma(RType(i)))
+        }
         _ => Err(DecodingError::Unknown),
     }
 }
@@ -275,10 +436,113 @@ fn decode_amo(i: u32) -> DecodingResult {
     }
 }
 
+#[inline(always)]
+fn decode_load_fp(i: u32) -> DecodingResult {
+    match (i >> 12) & 0b111 {
+        0b010 => Ok(Instruction::Flw(IType(i))),
+        0b011 => Ok(Instruction::Fld(IType(i))),
+        _ => Err(DecodingError::Unimplemented), // half, quad precision and vector loads
+    }
+}
+
+#[inline(always)]
+fn decode_store_fp(i: u32) -> DecodingResult {
+    match (i >> 12) & 0b111 {
+        0b010 => Ok(Instruction::Fsw(SType(i))),
+        0b011 => Ok(Instruction::Fsd(SType(i))),
+        _ => Err(DecodingError::Unimplemented), // half, quad precision and vector stores
+    }
+}
+
+/// Check whether the funct3 field of a floating-point instruction is a valid rounding mode.
+fn is_rounding_mode(i: u32) -> bool {
+    RoundingMode::try_from((i >> 12) & 0b111).is_ok()
+}
+
+#[inline(always)]
+fn decode_fused(
+    i: u32,
+    single: fn(R4Type) -> Instruction,
+    double: fn(R4Type) -> Instruction,
+) -> DecodingResult {
+    match (i >> 25) & 0b11 {
+        _ if !is_rounding_mode(i) => Err(DecodingError::Reserved),
+        0b00 => Ok(single(R4Type(i))),
+        0b01 => Ok(double(R4Type(i))),
+        _ => Err(DecodingError::Unimplemented), // half and quad precision
+    }
+}
+
+#[inline(always)]
+fn decode_op_fp(i: u32) -> DecodingResult {
+    if (i >> 25) & 0b11 > 0b01 {
+        return Err(DecodingError::Unimplemented); // half and quad precision
+    }
+
+    // funct3 is either the rounding mode or selects the operation
+    let rm = is_rounding_mode(i);
+
+    match (i >> 25, (i >> 20) & 0b11111, (i >> 12) & 0b111) {
+        (0b0000000, _, _) if rm => Ok(Instruction::Fadds(RType(i))),
+        (0b0000100, _, _) if rm => Ok(Instruction::Fsubs(RType(i))),
+        (0b0001000, _, _) if rm => Ok(Instruction::Fmuls(RType(i))),
+        (0b0001100, _, _) if rm => Ok(Instruction::Fdivs(RType(i))),
+        (0b0101100, 0b00000, _) if rm => Ok(Instruction::Fsqrts(RType(i))),
+        (0b0010000, _, 0b000) => Ok(Instruction::Fsgnjs(RType(i))),
+        (0b0010000, _, 0b001) => Ok(Instruction::Fsgnjns(RType(i))),
+        (0b0010000, _, 0b010) => Ok(Instruction::Fsgnjxs(RType(i))),
+        (0b0010100, _, 0b000) => Ok(Instruction::Fmins(RType(i))),
+        (0b0010100, _, 0b001) => Ok(Instruction::Fmaxs(RType(i))),
+        (0b1100000, 0b00000, _) if rm => Ok(Instruction::Fcvtws(RType(i))),
+        (0b1100000, 0b00001, _) if rm => Ok(Instruction::Fcvtwus(RType(i))),
+        (0b1100000, 0b00010, _) if rm => Ok(Instruction::Fcvtls(RType(i))),
+        (0b1100000, 0b00011, _) if rm => Ok(Instruction::Fcvtlus(RType(i))),
+        (0b1101000, 0b00000, _) if rm => Ok(Instruction::Fcvtsw(RType(i))),
+        (0b1101000, 0b00001, _) if rm => Ok(Instruction::Fcvtswu(RType(i))),
+        (0b1101000, 0b00010, _) if rm => Ok(Instruction::Fcvtsl(RType(i))),
+        (0b1101000, 0b00011, _) if rm => Ok(Instruction::Fcvtslu(RType(i))),
+        (0b1010000, _, 0b010) => Ok(Instruction::Feqs(RType(i))),
+        (0b1010000, _, 0b001) => Ok(Instruction::Flts(RType(i))),
+        (0b1010000, _, 0b000) => Ok(Instruction::Fles(RType(i))),
+        (0b1110000, 0b00000, 0b001) => Ok(Instruction::Fclasss(RType(i))),
+        (0b1110000, 0b00000, 0b000) => Ok(Instruction::Fmvxw(RType(i))),
+        (0b1111000, 0b00000, 0b000) => Ok(Instruction::Fmvwx(RType(i))),
+        (0b0000001, _, _) if rm => Ok(Instruction::Faddd(RType(i))),
+        (0b0000101, _, _) if rm => Ok(Instruction::Fsubd(RType(i))),
+        (0b0001001, _, _) if rm => Ok(Instruction::Fmuld(RType(i))),
+        (0b0001101, _, _) if rm => Ok(Instruction::Fdivd(RType(i))),
+        (0b0101101, 0b00000, _) if rm => Ok(Instruction::Fsqrtd(RType(i))),
+        (0b0010001, _, 0b000) => Ok(Instruction::Fsgnjd(RType(i))),
+        (0b0010001, _, 0b001) => Ok(Instruction::Fsgnjnd(RType(i))),
+        (0b0010001, _, 0b010) => Ok(Instruction::Fsgnjxd(RType(i))),
+        (0b0010101, _, 0b000) => Ok(Instruction::Fmind(RType(i))),
+        (0b0010101, _, 0b001) => Ok(Instruction::Fmaxd(RType(i))),
+        (0b1100001, 0b00000, _) if rm => Ok(Instruction::Fcvtwd(RType(i))),
+        (0b1100001, 0b00001, _) if rm => Ok(Instruction::Fcvtwud(RType(i))),
+        (0b1100001, 0b00010, _) if rm => Ok(Instruction::Fcvtld(RType(i))),
+        (0b1100001, 0b00011, _) if rm => Ok(Instruction::Fcvtlud(RType(i))),
+        (0b1101001, 0b00000, _) if rm => Ok(Instruction::Fcvtdw(RType(i))),
+        (0b1101001, 0b00001, _) if rm => Ok(Instruction::Fcvtdwu(RType(i))),
+        (0b1101001, 0b00010, _) if rm => Ok(Instruction::Fcvtdl(RType(i))),
+        (0b1101001, 0b00011, _) if rm => Ok(Instruction::Fcvtdlu(RType(i))),
+        (0b1010001, _, 0b010) => Ok(Instruction::Feqd(RType(i))),
+        (0b1010001, _, 0b001) => Ok(Instruction::Fltd(RType(i))),
+        (0b1010001, _, 0b000) => Ok(Instruction::Fled(RType(i))),
+        (0b1110001, 0b00000, 0b001) => Ok(Instruction::Fclassd(RType(i))),
+        (0b1110001, 0b00000, 0b000) => Ok(Instruction::Fmvxd(RType(i))),
+        (0b1111001, 0b00000, 0b000) => Ok(Instruction::Fmvdx(RType(i))),
+        (0b0100000, 0b00001, _) if rm => Ok(Instruction::Fcvtsd(RType(i))),
+        (0b0100001, 0b00000, _) if rm => Ok(Instruction::Fcvtds(RType(i))),
+        _ if !rm => Err(DecodingError::Reserved),
+        _ => Err(DecodingError::Unknown),
+    }
+}
+
 #[inline(always)]
 fn decode_fence(i: u32) -> DecodingResult {
     match (i >> 12) & 0b111 {
         0b000 => Ok(Instruction::Fence(IType(i))),
+        0b001 => Ok(Instruction::FenceI(IType(i))),
         _ => Err(DecodingError::Unknown),
     }
 }
@@ -367,5 +631,357 @@ mod tests {
     #[test]
     fn system() {
         assert_eq!(decode(0x00000073).unwrap(), Instruction::new_ecall()); // ecall
+        assert_eq!(decode(0x00100073).unwrap(), Instruction::new_ebreak()); // ebreak
+        assert_eq!(decode(0x10200073).unwrap(), Instruction::new_sret()); // sret
+        assert_eq!(decode(0x30200073).unwrap(), Instruction::new_mret()); // mret
+        assert_eq!(decode(0x10500073).unwrap(), Instruction::new_wfi()); // wfi
+        assert_eq!(decode(0x0000100f).unwrap(), Instruction::new_fence_i()); // fence.i
+        assert_eq!(decode(0x12b50073).unwrap(), SfenceVma(RType(0x12b50073))); // sfence.vma x10,x11
+        assert_eq!(decode(0x00004073), Err(DecodingError::Unknown));
+        assert_eq!(decode(0x12b50573), Err(DecodingError::Unknown));
+    }
+
+    #[test]
+    fn csr() {
+        use crate::{Csr, Register::*};
+
+        assert_eq!(decode(0x30002573).unwrap(), Csrrs(IType(0x30002573))); // csrrs x10,mstatus,x0
+        assert_eq!(decode(0x30551073).unwrap(), Csrrw(IType(0x30551073))); // csrrw x0,mtvec,x10
+        assert_eq!(decode(0x0035b573).unwrap(), Csrrc(IType(0x0035b573))); // csrrc x10,fcsr,x11
+        assert_eq!(decode(0x30046073).unwrap(), Csrrsi(IType(0x30046073))); // csrrsi x0,mstatus,8
+        assert_eq!(decode(0x10017073).unwrap(), Csrrci(IType(0x10017073))); // csrrci x0,sstatus,2
+
+        assert_eq!(
+            decode(0x30002573).unwrap(),
+            Instruction::new_csrrs(A0, Csr::MSTATUS, Zero)
+        );
+        assert_eq!(
+            decode(0x30551073).unwrap(),
+            Instruction::new_csrrw(Zero, Csr::MTVEC, A0)
+        );
+        assert_eq!(
+            decode(0xc00027f3).unwrap(),
+            Instruction::new_csrrs(A5, Csr::CYCLE, Zero)
+        );
+        assert_eq!(
+            decode(0x30046073).unwrap(),
+            Instruction::new_csrrsi(Zero, Csr::MSTATUS, 8)
+        );
+        assert_eq!(
+            decode(0xfff0d073).unwrap(),
+            Instruction::new_csrrwi(Zero, Csr(0xfff), 1)
+        );
+        assert_eq!(
+            decode(0x12b50073).unwrap(),
+            Instruction::new_sfence_vma(A0, A1)
+        );
+    }
+
+    #[test]
+    fn decoder_with_profile() {
+        let riscu = Decoder::new(IsaProfile::RISCU);
+        let rv64imac = Decoder::new("rv64imac".parse().unwrap());
+
+        assert_eq!(riscu.decode(0x02b50533).unwrap(), Mul(RType(0x02b50533))); // mul x10,x10,x11
+        assert_eq!(
+            riscu.decode(0x02b5053b),
+            Err(DecodingError::OutsideProfile(Mulw(RType(0x02b5053b)))) // mulw x10,x10,x11
+        );
+        assert_eq!(
+            riscu.decode(0x952e),
+            Err(DecodingError::OutsideProfile(Add(RType(0x00b50533)))) // c.add x10,x11
+        );
+        assert_eq!(riscu.decode(0x00004073), Err(DecodingError::Unknown));
+        assert_eq!(riscu.decode(0x00000000), Err(DecodingError::Illegal));
+
+        assert_eq!(rv64imac.decode(0x952e).unwrap(), Add(RType(0x00b50533))); // c.add x10,x11
+        assert_eq!(
+            rv64imac.decode(0x30200073),
+            Err(DecodingError::OutsideProfile(Mret(IType(0x30200073)))) // mret
+        );
+    }
+
+    #[test]
+    fn rv32() {
+        use crate::isa::Xlen::*;
+
+        assert_eq!(
+            decode_xlen(0x00000073, Rv32).unwrap(),
+            Ecall(IType(0x00000073))
+        ); // ecall
+        assert_eq!(decode_xlen(0x2001, Rv32).unwrap(), Jal(JType(0x000000ef))); // c.jal 0
+        assert_eq!(
+            decode_xlen(0x01f51513, Rv32).unwrap(),
+            Slli(IType(0x01f51513))
+        ); // slli x10,x10,31
+        assert_eq!(decode_xlen(0x02051513, Rv32), Err(DecodingError::Reserved)); // slli x10,x10,32
+        assert_eq!(
+            decode_xlen(0x00053503, Rv32),
+            Err(DecodingError::RequiresRv64(Ld(IType(0x00053503)))) // ld x10,0(x10)
+        );
+        assert_eq!(
+            decode_xlen(0x02b5053b, Rv32),
+            Err(DecodingError::RequiresRv64(Mulw(RType(0x02b5053b)))) // mulw x10,x10,x11
+        );
+
+        // rev8 and zext.h are encoded differently
+        assert_eq!(
+            decode_xlen(0x69855513, Rv32).unwrap(),
+            Rev8(IType(0x69855513))
+        ); // rev8 x10,x10
+        assert_eq!(decode_xlen(0x6b855513, Rv32), Err(DecodingError::Reserved));
+        assert_eq!(
+            decode_xlen(0x0805c533, Rv32).unwrap(),
+            Zexth(RType(0x0805c533))
+        ); // zext.h x10,x11
+        assert_eq!(
+            decode_xlen(0x0805c53b, Rv32),
+            Err(DecodingError::RequiresRv64(Zexth(RType(0x0805c53b))))
+        );
+
+        assert_eq!(decode_xlen(0x00053503, Rv64), decode(0x00053503));
+        assert_eq!(
+            Decoder::new(IsaProfile::RV32IMAC).decode(0x2001).unwrap(),
+            Jal(JType(0x000000ef))
+        );
+    }
+
+    #[test]
+    fn bit_manipulation() {
+        use crate::Register::*;
+
+        assert_eq!(
+            decode(0x08b5053b).unwrap(),
+            Instruction::new_adduw(A0, A0, A1)
+        );
+        assert_eq!(
+            decode(0x20b52533).unwrap(),
+            Instruction::new_sh1add(A0, A0, A1)
+        );
+        assert_eq!(
+            decode(0x0a05151b).unwrap(),
+            Instruction::new_slliuw(A0, A0, 32)
+        );
+        assert_eq!(
+            decode(0x40b57533).unwrap(),
+            Instruction::new_andn(A0, A0, A1)
+        );
+        assert_eq!(decode(0x60059513).unwrap(), Instruction::new_clz(A0, A1));
+        assert_eq!(decode(0x6025151b).unwrap(), Instruction::new_cpopw(A0, A0));
+        assert_eq!(decode(0x60451513).unwrap(), Instruction::new_sextb(A0, A0));
+        assert_eq!(decode(0x0805c53b).unwrap(), Instruction::new_zexth(A0, A1));
+        assert_eq!(
+            decode(0x0ab56533).unwrap(),
+            Instruction::new_max(A0, A0, A1)
+        );
+        assert_eq!(
+            decode(0x60b5153b).unwrap(),
+            Instruction::new_rolw(A0, A0, A1)
+        );
+        assert_eq!(
+            decode(0x60355513).unwrap(),
+            Instruction::new_rori(A0, A0, 3)
+        );
+        assert_eq!(
+            decode(0x61f5551b).unwrap(),
+            Instruction::new_roriw(A0, A0, 31)
+        );
+        assert_eq!(decode(0x28755513).unwrap(), Instruction::new_orcb(A0, A0));
+        assert_eq!(decode(0x6b855513).unwrap(), Instruction::new_rev8(A0, A0));
+        assert_eq!(
+            decode(0x0ab51533).unwrap(),
+            Instruction::new_clmul(A0, A0, A1)
+        );
+        assert_eq!(
+            decode(0x28b51533).unwrap(),
+            Instruction::new_bset(A0, A0, A1)
+        );
+        assert_eq!(
+            decode(0x2bf51513).unwrap(),
+            Instruction::new_bseti(A0, A0, 63)
+        );
+        assert_eq!(
+            decode(0x4855d513).unwrap(),
+            Instruction::new_bexti(A0, A1, 5)
+        );
+
+        assert_eq!(decode(0x60351513), Err(DecodingError::Unknown)); // clz with rs2 = 3
+        assert_eq!(decode(0x08b5453b), Err(DecodingError::Unknown)); // zext.h with rs2 = a1
+        assert_eq!(decode(0x28855513), Err(DecodingError::Unknown)); // orc.b with wrong imm
+    }
+
+    #[test]
+    fn floating_point() {
+        assert_eq!(decode(0x00452507).unwrap(), Flw(IType(0x00452507))); // flw f10,4(x10)
+        assert_eq!(decode(0x00813787).unwrap(), Fld(IType(0x00813787))); // fld f15,8(x2)
+        assert_eq!(decode(0x00a12227).unwrap(), Fsw(SType(0x00a12227))); // fsw f10,4(x2)
+        assert_eq!(decode(0x00813427).unwrap(), Fsd(SType(0x00813427))); // fsd f8,8(x2)
+        assert_eq!(decode(0x6ac5f543).unwrap(), Fmaddd(R4Type(0x6ac5f543))); // fmadd.d f10,f11,f12,f13
+        assert_eq!(decode(0x02b57553).unwrap(), Faddd(RType(0x02b57553))); // fadd.d f10,f10,f11
+        assert_eq!(decode(0x58057553).unwrap(), Fsqrts(RType(0x58057553))); // fsqrt.s f10,f10
+        assert_eq!(decode(0x22b58553).unwrap(), Fsgnjd(RType(0x22b58553))); // fsgnj.d f10,f11,f11
+        assert_eq!(decode(0xa2b52553).unwrap(), Feqd(RType(0xa2b52553))); // feq.d x10,f10,f11
+        assert_eq!(decode(0xc2051553).unwrap(), Fcvtwd(RType(0xc2051553))); // fcvt.w.d x10,f10,rtz
+        assert_eq!(decode(0xd2078753).unwrap(), Fcvtdw(RType(0xd2078753))); // fcvt.d.w f14,x15
+        assert_eq!(decode(0x40157553).unwrap(), Fcvtsd(RType(0x40157553))); // fcvt.s.d f10,f10
+        assert_eq!(decode(0x42050553).unwrap(), Fcvtds(RType(0x42050553))); // fcvt.d.s f10,f10
+        assert_eq!(decode(0xe2050553).unwrap(), Fmvxd(RType(0xe2050553))); // fmv.x.d x10,f10
+        assert_eq!(decode(0xe2051553).unwrap(), Fclassd(RType(0xe2051553))); // fclass.d x10,f10
+        assert_eq!(decode(0xf2078753).unwrap(), Fmvdx(RType(0xf2078753))); // fmv.d.x f14,x15
+
+        // reserved rounding modes
+        assert_eq!(decode(0x02b55553), Err(DecodingError::Reserved)); // fadd.d f10,f10,f11
+        assert_eq!(decode(0x6ac5e543), Err(DecodingError::Reserved)); // fmadd.d f10,f11,f12,f13
+
+        // quad precision
+        assert_eq!(decode(0x06b57553), Err(DecodingError::Unimplemented)); // fadd.q f10,f10,f11
+    }
+
+    #[test]
+    fn floating_point_constructors() {
+        use crate::types::RoundingMode::*;
+        use crate::{FRegister::*, Register::*};
+
+        assert_eq!(
+            decode(0x00452507).unwrap(),
+            Instruction::new_flw(Fa0, A0, 4)
+        );
+        assert_eq!(
+            decode(0x00813787).unwrap(),
+            Instruction::new_fld(Fa5, Sp, 8)
+        );
+        assert_eq!(
+            decode(0x00a12227).unwrap(),
+            Instruction::new_fsw(Sp, Fa0, 4)
+        );
+        assert_eq!(
+            decode(0x00813427).unwrap(),
+            Instruction::new_fsd(Sp, Fs0, 8)
+        );
+        assert_eq!(
+            decode(0x6ac5f543).unwrap(),
+            Instruction::new_fmaddd(Fa0, Fa1, Fa2, Fa3, Dyn)
+        );
+        assert_eq!(
+            decode(0x02b57553).unwrap(),
+            Instruction::new_faddd(Fa0, Fa0, Fa1, Dyn)
+        );
+        assert_eq!(
+            decode(0x58057553).unwrap(),
+            Instruction::new_fsqrts(Fa0, Fa0, Dyn)
+        );
+        assert_eq!(
+            decode(0x22b58553).unwrap(),
+            Instruction::new_fsgnjd(Fa0, Fa1, Fa1)
+        );
+        assert_eq!(
+            decode(0xa2b52553).unwrap(),
+            Instruction::new_feqd(A0, Fa0, Fa1)
+        );
+        assert_eq!(
+            decode(0xc2051553).unwrap(),
+            Instruction::new_fcvtwd(A0, Fa0, Rtz)
+        );
+        assert_eq!(
+            decode(0xd2078753).unwrap(),
+            Instruction::new_fcvtdw(Fa4, A5)
+        );
+        assert_eq!(
+            decode(0x40157553).unwrap(),
+            Instruction::new_fcvtsd(Fa0, Fa0, Dyn)
+        );
+        assert_eq!(
+            decode(0x42050553).unwrap(),
+            Instruction::new_fcvtds(Fa0, Fa0)
+        );
+        assert_eq!(decode(0xe2050553).unwrap(), Instruction::new_fmvxd(A0, Fa0));
+        assert_eq!(
+            decode(0xe2051553).unwrap(),
+            Instruction::new_fclassd(A0, Fa0)
+        );
+        assert_eq!(decode(0xf2078753).unwrap(), Instruction::new_fmvdx(Fa4, A5));
+    }
+
+    #[test]
+    fn constructors() {
+        use crate::Register::*;
+
+        assert_eq!(decode(0x000322b7).unwrap(), Instruction::new_lui(T0, 0x32)); // lui x5,0x32
+        assert_eq!(decode(0xfffff7b7).unwrap(), Instruction::new_lui(A5, -1)); // lui x15,0xfffff
+        assert_eq!(decode(0x00001517).unwrap(), Instruction::new_auipc(A0, 1)); // auipc x10,0x1
+        assert_eq!(
+            decode(0x00810513).unwrap(),
+            Instruction::new_addi(A0, Sp, 8)
+        ); // addi x10,x2,8
+        assert_eq!(decode(0x01853683).unwrap(), Instruction::new_ld(A3, A0, 24)); // ld x13,24(x10)
+        assert_eq!(decode(0x00f6b423).unwrap(), Instruction::new_sd(A3, A5, 8)); // sd x15,8(x13)
+        assert_eq!(
+            decode(0x00c58633).unwrap(),
+            Instruction::new_add(A2, A1, A2)
+        ); // add x12,x11,x12
+        assert_eq!(
+            decode(0x40b50533).unwrap(),
+            Instruction::new_sub(A0, A0, A1)
+        ); // sub x10,x10,x11
+        assert_eq!(
+            decode(0x02208f33).unwrap(),
+            Instruction::new_mul(T5, Ra, Sp)
+        ); // mul x30,x1,x2
+        assert_eq!(
+            decode(0x0220df33).unwrap(),
+            Instruction::new_divu(T5, Ra, Sp)
+        ); // divu x30,x1,x2
+        assert_eq!(
+            decode(0x0220ff33).unwrap(),
+            Instruction::new_remu(T5, Ra, Sp)
+        ); // remu x30,x1,x2
+        assert_eq!(
+            decode(0x0020bf33).unwrap(),
+            Instruction::new_sltu(T5, Ra, Sp)
+        ); // sltu x30,x1,x2
+        assert_eq!(
+            decode(0x00078463).unwrap(),
+            Instruction::new_beq(A5, Zero, 8)
+        ); // beq x15,x0,8
+        assert_eq!(
+            decode(0xd89ff0ef).unwrap(),
+            Instruction::new_jal(Ra, -0x278)
+        ); // jal x1,-0x278
+        assert_eq!(
+            decode(0x000780e7).unwrap(),
+            Instruction::new_jalr(Ra, A5, 0)
+        ); // jalr x1,0(x15)
+        assert_eq!(
+            decode(0x00379793).unwrap(),
+            Instruction::new_slli(A5, A5, 3)
+        ); // slli x15,x15,3
+        assert_eq!(
+            decode(0x00375713).unwrap(),
+            Instruction::new_srli(A4, A4, 3)
+        ); // srli x14,x14,3
+        assert_eq!(
+            decode(0x43f45413).unwrap(),
+            Instruction::new_srai(Fp, Fp, 63)
+        ); // srai x8,x8,63
+        assert_eq!(
+            decode(0x4037d79b).unwrap(),
+            Instruction::new_sraiw(A5, A5, 3)
+        ); // sraiw x15,x15,3
+        assert_eq!(
+            decode(0x100526af).unwrap(),
+            Instruction::new_lrw(A3, A0, Zero)
+        ); // lr.w x13,(x10)
+        assert_eq!(
+            decode(0x18c5272f).unwrap(),
+            Instruction::new_scw(A4, A0, A2)
+        ); // sc.w x14,x12,(x10)
+        assert_eq!(
+            decode(0x08b6b6af).unwrap(),
+            Instruction::new_amoswapd(A3, A3, A1)
+        ); // amoswap.d x13,x11,(x13)
+        assert_eq!(
+            decode(0x00b5b02f).unwrap(),
+            Instruction::new_amoaddd(Zero, A1, A1)
+        ); // amoadd.d x0,x11,(x11)
     }
 }
@@ -1,11 +1,44 @@
 mod detail;
 mod util;
 
-use crate::DecodingError;
+use crate::{isa::Xlen, DecodingError, Register};
 use detail::*;
 
 type DecompressionResult = Result<u32, DecodingError>;
 
+/// Decompress a compressed instruction from any quadrant to the corresponding 32-bit instruction.
+pub fn decompress(i: u16) -> DecompressionResult {
+    match i & 0b11 {
+        0b00 => decompress_q0(i),
+        0b01 => decompress_q1(i),
+        0b10 => decompress_q2(i),
+        _ => Err(DecodingError::Illegal),
+    }
+}
+
+/// Decompress a compressed instruction of RV32C or RV64C. The encodings of C.LD, C.SD, C.LDSP
+/// and C.SDSP are C.FLW, C.FSW, C.FLWSP and C.FSWSP on RV32, C.ADDIW is C.JAL and C.SUBW, C.ADDW
+/// as well as shift amounts above 31 are reserved.
+pub fn decompress_xlen(i: u16, xlen: Xlen) -> DecompressionResult {
+    if xlen == Xlen::Rv64 {
+        return decompress(i);
+    }
+
+    let funct2 = (i >> 10) & 0b11;
+    let bit12 = (i >> 12) & 0b1;
+
+    match (i & 0b11, (i >> 13) & 0b111) {
+        (0b00, 0b011) => decompress_load(i, CiInstr::Flw),
+        (0b00, 0b111) => decompress_store(i, CsInstr::Fsw),
+        (0b01, 0b001) => decompress_jump(i, Register::Ra),
+        (0b01, 0b100) if bit12 == 1 && funct2 != 0b10 => Err(DecodingError::Reserved),
+        (0b10, 0b000) if bit12 == 1 => Err(DecodingError::Reserved),
+        (0b10, 0b011) => decompress_load_sp(i, CiInstr::Flw),
+        (0b10, 0b111) => decompress_store_sp(i, CsInstr::Fsw),
+        _ => decompress(i),
+    }
+}
+
 /// Decompress compressed instructions from quadrant zero to the corresponding 32-bit instruction.
 pub fn decompress_q0(i: u16) -> DecompressionResult {
     if i == 0 {
@@ -14,11 +47,11 @@ pub fn decompress_q0(i: u16) -> DecompressionResult {
 
     match (i >> 13) & 0b111 {
         0b000 => decompress_addi4spn(i),
-        0b001 => Err(DecodingError::Unimplemented), // C.LFD
+        0b001 => decompress_load(i, CiInstr::Fld),
         0b010 => decompress_load(i, CiInstr::Lw),
         0b011 => decompress_load(i, CiInstr::Ld),
         0b100 => Err(DecodingError::Reserved),
-        0b101 => Err(DecodingError::Unimplemented), // C.FSD
+        0b101 => decompress_store(i, CsInstr::Fsd),
         0b110 => decompress_store(i, CsInstr::Sw),
         0b111 => decompress_store(i, CsInstr::Sd),
         _ => unreachable!(),
@@ -33,7 +66,7 @@ pub fn decompress_q1(i: u16) -> DecompressionResult {
         0b010 => decompress_li(i),
         0b011 => decompress_lui_addi16sp(i),
         0b100 => decompress_misc_alu(i),
-        0b101 => decompress_jump(i),
+        0b101 => decompress_jump(i, Register::Zero),
         0b110 => decompress_branch(i, CbInstr::Beq),
         0b111 => decompress_branch(i, CbInstr::Bne),
         _ => unreachable!(),
@@ -44,19 +77,97 @@ pub fn decompress_q1(i: u16) -> DecompressionResult {
 pub fn decompress_q2(i: u16) -> DecompressionResult {
     match (i >> 13) & 0b111 {
         0b000 => decompress_slli(i),
-        0b001 => Err(DecodingError::Unimplemented), // C.FLSDP
+        0b001 => decompress_load_sp(i, CiInstr::Fld),
         0b010 => decompress_load_sp(i, CiInstr::Lw),
         0b011 => decompress_load_sp(i, CiInstr::Ld),
         0b100 => decompress_jr_mv_add(i),
-        0b101 => Err(DecodingError::Unimplemented), // C.FSDSP
+        0b101 => decompress_store_sp(i, CsInstr::Fsd),
         0b110 => decompress_store_sp(i, CsInstr::Sw),
         0b111 => decompress_store_sp(i, CsInstr::Sd),
         _ => unreachable!(),
     }
 }
 
+/// Return the mnemonic of a compressed instruction (e.g. `c.addi`), or `None` if the encoding
+/// does not belong to any compressed instruction.
+pub fn compressed_mnemonic(i: u16) -> Option<&'static str> {
+    compressed_mnemonic_xlen(i, Xlen::Rv64)
+}
+
+/// Return the mnemonic of a compressed instruction of RV32C or RV64C (see [`decompress_xlen`]).
+pub fn compressed_mnemonic_xlen(i: u16, xlen: Xlen) -> Option<&'static str> {
+    if xlen == Xlen::Rv32 {
+        match (i & 0b11, (i >> 13) & 0b111) {
+            (0b00, 0b011) => return Some("c.flw"),
+            (0b00, 0b111) => return Some("c.fsw"),
+            (0b01, 0b001) => return Some("c.jal"),
+            (0b10, 0b011) => return Some("c.flwsp"),
+            (0b10, 0b111) => return Some("c.fswsp"),
+            _ => {}
+        }
+    }
+
+    let rd = (i >> 7) & 0b1_1111;
+    let rs2 = (i >> 2) & 0b1_1111;
+
+    let mnemonic = match (i & 0b11, (i >> 13) & 0b111) {
+        (0b00, _) if i == 0 => return None,
+        (0b00, 0b000) => "c.addi4spn",
+        (0b00, 0b001) => "c.fld",
+        (0b00, 0b010) => "c.lw",
+        (0b00, 0b011) => "c.ld",
+        (0b00, 0b100) => return None,
+        (0b00, 0b101) => "c.fsd",
+        (0b00, 0b110) => "c.sw",
+        (0b00, 0b111) => "c.sd",
+
+        (0b01, 0b000) if rd == 0 => "c.nop",
+        (0b01, 0b000) => "c.addi",
+        (0b01, 0b001) => "c.addiw",
+        (0b01, 0b010) => "c.li",
+        (0b01, 0b011) if rd == 2 => "c.addi16sp",
+        (0b01, 0b011) => "c.lui",
+        (0b01, 0b100) => match ((i >> 10) & 0b11, (i >> 12) & 0b1, (i >> 5) & 0b11) {
+            (0b00, _, _) => "c.srli",
+            (0b01, _, _) => "c.srai",
+            (0b10, _, _) => "c.andi",
+            (0b11, 0b0, 0b00) => "c.sub",
+            (0b11, 0b0, 0b01) => "c.xor",
+            (0b11, 0b0, 0b10) => "c.or",
+            (0b11, 0b0, 0b11) => "c.and",
+            (0b11, 0b1, 0b00) => "c.subw",
+            (0b11, 0b1, 0b01) => "c.addw",
+            _ => return None,
+        },
+        (0b01, 0b101) => "c.j",
+        (0b01, 0b110) => "c.beqz",
+        (0b01, 0b111) => "c.bnez",
+
+        (0b10, 0b000) => "c.slli",
+        (0b10, 0b001) => "c.fldsp",
+        (0b10, 0b010) => "c.lwsp",
+        (0b10, 0b011) => "c.ldsp",
+        (0b10, 0b100) => match ((i >> 12) & 0b1, rd, rs2) {
+            (0b0, _, 0) => "c.jr",
+            (0b0, _, _) => "c.mv",
+            (0b1, 0, 0) => "c.ebreak",
+            (0b1, _, 0) => "c.jalr",
+            (0b1, _, _) => "c.add",
+            _ => unreachable!(),
+        },
+        (0b10, 0b101) => "c.fsdsp",
+        (0b10, 0b110) => "c.swsp",
+        (0b10, 0b111) => "c.sdsp",
+
+        _ => return None,
+    };
+
+    Some(mnemonic)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::{decode, types::*, Instruction::*};
 
     // The bulk of the instructions were obtained by compiling selfie with
@@ -67,7 +178,8 @@ mod tests {
         // C.ADDI4SPN
         assert_eq!(decode(0x002c).unwrap(), Addi(IType(0x00810593))); // addi a1, sp, 8
 
-        // C.LFD unimplemented
+        // C.FLD
+        assert_eq!(decompress_q0(0x2108), Ok(0x00053507)); // fld fa0, 0(a0)
 
         // C.LW
         assert_eq!(decode(0x4298).unwrap(), Lw(IType(0x0006a703))); // lw a4, 0(a3)
@@ -83,7 +195,8 @@ mod tests {
         // C.LD: check that no sign extension happens (smoke test)
         assert_eq!(decode(0x7ffc).unwrap(), Ld(IType(0x0f87b783))); // ld a5, 248(a5)
 
-        // C.FSD unimplemented
+        // C.FSD
+        assert_eq!(decompress_q0(0xa508), Ok(0x00a53427)); // fsd fa0, 8(a0)
 
         // C.SW
         assert_eq!(decode(0xc298).unwrap(), Sw(SType(0x00e6a023))); // sw a4, 0(a3)
@@ -159,7 +272,8 @@ mod tests {
         // C.SUB
         assert_eq!(decode(0x8e09).unwrap(), Sub(RType(0x40a60633))); // sub a2, a2, a0
 
-        // C.XOR unimplemented
+        // C.XOR
+        assert_eq!(decode(0x8f3d).unwrap(), Xor(RType(0x00f74733))); // xor a4, a4, a5
 
         // C.OR
         assert_eq!(decode(0x8f5d).unwrap(), Or(RType(0x00f76733))); // or a4, a4, a5
@@ -179,7 +293,8 @@ mod tests {
         // C.SLLI
         assert_eq!(decode(0x078e).unwrap(), Slli(IType(0x00379793))); // slli a5, a5, 0x3
 
-        // C.FLDSP unimplemented
+        // C.FLDSP
+        assert_eq!(decompress_q2(0x27a2), Ok(0x00813787)); // fld fa5, 8(sp)
 
         // C.LWSP
         assert_eq!(decode(0x4502).unwrap(), Lw(IType(0x00012503))); // lw a0, 0(sp)
@@ -195,7 +310,11 @@ mod tests {
         assert_eq!(decode(0x9782).unwrap(), Jalr(IType(0x000780e7))); // jalr a5
         assert_eq!(decode(0x97ba).unwrap(), Add(RType(0x00e787b3))); // add a5, a5, a4
 
-        // C.FSDSP unimplemented
+        // C.EBREAK
+        assert_eq!(decode(0x9002).unwrap(), Ebreak(IType(0x00100073))); // ebreak
+
+        // C.FSDSP
+        assert_eq!(decompress_q2(0xa422), Ok(0x00813427)); // fsd fs0, 8(sp)
 
         // C.SWSP
         assert_eq!(decode(0xd03e).unwrap(), Sw(SType(0x02f12023))); // sw a5, 21(sp)
@@ -204,4 +323,79 @@ mod tests {
         assert_eq!(decode(0xe022).unwrap(), Sd(SType(0x00813023))); // sd s0, 0(sp)
         assert_eq!(decode(0xec06).unwrap(), Sd(SType(0x00113c23))); // sd ra, 24(sp)
     }
+
+    #[test]
+    fn test_hints() {
+        assert_eq!(decode(0x0005).unwrap(), Addi(IType(0x00100013))); // c.addi zero, 1
+        assert_eq!(decode(0x0501).unwrap(), Addi(IType(0x00050513))); // c.addi a0, 0
+        assert_eq!(decode(0x4005).unwrap(), Addi(IType(0x00100013))); // c.li zero, 1
+        assert_eq!(decode(0x6005).unwrap(), Lui(UType(0x00001037))); // c.lui zero, 1
+        assert_eq!(decode(0x8101).unwrap(), Srli(IType(0x00055513))); // c.srli a0, 0
+        assert_eq!(decode(0x0006).unwrap(), Slli(IType(0x00101013))); // c.slli zero, 1
+        assert_eq!(decode(0x802a).unwrap(), Add(RType(0x00a00033))); // c.mv zero, a0
+        assert_eq!(decode(0x902a).unwrap(), Add(RType(0x00a00033))); // c.add zero, a0
+    }
+
+    #[test]
+    fn test_reserved() {
+        for i in [
+            0x0004, // c.addi4spn a0, sp, 0
+            0x8000, // quadrant 0, funct3 = 100
+            0x2005, // c.addiw zero, 1
+            0x6101, // c.addi16sp sp, 0
+            0x6501, // c.lui a0, 0
+            0x9c41, // quadrant 1, funct6 = 100111, funct2 = 10
+            0x4002, // c.lwsp zero, 0(sp)
+            0x6002, // c.ldsp zero, 0(sp)
+            0x8002, // c.jr zero
+        ] {
+            assert_eq!(decompress(i), Err(DecodingError::Reserved), "{:#06x}", i);
+        }
+    }
+
+    #[test]
+    fn test_rv32() {
+        let rv32 = |i| decompress_xlen(i, Xlen::Rv32);
+
+        assert_eq!(rv32(0x2001), Ok(0x000000ef)); // c.jal 0
+        assert_eq!(rv32(0x3ffd), Ok(0xfffff0ef)); // c.jal -2
+        assert_eq!(rv32(0x6108), Ok(0x00052507)); // c.flw fa0, 0(a0)
+        assert_eq!(rv32(0xe02a), Ok(0x00a12027)); // c.fswsp fa0, 0(sp)
+        assert_eq!(rv32(0x1502), Err(DecodingError::Reserved)); // c.slli a0, 32
+        assert_eq!(rv32(0x9d2d), Err(DecodingError::Reserved)); // c.addw a0, a1
+        assert_eq!(rv32(0x0505), decompress(0x0505)); // c.addi a0, 1
+
+        assert_eq!(decompress(0x2001), Err(DecodingError::Reserved)); // c.addiw zero, 0
+        assert_eq!(compressed_mnemonic_xlen(0x2001, Xlen::Rv32), Some("c.jal"));
+        assert_eq!(compressed_mnemonic_xlen(0x6108, Xlen::Rv32), Some("c.flw"));
+        assert_eq!(compressed_mnemonic(0x6108), Some("c.ld"));
+    }
+
+    #[test]
+    fn test_total() {
+        for i in (0..=u16::MAX).filter(|i| i & 0b11 != 0b11) {
+            // Must not panic and every valid encoding has a name.
+            if decompress(i).is_ok() {
+                assert!(compressed_mnemonic(i).is_some(), "{:#06x}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compressed_mnemonic() {
+        assert_eq!(compressed_mnemonic(0x0000), None);
+        assert_eq!(compressed_mnemonic(0x002c), Some("c.addi4spn"));
+        assert_eq!(compressed_mnemonic(0x0001), Some("c.nop"));
+        assert_eq!(compressed_mnemonic(0x7139), Some("c.addi16sp"));
+        assert_eq!(compressed_mnemonic(0x6785), Some("c.lui"));
+        assert_eq!(compressed_mnemonic(0x8e09), Some("c.sub"));
+        assert_eq!(compressed_mnemonic(0x9f21), Some("c.addw"));
+        assert_eq!(compressed_mnemonic(0x8782), Some("c.jr"));
+        assert_eq!(compressed_mnemonic(0x853e), Some("c.mv"));
+        assert_eq!(compressed_mnemonic(0x9002), Some("c.ebreak"));
+        assert_eq!(compressed_mnemonic(0x9782), Some("c.jalr"));
+        assert_eq!(compressed_mnemonic(0x97ba), Some("c.add"));
+        assert_eq!(compressed_mnemonic(0xec06), Some("c.sdsp"));
+        assert_eq!(compressed_mnemonic(0x0013), None);
+    }
 }
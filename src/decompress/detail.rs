@@ -8,7 +8,9 @@ pub(super) fn decompress_addi4spn(i: u16) -> DecompressionResult {
     let imm = get_imm(i, InstrFormat::Ciw).inv_permute(&[5, 4, 9, 8, 7, 6, 2, 3]);
     let rd = 8 + ((i >> 2) & 0b111);
 
-    assert!(imm != 0, "imm == 0 is reserved!");
+    if imm == 0 {
+        return Err(DecodingError::Reserved);
+    }
 
     Ok(build_itype(CiInstr::Addi, rd, Register::Sp as u16, imm))
 }
@@ -20,7 +22,9 @@ pub(super) fn decompress_load(i: u16, instruction_type: CiInstr) -> Decompressio
 
     Ok(match instruction_type {
         CiInstr::Lw => build_itype(CiInstr::Lw, rd, rs1, imm.inv_permute(&[5, 4, 3, 2, 6])),
+        CiInstr::Flw => build_itype(CiInstr::Flw, rd, rs1, imm.inv_permute(&[5, 4, 3, 2, 6])),
         CiInstr::Ld => build_itype(CiInstr::Ld, rd, rs1, imm.inv_permute(&[5, 4, 3, 7, 6])),
+        CiInstr::Fld => build_itype(CiInstr::Fld, rd, rs1, imm.inv_permute(&[5, 4, 3, 7, 6])),
         _ => unreachable!(),
     })
 }
@@ -32,7 +36,9 @@ pub(super) fn decompress_store(i: u16, instruction_type: CsInstr) -> Decompressi
 
     Ok(match instruction_type {
         CsInstr::Sw => build_stype(CsInstr::Sw, rs1, rs2, imm.inv_permute(&[5, 4, 3, 2, 6])),
+        CsInstr::Fsw => build_stype(CsInstr::Fsw, rs1, rs2, imm.inv_permute(&[5, 4, 3, 2, 6])),
         CsInstr::Sd => build_stype(CsInstr::Sd, rs1, rs2, imm.inv_permute(&[5, 4, 3, 7, 6])),
+        CsInstr::Fsd => build_stype(CsInstr::Fsd, rs1, rs2, imm.inv_permute(&[5, 4, 3, 7, 6])),
     })
 }
 // }}}
@@ -43,29 +49,23 @@ pub(super) fn decompress_addi(i: u16, instruction_type: CiInstr) -> Decompressio
     let dest = (i >> 7) & 0b1_1111;
 
     if matches!(instruction_type, CiInstr::Addiw) {
-        assert!(dest != 0, "dest == 0 is reserved!");
+        if dest == 0 {
+            return Err(DecodingError::Reserved);
+        }
 
         return Ok(build_itype(CiInstr::Addiw, dest, dest, imm));
     }
 
-    if dest == 0 {
-        // The instruction is the pseudoinstruction nop.
-        assert!(imm == 0, "imm != 0 is a HINT!");
-
-        Ok(build_itype(CiInstr::Addi, dest, dest, imm))
-    } else {
-        assert!(imm != 0, "imm == 0 is a HINT!");
-
-        Ok(build_itype(CiInstr::Addi, dest, dest, imm))
-    }
+    // dest == 0 is C.NOP, dest == 0 with imm != 0 and imm == 0 with dest != 0 are HINTs which
+    // expand to instructions without any effect.
+    Ok(build_itype(CiInstr::Addi, dest, dest, imm))
 }
 
 pub(super) fn decompress_li(i: u16) -> DecompressionResult {
     let rd = (i >> 7) & 0b11111;
     let imm = sign_extend16(get_imm(i, InstrFormat::Ci), 6);
 
-    assert!(rd != 0, "rd == 0 is reserved!");
-
+    // rd == 0 is a HINT
     Ok(build_itype(CiInstr::Addi, rd, Register::Zero as u16, imm))
 }
 
@@ -73,25 +73,27 @@ pub(super) fn decompress_lui_addi16sp(i: u16) -> DecompressionResult {
     let rd = (i >> 7) & 0b1_1111;
     let imm = get_imm(i, InstrFormat::Ci);
 
-    assert!(rd != 0, "rd = 0 is reserved!");
+    if imm == 0 {
+        return Err(DecodingError::Reserved);
+    }
 
     if rd == 2 {
         /* C.ADDI16SP */
-        assert!(imm != 0, "imm = 0 is reserved!");
-
         let imm = imm.inv_permute(&[9, 4, 6, 8, 7, 5]);
 
         Ok(build_itype(CiInstr::Addi, rd, rd, sign_extend16(imm, 10)))
     } else {
+        // rd == 0 is a HINT
         let imm = (imm as u32).inv_permute(&[17, 16, 15, 14, 13, 12]);
 
         Ok(build_utype(CuInstr::Lui, rd, sign_extend32(imm, 18)))
     }
 }
 
-pub(super) fn decompress_jump(i: u16) -> DecompressionResult {
+/// C.J, or C.JAL (which links to `ra`) for RV32.
+pub(super) fn decompress_jump(i: u16, rd: Register) -> DecompressionResult {
     let imm = get_imm(i, InstrFormat::Cj).inv_permute(&[11, 4, 9, 8, 10, 6, 7, 3, 2, 1, 5]);
-    Ok(build_jtype(imm))
+    Ok(build_jtype(rd, imm))
 }
 
 pub(super) fn decompress_misc_alu(i: u16) -> DecompressionResult {
@@ -100,16 +102,14 @@ pub(super) fn decompress_misc_alu(i: u16) -> DecompressionResult {
             let shamt = get_imm(i, InstrFormat::Ci);
             let rd_rs1 = 8 + ((i >> 7) & 0b111);
 
-            assert!(shamt != 0, "shamt == 0 is a HINT!");
-
+            // shamt == 0 is a HINT
             Ok(build_itype(CiInstr::Srli, rd_rs1, rd_rs1, shamt))
         }
         0b01 => {
             let shamt = get_imm(i, InstrFormat::Ci);
             let rd_rs1 = 8 + ((i >> 7) & 0b111);
 
-            assert!(shamt != 0, "shamt == 0 is a HINT!");
-
+            // shamt == 0 is a HINT
             Ok(build_itype(CiInstr::Srai, rd_rs1, rd_rs1, shamt))
         }
         0b10 => {
@@ -129,6 +129,7 @@ pub(super) fn decompress_misc_alu(i: u16) -> DecompressionResult {
 
             match ((i >> 12) & 0b1, (i >> 5) & 0b11) {
                 (0, 0b00) => Ok(build_rtype(CrInstr::Sub, rs1_rd, rs1_rd, rs2)),
+                (0, 0b01) => Ok(build_rtype(CrInstr::Xor, rs1_rd, rs1_rd, rs2)),
                 (0, 0b10) => Ok(build_rtype(CrInstr::Or, rs1_rd, rs1_rd, rs2)),
                 (0, 0b11) => Ok(build_rtype(CrInstr::And, rs1_rd, rs1_rd, rs2)),
                 (1, 0b01) => Ok(build_rtype(CrInstr::Addw, rs1_rd, rs1_rd, rs2)),
@@ -138,7 +139,7 @@ pub(super) fn decompress_misc_alu(i: u16) -> DecompressionResult {
                 _ => unreachable!(),
             }
         }
-        _ => Err(DecodingError::Unimplemented),
+        _ => unreachable!(),
     }
 }
 
@@ -155,9 +156,7 @@ pub(super) fn decompress_slli(i: u16) -> DecompressionResult {
     let shamt = get_imm(i, InstrFormat::Ci);
     let rd_rs1 = (i >> 7) & 0b1_1111;
 
-    assert!(rd_rs1 != 0, "rd_rs1 == 0 is reserved!");
-    assert!(shamt != 0, "shamt == 0 is a HINT!");
-
+    // rd_rs1 == 0 and shamt == 0 are HINTs
     Ok(build_itype(CiInstr::Slli, rd_rs1, rd_rs1, shamt))
 }
 
@@ -166,41 +165,42 @@ pub(super) fn decompress_load_sp(i: u16, instruction_type: CiInstr) -> Decompres
     let rs1 = Register::Sp as u16;
     let rd = (i >> 7) & 0b1_1111;
 
-    assert!(rd != 0, "rd == 0 is reserved!");
+    if rd == 0 && !matches!(instruction_type, CiInstr::Flw | CiInstr::Fld) {
+        return Err(DecodingError::Reserved);
+    }
 
     match instruction_type {
         CiInstr::Lw => {
             let imm = imm.inv_permute(&[5, 4, 3, 2, 7, 6]);
             Ok(build_itype(CiInstr::Lw, rd, rs1, imm))
         }
+        CiInstr::Flw => {
+            let imm = imm.inv_permute(&[5, 4, 3, 2, 7, 6]);
+            Ok(build_itype(CiInstr::Flw, rd, rs1, imm))
+        }
         CiInstr::Ld => {
             let imm = imm.inv_permute(&[5, 4, 3, 8, 7, 6]);
             Ok(build_itype(CiInstr::Ld, rd, rs1, imm))
         }
+        CiInstr::Fld => {
+            let imm = imm.inv_permute(&[5, 4, 3, 8, 7, 6]);
+            Ok(build_itype(CiInstr::Fld, rd, rs1, imm))
+        }
         _ => unreachable!(),
     }
 }
 
 pub(super) fn decompress_jr_mv_add(i: u16) -> DecompressionResult {
     match ((i >> 12) & 0b1, (i >> 7) & 0b1_1111, (i >> 2) & 0b1_1111) {
-        (0, rs1, 0) /* C.JR */ => {
-            assert!(rs1 != 0, "rs1 == 0 is reserved!");
-
-            Ok(build_itype(CiInstr::Jalr, Register::Zero as u16, rs1, 0))
-        },
-        (0, rd, rs2) /* C.MV */ => {
-            assert!(rd != 0, "rd == 0 is reserved!");
-
+        (0, 0, 0) /* C.JR */ => Err(DecodingError::Reserved),
+        (0, rs1, 0) /* C.JR */ => Ok(build_itype(CiInstr::Jalr, Register::Zero as u16, rs1, 0)),
+        (0, rd, rs2) /* C.MV, rd == 0 is a HINT */ => {
             Ok(build_rtype(CrInstr::Add, rd, Register::Zero as u16, rs2))
         },
-        (1, 0, 0) /* C.EBREAK */ => Err(DecodingError::Unimplemented),
-        (1, rs1, 0) => Ok(build_itype(CiInstr::Jalr, Register::Ra as u16, rs1, 0)),
-        (1, rd, rs2) => {
-            assert!(!(rd == 0 && rs2 != 0), "rd == 0 && rs2 != 0 is a HINT!");
-
-            Ok(build_rtype(CrInstr::Add, rd, rd, rs2))
-        },
-        (_, _, _) => Err(DecodingError::Unimplemented),
+        (1, 0, 0) /* C.EBREAK */ => Ok(build_itype(CiInstr::Ebreak, 0, 0, 0)),
+        (1, rs1, 0) /* C.JALR */ => Ok(build_itype(CiInstr::Jalr, Register::Ra as u16, rs1, 0)),
+        (1, rd, rs2) /* C.ADD, rd == 0 is a HINT */ => Ok(build_rtype(CrInstr::Add, rd, rd, rs2)),
+        (_, _, _) => unreachable!(),
     }
 }
 
@@ -211,7 +211,9 @@ pub(super) fn decompress_store_sp(i: u16, instruction_type: CsInstr) -> Decompre
 
     Ok(match instruction_type {
         CsInstr::Sw => build_stype(CsInstr::Sw, rs1, rs2, imm.inv_permute(&[5, 4, 3, 2, 7, 6])),
+        CsInstr::Fsw => build_stype(CsInstr::Fsw, rs1, rs2, imm.inv_permute(&[5, 4, 3, 2, 7, 6])),
         CsInstr::Sd => build_stype(CsInstr::Sd, rs1, rs2, imm.inv_permute(&[5, 4, 3, 8, 7, 6])),
+        CsInstr::Fsd => build_stype(CsInstr::Fsd, rs1, rs2, imm.inv_permute(&[5, 4, 3, 8, 7, 6])),
     })
 }
 // }}}
@@ -3,6 +3,7 @@ use crate::Register;
 pub(super) enum CrInstr {
     Sub,
     Add,
+    Xor,
     Or,
     And,
     Subw,
@@ -15,7 +16,10 @@ pub(super) enum CiInstr {
     Andi,
     Lw,
     Ld,
+    Flw,
+    Fld,
     Jalr,
+    Ebreak,
     Slli,
     Srli,
     Srai,
@@ -29,6 +33,8 @@ pub(super) enum CbInstr {
 pub(super) enum CsInstr {
     Sw,
     Sd,
+    Fsw,
+    Fsd,
 }
 
 pub(super) enum CuInstr {
@@ -47,6 +53,7 @@ pub(super) fn build_rtype(instruction_type: CrInstr, rd: u16, rs1: u16, rs2: u16
     match instruction_type {
         CrInstr::Sub => mold(0b0100000, rs2, rs1, 0b000, rd, 0b0110011),
         CrInstr::Add => mold(0b0000000, rs2, rs1, 0b000, rd, 0b0110011),
+        CrInstr::Xor => mold(0b0000000, rs2, rs1, 0b100, rd, 0b0110011),
         CrInstr::Or => mold(0b0000000, rs2, rs1, 0b110, rd, 0b0110011),
         CrInstr::And => mold(0b0000000, rs2, rs1, 0b111, rd, 0b0110011),
         CrInstr::Subw => mold(0b0100000, rs2, rs1, 0b000, rd, 0b0111011),
@@ -69,7 +76,10 @@ pub(super) fn build_itype(instruction_type: CiInstr, rd: u16, rs1: u16, imm: u16
         CiInstr::Andi => mold(imm, rs1, 0b111, rd, 0b0010011),
         CiInstr::Lw => mold(imm, rs1, 0b010, rd, 0b0000011),
         CiInstr::Ld => mold(imm, rs1, 0b011, rd, 0b0000011),
+        CiInstr::Flw => mold(imm, rs1, 0b010, rd, 0b0000111),
+        CiInstr::Fld => mold(imm, rs1, 0b011, rd, 0b0000111),
         CiInstr::Jalr => mold(imm, rs1, 0b000, rd, 0b1100111),
+        CiInstr::Ebreak => mold(1, 0, 0b000, 0, 0b1110011),
         CiInstr::Slli => mold(imm, rs1, 0b001, rd, 0b0010011),
         CiInstr::Srai => mold((0b0100000 << 5) | imm, rs1, 0b101, rd, 0b0010011),
         CiInstr::Srli => mold(imm, rs1, 0b101, rd, 0b0010011),
@@ -110,6 +120,8 @@ pub(super) fn build_stype(instruction_type: CsInstr, rs1: u16, rs2: u16, imm: u1
     match instruction_type {
         CsInstr::Sw => mold(rs2, rs1, 0b010, imm, 0b0100011),
         CsInstr::Sd => mold(rs2, rs1, 0b011, imm, 0b0100011),
+        CsInstr::Fsw => mold(rs2, rs1, 0b010, imm, 0b0100111),
+        CsInstr::Fsd => mold(rs2, rs1, 0b011, imm, 0b0100111),
     }
 }
 
@@ -126,7 +138,7 @@ pub(super) fn build_utype(instruction_type: CuInstr, rd: u16, imm: u32) -> u32 {
     }
 }
 
-pub(super) fn build_jtype(imm: u16) -> u32 {
+pub(super) fn build_jtype(rd: Register, imm: u16) -> u32 {
     let mold = |imm: u16, rd: u16, opcode: u32| -> u32 {
         let rd: u32 = rd.into();
 
@@ -137,7 +149,7 @@ pub(super) fn build_jtype(imm: u16) -> u32 {
         (imm << 12) | (rd << 7) | opcode
     };
 
-    mold(imm, Register::Zero as u16, 0b1101111)
+    mold(imm, rd as u16, 0b1101111)
 }
 
 pub(super) enum InstrFormat {
@@ -0,0 +1,384 @@
+//! # Disassemble instructions into GNU assembler syntax
+//!
+//! The output follows `objdump -d -M no-aliases`: mnemonic and operands are separated by a single
+//! space, operands by commas, registers are named by their ABI names and immediates of shifts,
+//! `lui` and `auipc` are printed in hexadecimal. Branch and jump targets are printed as absolute
+//! addresses if the address of the instruction is known and relative to it (`.+8`) otherwise.
+
+use crate::{types::*, Instruction, Symbol};
+use core::fmt;
+
+impl Instruction {
+    /// Disassemble the instruction located at address `pc`.
+    pub fn disassemble(&self, pc: u64) -> String {
+        Disassembly {
+            instruction: *self,
+            pc: Some(pc),
+        }
+        .to_string()
+    }
+
+    /// Disassemble the instruction located at address `pc` which accesses or jumps to the
+    /// absolute `address`, e.g. one found by [constant
+    /// propagation](crate::dataflow::constant_propagation). The address is appended as a comment
+    /// together with the symbol containing it: `ld a0,8(gp) # 0x11008 <data+0x8>`.
+    pub fn disassemble_resolved(&self, pc: u64, address: u64, symbol: Option<&Symbol>) -> String {
+        let mut disassembly = format!("{} # {:#x}", self.disassemble(pc), address);
+
+        match symbol {
+            Some(symbol) if symbol.address == address => {
+                disassembly += &format!(" <{}>", symbol.name)
+            }
+            Some(symbol) => {
+                let offset = address.wrapping_sub(symbol.address);
+                disassembly += &format!(" <{}+{:#x}>", symbol.name, offset)
+            }
+            None => {}
+        }
+
+        disassembly
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Disassembly {
+            instruction: *self,
+            pc: None,
+        }
+        .fmt(f)
+    }
+}
+
+struct Disassembly {
+    instruction: Instruction,
+    pc: Option<u64>,
+}
+
+impl Disassembly {
+    fn target(&self, offset: i32) -> Target {
+        match self.pc {
+            Some(pc) => Target::Absolute(pc.wrapping_add(offset as i64 as u64)),
+            None => Target::Relative(offset),
+        }
+    }
+}
+
+enum Target {
+    Absolute(u64),
+    Relative(i32),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Absolute(address) => write!(f, "{:#x}", address),
+            Target::Relative(offset) if *offset < 0 => write!(f, ".{}", offset),
+            Target::Relative(offset) => write!(f, ".+{}", offset),
+        }
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Instruction::*;
+
+        let m = self.instruction.opcode().mnemonic();
+
+        match self.instruction {
+            Lui(u) | Auipc(u) => write!(f, "{} {},{:#x}", m, u.rd(), u.imm()),
+            Jal(j) => write!(f, "{} {},{}", m, j.rd(), self.target(j.imm())),
+            Jalr(i) => write!(f, "{} {},{}({})", m, i.rd(), i.imm(), i.rs1()),
+            Beq(b) | Bne(b) | Blt(b) | Bge(b) | Bltu(b) | Bgeu(b) => {
+                write!(f, "{} {},{},{}", m, b.rs1(), b.rs2(), self.target(b.imm()))
+            }
+            Lb(i) | Lh(i) | Lw(i) | Ld(i) | Lbu(i) | Lhu(i) | Lwu(i) => {
+                write!(f, "{} {},{}({})", m, i.rd(), i.imm(), i.rs1())
+            }
+            Sb(s) | Sh(s) | Sw(s) | Sd(s) => {
+                write!(f, "{} {},{}({})", m, s.rs2(), s.imm(), s.rs1())
+            }
+            Fence(i) => write!(
+                f,
+                "{} {},{}",
+                m,
+                FenceSet((i.0 >> 24) & 0b1111),
+                FenceSet((i.0 >> 20) & 0b1111)
+            ),
+            Addi(i) | Slti(i) | Sltiu(i) | Xori(i) | Ori(i) | Andi(i) | Addiw(i) => {
+                write!(f, "{} {},{},{}", m, i.rd(), i.rs1(), i.imm())
+            }
+            Slli(i) | Srli(i) | Srai(i) => {
+                write!(f, "{} {},{},{:#x}", m, i.rd(), i.rs1(), i.imm() & 0x3f)
+            }
+            Slliw(i) | Srliw(i) | Sraiw(i) => {
+                write!(f, "{} {},{},{:#x}", m, i.rd(), i.rs1(), i.imm() & 0x1f)
+            }
+            Add(r) | Sub(r) | Sll(r) | Slt(r) | Sltu(r) | Xor(r) | Srl(r) | Sra(r) | Or(r)
+            | And(r) | Mul(r) | Mulh(r) | Mulhsu(r) | Mulhu(r) | Div(r) | Divu(r) | Rem(r)
+            | Remu(r) | Addw(r) | Subw(r) | Sllw(r) | Srlw(r) | Sraw(r) | Mulw(r) | Divw(r)
+            | Divuw(r) | Remw(r) | Remuw(r) => {
+                write!(f, "{} {},{},{}", m, r.rd(), r.rs1(), r.rs2())
+            }
+            Ecall(_) | Ebreak(_) | Sret(_) | Mret(_) | Wfi(_) | FenceI(_) => write!(f, "{}", m),
+            SfenceVma(r) => write!(f, "{} {},{}", m, r.rs1(), r.rs2()),
+            Csrrw(i) | Csrrs(i) | Csrrc(i) => {
+                write!(f, "{} {},{},{}", m, i.rd(), i.csr(), i.rs1())
+            }
+            Csrrwi(i) | Csrrsi(i) | Csrrci(i) => {
+                write!(f, "{} {},{},{}", m, i.rd(), i.csr(), i.zimm())
+            }
+            Lrw(r) | Lrd(r) => write!(f, "{}{} {},({})", m, Ordering(r), r.rd(), r.rs1()),
+            Scw(r) | Amoswapw(r) | Amoaddw(r) | Amoxorw(r) | Amoandw(r) | Amoorw(r)
+            | Amominw(r) | Amomaxw(r) | Amominuw(r) | Amomaxuw(r) | Scd(r) | Amoswapd(r)
+            | Amoaddd(r) | Amoxord(r) | Amoandd(r) | Amoord(r) | Amomind(r) | Amomaxd(r)
+            | Amominud(r) | Amomaxud(r) => write!(
+                f,
+                "{}{} {},{},({})",
+                m,
+                Ordering(r),
+                r.rd(),
+                r.rs2(),
+                r.rs1()
+            ),
+            Flw(i) | Fld(i) => write!(f, "{} {},{}({})", m, i.frd(), i.imm(), i.rs1()),
+            Fsw(s) | Fsd(s) => write!(f, "{} {},{}({})", m, s.frs2(), s.imm(), s.rs1()),
+            Fmadds(r) | Fmsubs(r) | Fnmsubs(r) | Fnmadds(r) | Fmaddd(r) | Fmsubd(r)
+            | Fnmsubd(r) | Fnmaddd(r) => write!(
+                f,
+                "{} {},{},{},{}{}",
+                m,
+                r.rd(),
+                r.rs1(),
+                r.rs2(),
+                r.rs3(),
+                Rounding(r.rm(), RoundingMode::Dyn)
+            ),
+            Fadds(r) | Fsubs(r) | Fmuls(r) | Fdivs(r) | Faddd(r) | Fsubd(r) | Fmuld(r)
+            | Fdivd(r) => write!(
+                f,
+                "{} {},{},{}{}",
+                m,
+                r.frd(),
+                r.frs1(),
+                r.frs2(),
+                Rounding(r.rm(), RoundingMode::Dyn)
+            ),
+            Fsgnjs(r) | Fsgnjns(r) | Fsgnjxs(r) | Fmins(r) | Fmaxs(r) | Fsgnjd(r) | Fsgnjnd(r)
+            | Fsgnjxd(r) | Fmind(r) | Fmaxd(r) => {
+                write!(f, "{} {},{},{}", m, r.frd(), r.frs1(), r.frs2())
+            }
+            Feqs(r) | Flts(r) | Fles(r) | Feqd(r) | Fltd(r) | Fled(r) => {
+                write!(f, "{} {},{},{}", m, r.rd(), r.frs1(), r.frs2())
+            }
+            Fsqrts(r) | Fsqrtd(r) | Fcvtsd(r) => {
+                let rounding = Rounding(r.rm(), RoundingMode::Dyn);
+                write!(f, "{} {},{}{}", m, r.frd(), r.frs1(), rounding)
+            }
+            Fcvtds(r) => {
+                let rounding = Rounding(r.rm(), RoundingMode::Rne);
+                write!(f, "{} {},{}{}", m, r.frd(), r.frs1(), rounding)
+            }
+            Fcvtws(r) | Fcvtwus(r) | Fcvtls(r) | Fcvtlus(r) | Fcvtwd(r) | Fcvtwud(r)
+            | Fcvtld(r) | Fcvtlud(r) => {
+                let rounding = Rounding(r.rm(), RoundingMode::Dyn);
+                write!(f, "{} {},{}{}", m, r.rd(), r.frs1(), rounding)
+            }
+            Fcvtsw(r) | Fcvtswu(r) | Fcvtsl(r) | Fcvtslu(r) | Fcvtdl(r) | Fcvtdlu(r) => {
+                let rounding = Rounding(r.rm(), RoundingMode::Dyn);
+                write!(f, "{} {},{}{}", m, r.frd(), r.rs1(), rounding)
+            }
+            Fcvtdw(r) | Fcvtdwu(r) => {
+                let rounding = Rounding(r.rm(), RoundingMode::Rne);
+                write!(f, "{} {},{}{}", m, r.frd(), r.rs1(), rounding)
+            }
+            Fmvxw(r) | Fclasss(r) | Fmvxd(r) | Fclassd(r) => {
+                write!(f, "{} {},{}", m, r.rd(), r.frs1())
+            }
+            Fmvwx(r) | Fmvdx(r) => write!(f, "{} {},{}", m, r.frd(), r.rs1()),
+            Adduw(r) | Sh1add(r) | Sh2add(r) | Sh3add(r) | Sh1adduw(r) | Sh2adduw(r)
+            | Sh3adduw(r) | Andn(r) | Orn(r) | Xnor(r) | Max(r) | Maxu(r) | Min(r) | Minu(r)
+            | Rol(r) | Rolw(r) | Ror(r) | Rorw(r) | Clmul(r) | Clmulh(r) | Clmulr(r) | Bclr(r)
+            | Bext(r) | Binv(r) | Bset(r) => {
+                write!(f, "{} {},{},{}", m, r.rd(), r.rs1(), r.rs2())
+            }
+            Clz(i) | Clzw(i) | Ctz(i) | Ctzw(i) | Cpop(i) | Cpopw(i) | Sextb(i) | Sexth(i)
+            | Orcb(i) | Rev8(i) => write!(f, "{} {},{}", m, i.rd(), i.rs1()),
+            Zexth(r) => write!(f, "{} {},{}", m, r.rd(), r.rs1()),
+            Slliuw(i) | Rori(i) | Bclri(i) | Bexti(i) | Binvi(i) | Bseti(i) => {
+                write!(f, "{} {},{},{:#x}", m, i.rd(), i.rs1(), i.imm() & 0x3f)
+            }
+            Roriw(i) => write!(f, "{} {},{},{:#x}", m, i.rd(), i.rs1(), i.imm() & 0x1f),
+        }
+    }
+}
+
+/// The predecessor or successor set of a fence.
+struct FenceSet(u32);
+
+impl fmt::Display for FenceSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return write!(f, "0");
+        }
+
+        "iorw"
+            .chars()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & (0b1000 >> bit) != 0)
+            .try_for_each(|(_, c)| write!(f, "{}", c))
+    }
+}
+
+/// The memory ordering suffix of an atomic instruction.
+struct Ordering(RType);
+
+impl fmt::Display for Ordering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match ((self.0).0 >> 25) & 0b11 {
+            0b10 => write!(f, ".aq"),
+            0b01 => write!(f, ".rl"),
+            0b11 => write!(f, ".aqrl"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The rounding mode operand of a floating-point instruction, which is omitted if it is the
+/// default of the instruction.
+struct Rounding(Option<RoundingMode>, RoundingMode);
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(rm) if rm == self.1 => Ok(()),
+            Some(rm) => write!(f, ",{}", rm),
+            // only instructions which were not created by the decoder can have these encodings
+            None => write!(f, ",reserved"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{decode, Symbol, SymbolKind};
+
+    // The expected output was produced with `riscv64-unknown-elf-objdump -d -M no-aliases`.
+
+    fn disassemble(i: u32, pc: u64) -> String {
+        decode(i).unwrap().disassemble(pc)
+    }
+
+    #[test]
+    fn disassemble_riscu() {
+        assert_eq!(disassemble(0x000322b7, 0x10000), "lui t0,0x32");
+        assert_eq!(disassemble(0x00810513, 0x10000), "addi a0,sp,8");
+        assert_eq!(disassemble(0xff810113, 0x10000), "addi sp,sp,-8");
+        assert_eq!(disassemble(0x01853683, 0x10000), "ld a3,24(a0)");
+        assert_eq!(disassemble(0x00113023, 0x10000), "sd ra,0(sp)");
+        assert_eq!(disassemble(0x00c58633, 0x10000), "add a2,a1,a2");
+        assert_eq!(disassemble(0x40b50533, 0x10000), "sub a0,a0,a1");
+        assert_eq!(disassemble(0x021080b3, 0x10000), "mul ra,ra,ra");
+        assert_eq!(disassemble(0x0220df33, 0x10000), "divu t5,ra,sp");
+        assert_eq!(disassemble(0x0220ff33, 0x10000), "remu t5,ra,sp");
+        assert_eq!(disassemble(0x0020bf33, 0x10000), "sltu t5,ra,sp");
+        assert_eq!(disassemble(0x00078463, 0x10070), "beq a5,zero,0x10078");
+        assert_eq!(disassemble(0xfe1ff06f, 0x80002a0c), "jal zero,0x800029ec");
+        assert_eq!(disassemble(0xd89ff0ef, 0x800024a8), "jal ra,0x80002230");
+        assert_eq!(disassemble(0x00008067, 0x10000), "jalr zero,0(ra)");
+        assert_eq!(disassemble(0x00000073, 0x10000), "ecall");
+    }
+
+    #[test]
+    fn disassemble_rv64ima() {
+        assert_eq!(disassemble(0x00379793, 0), "slli a5,a5,0x3");
+        assert_eq!(disassemble(0x43f45413, 0), "srai s0,s0,0x3f");
+        assert_eq!(disassemble(0xfff7879b, 0), "addiw a5,a5,-1");
+        assert_eq!(disassemble(0x0087073b, 0), "addw a4,a4,s0");
+        assert_eq!(disassemble(0x00001517, 0), "auipc a0,0x1");
+        assert_eq!(disassemble(0x0ff0000f, 0), "fence iorw,iorw");
+        assert_eq!(disassemble(0x0330000f, 0), "fence rw,rw");
+        assert_eq!(disassemble(0x00100073, 0), "ebreak");
+        assert_eq!(disassemble(0x100526af, 0), "lr.w a3,(a0)");
+        assert_eq!(disassemble(0x18c5272f, 0), "sc.w a4,a2,(a0)");
+        assert_eq!(disassemble(0x0cb6b6af, 0), "amoswap.d.aq a3,a1,(a3)");
+        assert_eq!(disassemble(0x00b5b02f, 0), "amoadd.d zero,a1,(a1)");
+    }
+
+    #[test]
+    fn disassemble_system() {
+        assert_eq!(disassemble(0x30002573, 0), "csrrs a0,mstatus,zero");
+        assert_eq!(disassemble(0x30551073, 0), "csrrw zero,mtvec,a0");
+        assert_eq!(disassemble(0xc00027f3, 0), "csrrs a5,cycle,zero");
+        assert_eq!(disassemble(0x30046073, 0), "csrrsi zero,mstatus,8");
+        assert_eq!(disassemble(0x7c051073, 0), "csrrw zero,0x7c0,a0");
+        assert_eq!(disassemble(0x0000100f, 0), "fence.i");
+        assert_eq!(disassemble(0x30200073, 0), "mret");
+        assert_eq!(disassemble(0x10200073, 0), "sret");
+        assert_eq!(disassemble(0x10500073, 0), "wfi");
+        assert_eq!(disassemble(0x12000073, 0), "sfence.vma zero,zero");
+        assert_eq!(disassemble(0x12b50073, 0), "sfence.vma a0,a1");
+    }
+
+    #[test]
+    fn disassemble_bit_manipulation() {
+        assert_eq!(disassemble(0x08b5053b, 0), "add.uw a0,a0,a1");
+        assert_eq!(disassemble(0x20b52533, 0), "sh1add a0,a0,a1");
+        assert_eq!(disassemble(0x0a05151b, 0), "slli.uw a0,a0,0x20");
+        assert_eq!(disassemble(0x40b57533, 0), "andn a0,a0,a1");
+        assert_eq!(disassemble(0x60059513, 0), "clz a0,a1");
+        assert_eq!(disassemble(0x0805c53b, 0), "zext.h a0,a1");
+        assert_eq!(disassemble(0x61f5551b, 0), "roriw a0,a0,0x1f");
+        assert_eq!(disassemble(0x28755513, 0), "orc.b a0,a0");
+        assert_eq!(disassemble(0x6b855513, 0), "rev8 a0,a0");
+        assert_eq!(disassemble(0x0ab51533, 0), "clmul a0,a0,a1");
+        assert_eq!(disassemble(0x2bf51513, 0), "bseti a0,a0,0x3f");
+    }
+
+    #[test]
+    fn disassemble_floating_point() {
+        assert_eq!(disassemble(0x00452507, 0), "flw fa0,4(a0)");
+        assert_eq!(disassemble(0x00813427, 0), "fsd fs0,8(sp)");
+        assert_eq!(disassemble(0x6ac5f543, 0), "fmadd.d fa0,fa1,fa2,fa3");
+        assert_eq!(disassemble(0x6ac5a543, 0), "fmadd.d fa0,fa1,fa2,fa3,rdn");
+        assert_eq!(disassemble(0x02b57553, 0), "fadd.d fa0,fa0,fa1");
+        assert_eq!(disassemble(0x22b58553, 0), "fsgnj.d fa0,fa1,fa1");
+        assert_eq!(disassemble(0xa2b52553, 0), "feq.d a0,fa0,fa1");
+        assert_eq!(disassemble(0xc2051553, 0), "fcvt.w.d a0,fa0,rtz");
+        assert_eq!(disassemble(0xd2078753, 0), "fcvt.d.w fa4,a5");
+        assert_eq!(disassemble(0x42050553, 0), "fcvt.d.s fa0,fa0");
+        assert_eq!(disassemble(0x40157553, 0), "fcvt.s.d fa0,fa0");
+        assert_eq!(disassemble(0xe2050553, 0), "fmv.x.d a0,fa0");
+        assert_eq!(disassemble(0xf2078753, 0), "fmv.d.x fa4,a5");
+    }
+
+    #[test]
+    fn resolved_addresses() {
+        let data = Symbol {
+            name: "data".to_string(),
+            address: 0x11000,
+            size: 16,
+            kind: SymbolKind::Object,
+        };
+        let ld = decode(0x0081b503).unwrap();
+
+        assert_eq!(
+            ld.disassemble_resolved(0x10000, 0x11008, Some(&data)),
+            "ld a0,8(gp) # 0x11008 <data+0x8>"
+        );
+        assert_eq!(
+            ld.disassemble_resolved(0x10000, 0x11000, Some(&data)),
+            "ld a0,8(gp) # 0x11000 <data>"
+        );
+        assert_eq!(
+            ld.disassemble_resolved(0x10000, 0x11008, None),
+            "ld a0,8(gp) # 0x11008"
+        );
+    }
+
+    #[test]
+    fn relative_targets_without_address() {
+        assert_eq!(decode(0x00078463).unwrap().to_string(), "beq a5,zero,.+8");
+        assert_eq!(decode(0xfe1ff06f).unwrap().to_string(), "jal zero,.-32");
+    }
+}
@@ -1,55 +1,199 @@
-//! # Load RISC-U ELF64 files
+//! # Load and write RISC-U ELF64 files
+
+mod check;
+mod writer;
+
+pub use check::{Finding, FindingKind, RiscuReport};
+pub(crate) use writer::{round_up, PAGE_SIZE};
 
 use crate::{
-    iterators::{InstructionIter, LocationIter},
-    DecodingError,
+    emulator::VIRTUAL_MEMORY_SIZE,
+    isa::Xlen,
+    iterators::{InstructionIter, LocationIter, TryInstructionIter},
+    symbol::{extract_symbols, symbol_at},
+    DecodingError, Symbol,
 };
 use byteorder::{ByteOrder, LittleEndian};
-use goblin::elf::{program_header::PT_LOAD, section_header::SHT_PROGBITS, Elf};
+use goblin::elf::{
+    program_header::{ProgramHeader, PT_LOAD},
+    section_header::SHT_PROGBITS,
+    Elf,
+};
 use log::debug;
-use std::{fs, mem::size_of, ops::Range, path::Path};
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    mem::size_of,
+    ops::Range,
+    path::Path,
+};
 use thiserror::Error;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ProgramSegment<T> {
     pub address: u64,
     pub content: Vec<T>,
 }
 
-#[derive(Clone, Debug)]
+/// Access permissions of a loadable segment as given by the ELF `p_flags`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SegmentPermissions {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+/// A loadable (`PT_LOAD`) segment exactly as described by the program header table.
+///
+/// `content` holds the `p_filesz` bytes stored in the file. The remaining
+/// `memory_size - content.len()` bytes of the segment are zero-initialized.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Segment {
+    pub address: u64,
+    pub content: Vec<u8>,
+    pub memory_size: u64,
+    pub alignment: u64,
+    pub permissions: SegmentPermissions,
+}
+
+impl Segment {
+    pub fn file_size(&self) -> u64 {
+        self.content.len() as u64
+    }
+
+    /// The virtual address range occupied by this segment once loaded.
+    pub fn memory_range(&self) -> Range<u64> {
+        self.address..(self.address + self.memory_size)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Program {
     pub code: ProgramSegment<u8>,
     pub data: ProgramSegment<u8>,
     pub instruction_range: Range<u64>,
+    pub entry_point: u64,
+    pub segments: Vec<Segment>,
+    pub symbols: Vec<Symbol>,
     pub is64: bool,
 }
 
 impl Program {
+    /// Create a program from a code and a data segment, laid out the way Selfie does. The whole
+    /// code segment is treated as instructions and execution starts at its first address.
+    pub fn new(code: ProgramSegment<u8>, data: ProgramSegment<u8>) -> Program {
+        let segment = |s: &ProgramSegment<u8>, write: bool, execute: bool| Segment {
+            address: s.address,
+            content: s.content.clone(),
+            memory_size: s.content.len() as u64,
+            alignment: PAGE_SIZE,
+            permissions: SegmentPermissions {
+                read: true,
+                write,
+                execute,
+            },
+        };
+
+        Program {
+            instruction_range: code.address..(code.address + code.content.len() as u64),
+            entry_point: code.address,
+            segments: vec![segment(&code, false, true), segment(&data, true, false)],
+            symbols: Vec::new(),
+            is64: true,
+            code,
+            data,
+        }
+    }
+
+    /// Load a program from an in-memory RISC-U ELF image.
+    pub fn from_bytes(raw: &[u8]) -> Result<Program, RiscuError> {
+        load_object_bytes(raw)
+    }
+
+    /// Write the code and data segment of this program as a Selfie compatible RISC-U ELF64
+    /// binary, which can be loaded again with [`load_object_file`].
+    pub fn write_elf<W>(&self, writer: &mut W) -> Result<(), RiscuError>
+    where
+        W: Write,
+    {
+        writer::write_elf(self, writer)
+    }
+
     pub fn decode(&self) -> Result<DecodedProgram, RiscuError> {
         copy_and_decode(self)
     }
 
+    /// The width of the integer registers the program is compiled for, given by the ELF class.
+    pub fn xlen(&self) -> Xlen {
+        if self.is64 {
+            Xlen::Rv64
+        } else {
+            Xlen::Rv32
+        }
+    }
+
+    /// Check whether this program conforms to RISC-U: only the 14 RISC-U instructions, double-word
+    /// aligned memory accesses, branch and jump targets within the code and double-word aligned
+    /// segments.
+    pub fn check_riscu(&self) -> RiscuReport {
+        check::check_riscu(self)
+    }
+
     pub fn instructions(&self) -> &[u8] {
         let instr_start = (self.instruction_range.start - self.code.address) as usize;
         let instr_end = (self.instruction_range.end - self.code.address) as usize;
         let instr = instr_start..instr_end;
         &self.code.content[instr]
     }
+
+    /// All named symbols of the program sorted by address. Empty if the binary
+    /// has no symbol table, which is the case for binaries emitted by Selfie.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Find the symbol which contains the given address.
+    pub fn symbol_at(&self, address: u64) -> Option<&Symbol> {
+        symbol_at(&self.symbols, address)
+    }
+
+    /// Look up the address of a symbol by its name.
+    pub fn address_of(&self, name: &str) -> Option<u64> {
+        self.symbols
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| s.address)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct DecodedProgram {
     pub code: ProgramSegment<u8>,
+    /// The data segment split into words of `xlen` bits, 32-bit words are zero-extended.
     pub data: ProgramSegment<u64>,
+    /// The address of the first instruction to execute.
+    pub entry_point: u64,
+    pub xlen: Xlen,
 }
 
 impl DecodedProgram {
+    /// The size of a word of the data segment in bytes.
+    pub fn word_size(&self) -> usize {
+        self.xlen.bytes()
+    }
+
     pub fn iter_locations(&self) -> LocationIter<'_> {
-        LocationIter::new(&self.code.content, self.code.address)
+        LocationIter::new(&self.code.content, self.code.address).with_xlen(self.xlen)
     }
 
     pub fn iter_instructions(&self) -> InstructionIter<'_> {
-        InstructionIter::new(&self.code.content)
+        InstructionIter::new(&self.code.content, self.code.address).with_xlen(self.xlen)
+    }
+
+    /// Iterate over all instructions together with their addresses, reporting instructions which
+    /// can not be decoded instead of panicking.
+    pub fn try_iter_instructions(&self) -> TryInstructionIter<'_> {
+        TryInstructionIter::new(&self.code.content, self.code.address).with_xlen(self.xlen)
     }
 }
 
@@ -58,6 +202,9 @@ pub enum RiscuError {
     #[error("Error while reading file: {0}")]
     CouldNotReadFile(std::io::Error),
 
+    #[error("Error while writing file: {0}")]
+    CouldNotWriteFile(std::io::Error),
+
     #[error("Error while parsing ELF: {0}")]
     InvalidElf(goblin::error::Error),
 
@@ -74,11 +221,37 @@ where
 {
     fs::read(object_file)
         .map_err(RiscuError::CouldNotReadFile)
-        .and_then(|buffer| {
-            Elf::parse(&buffer)
-                .map_err(RiscuError::InvalidElf)
-                .and_then(|elf| extract_program(&buffer, &elf))
-        })
+        .and_then(|buffer| load_object_bytes(&buffer))
+}
+
+/// Load a RISC-U ELF image which is already present in memory.
+pub fn load_object_bytes(raw: &[u8]) -> Result<Program, RiscuError> {
+    Elf::parse(raw)
+        .map_err(RiscuError::InvalidElf)
+        .and_then(|elf| extract_program(raw, &elf))
+}
+
+/// Load a RISC-U ELF image by reading the reader to its end.
+pub fn load_object_reader<R>(mut reader: R) -> Result<Program, RiscuError>
+where
+    R: Read,
+{
+    let mut buffer = Vec::new();
+
+    reader
+        .read_to_end(&mut buffer)
+        .map_err(RiscuError::CouldNotReadFile)
+        .and_then(|_| load_object_bytes(&buffer))
+}
+
+/// Write a program to a RISC-U ELF file (see [`Program::write_elf`]).
+pub fn write_object_file<P>(program: &Program, object_file: P) -> Result<(), RiscuError>
+where
+    P: AsRef<Path>,
+{
+    File::create(object_file)
+        .map_err(RiscuError::CouldNotWriteFile)
+        .and_then(|mut file| program.write_elf(&mut file))
 }
 
 fn extract_program(raw: &[u8], elf: &Elf) -> Result<Program, RiscuError> {
@@ -153,16 +326,28 @@ fn extract_program(raw: &[u8], elf: &Elf) -> Result<Program, RiscuError> {
         };
 
     let code_start = code_segment_header.p_vaddr;
-    let code_segment = &raw[code_segment_header.file_range()];
-    let code_padding = (code_segment_header.p_memsz - code_segment_header.p_filesz) as usize;
+    let code_segment = segment_content(raw, code_segment_header)?;
+    let code_padding = segment_padding(code_segment_header)?;
 
     let data_start = data_segment_header.p_vaddr;
-    let data_segment = &raw[data_segment_header.file_range()];
-    let data_padding = (data_segment_header.p_memsz - data_segment_header.p_filesz) as usize;
+    let data_segment = segment_content(raw, data_segment_header)?;
+    let data_padding = segment_padding(data_segment_header)?;
+
+    let code_end = code_start + code_segment.len() as u64;
 
     let instruction_range = match sh_iter.find(|sh| !sh.is_writable() && sh.is_executable()) {
-        Some(section) => (section.vm_range().start as u64)..(section.vm_range().end as u64),
-        None => code_start..(code_start + code_segment.len() as u64),
+        Some(section) => {
+            let end = section
+                .sh_addr
+                .checked_add(section.sh_size)
+                .filter(|end| section.sh_addr >= code_start && *end <= code_end)
+                .ok_or(RiscuError::InvalidRiscu(
+                    "code section exceeds the code segment",
+                ))?;
+
+            section.sh_addr..end
+        }
+        None => code_start..code_end,
     };
 
     debug!(
@@ -182,6 +367,30 @@ fn extract_program(raw: &[u8], elf: &Elf) -> Result<Program, RiscuError> {
         instruction_range.start,
         instruction_range.end - instruction_range.start
     );
+    debug!("Entry: {:#010x}", elf.entry);
+
+    let segments = elf
+        .program_headers
+        .iter()
+        .filter(|ph| ph.p_type == PT_LOAD)
+        .map(|ph| {
+            let content = segment_content(raw, ph)?;
+
+            segment_padding(ph)?;
+
+            Ok(Segment {
+                address: ph.p_vaddr,
+                content: content.to_vec(),
+                memory_size: ph.p_memsz,
+                alignment: ph.p_align,
+                permissions: SegmentPermissions {
+                    read: ph.is_read(),
+                    write: ph.is_write(),
+                    execute: ph.is_executable(),
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(Program {
         code: ProgramSegment {
@@ -193,11 +402,42 @@ fn extract_program(raw: &[u8], elf: &Elf) -> Result<Program, RiscuError> {
             content: [data_segment.to_vec(), vec![0; data_padding]].concat(),
         },
         instruction_range,
+        entry_point: elf.entry,
+        segments,
+        symbols: extract_symbols(elf),
         is64: elf.is_64,
     })
 }
 
+/// The bytes of a segment in the file.
+fn segment_content<'a>(raw: &'a [u8], header: &ProgramHeader) -> Result<&'a [u8], RiscuError> {
+    header
+        .p_offset
+        .checked_add(header.p_filesz)
+        .and_then(|end| raw.get(header.p_offset as usize..end as usize))
+        .ok_or(RiscuError::InvalidRiscu("segment exceeds file size"))
+}
+
+/// The number of zero bytes following the content of a segment in memory.
+fn segment_padding(header: &ProgramHeader) -> Result<usize, RiscuError> {
+    header
+        .p_memsz
+        .checked_sub(header.p_filesz)
+        .filter(|_| {
+            matches!(
+                header.p_vaddr.checked_add(header.p_memsz),
+                Some(end) if end <= VIRTUAL_MEMORY_SIZE
+            )
+        })
+        .map(|padding| padding as usize)
+        .ok_or(RiscuError::InvalidRiscu(
+            "segment size in memory is smaller than in the file or exceeds 4 GiB",
+        ))
+}
+
 fn copy_and_decode(program: &Program) -> Result<DecodedProgram, RiscuError> {
+    let xlen = program.xlen();
+
     let code = ProgramSegment {
         address: program.instruction_range.start,
         content: program.instructions().to_vec(),
@@ -208,10 +448,163 @@ fn copy_and_decode(program: &Program) -> Result<DecodedProgram, RiscuError> {
         content: program
             .data
             .content
-            .chunks_exact(size_of::<u64>())
-            .map(LittleEndian::read_u64)
+            .chunks(xlen.bytes())
+            .map(|chunk| {
+                // a trailing partial word is padded with zeros instead of being dropped
+                let mut word = [0; size_of::<u64>()];
+                word[..chunk.len()].copy_from_slice(chunk);
+                LittleEndian::read_u64(&word)
+            })
             .collect::<Vec<_>>(),
     };
 
-    Ok(DecodedProgram { code, data })
+    Ok(DecodedProgram {
+        code,
+        data,
+        entry_point: program.entry_point,
+        xlen,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Instruction, Register};
+    use goblin::elf::program_header::PF_R;
+
+    fn test_binary() -> Vec<u8> {
+        let code = [
+            Instruction::new_addi(Register::A7, Register::Zero, 93),
+            Instruction::new_ecall(),
+        ]
+        .iter()
+        .flat_map(|i| u32::from(*i).to_le_bytes())
+        .collect::<Vec<_>>();
+
+        let program = Program::new(
+            ProgramSegment {
+                address: 0x10000,
+                content: code,
+            },
+            ProgramSegment {
+                address: 0x11000,
+                content: 42_u64.to_le_bytes().to_vec(),
+            },
+        );
+
+        let mut binary = Vec::new();
+        program.write_elf(&mut binary).unwrap();
+
+        binary
+    }
+
+    /// The file offset of the field at `offset` in the program header with the given index.
+    fn program_header_field(binary: &[u8], index: usize, offset: usize) -> usize {
+        LittleEndian::read_u64(&binary[0x20..0x28]) as usize + index * 56 + offset
+    }
+
+    #[test]
+    fn segments_and_permissions() {
+        let mut binary = test_binary();
+        let memsz = program_header_field(&binary, 1, 40);
+        LittleEndian::write_u64(&mut binary[memsz..memsz + 8], 0x100);
+
+        let program = load_object_bytes(&binary).unwrap();
+
+        assert_eq!(program.entry_point, 0x10000);
+        assert_eq!(program.instruction_range, 0x10000..0x10008);
+
+        let segments = program
+            .segments
+            .iter()
+            .map(|s| (s.address, s.file_size(), s.memory_size, s.permissions))
+            .collect::<Vec<_>>();
+
+        let permissions = |write, execute| SegmentPermissions {
+            read: true,
+            write,
+            execute,
+        };
+
+        assert_eq!(
+            segments,
+            vec![
+                (0x10000, 8, 8, permissions(false, true)),
+                (0x11000, 8, 0x100, permissions(true, false)),
+            ]
+        );
+        assert!(program.segments.iter().all(|s| s.alignment == PAGE_SIZE));
+        assert_eq!(program.data.content.len(), 0x100);
+    }
+
+    #[test]
+    fn reject_truncated_file() {
+        let mut binary = test_binary();
+        binary.truncate(binary.len() - 4);
+
+        assert!(matches!(
+            load_object_bytes(&binary),
+            Err(RiscuError::InvalidRiscu(_))
+        ));
+    }
+
+    #[test]
+    fn reject_memory_size_smaller_than_file_size() {
+        let mut binary = test_binary();
+
+        for index in 0..2 {
+            let mut malformed = binary.clone();
+            let memsz = program_header_field(&malformed, index, 40);
+            LittleEndian::write_u64(&mut malformed[memsz..memsz + 8], 4);
+
+            assert!(matches!(
+                load_object_bytes(&malformed),
+                Err(RiscuError::InvalidRiscu(_))
+            ));
+        }
+
+        let memsz = program_header_field(&binary, 1, 40);
+        LittleEndian::write_u64(&mut binary[memsz..memsz + 8], u64::MAX);
+
+        assert!(matches!(
+            load_object_bytes(&binary),
+            Err(RiscuError::InvalidRiscu(_))
+        ));
+    }
+
+    #[test]
+    fn reject_other_segment_beyond_address_space() {
+        let mut binary = test_binary();
+
+        // a third loadable segment, copied from the data segment into the space before the code
+        let data = program_header_field(&binary, 1, 0);
+        let other = program_header_field(&binary, 2, 0);
+        binary.copy_within(data..data + 56, other);
+        LittleEndian::write_u16(&mut binary[0x38..0x3a], 3);
+
+        let flags = program_header_field(&binary, 2, 4);
+        LittleEndian::write_u32(&mut binary[flags..flags + 4], PF_R);
+
+        assert_eq!(load_object_bytes(&binary).unwrap().segments.len(), 3);
+
+        let vaddr = program_header_field(&binary, 2, 16);
+        LittleEndian::write_u64(&mut binary[vaddr..vaddr + 8], u64::MAX - 4);
+
+        assert!(matches!(
+            load_object_bytes(&binary),
+            Err(RiscuError::InvalidRiscu(_))
+        ));
+    }
+
+    #[test]
+    fn reject_segment_offset_overflow() {
+        let mut binary = test_binary();
+        let offset = program_header_field(&binary, 0, 8);
+        LittleEndian::write_u64(&mut binary[offset..offset + 8], u64::MAX);
+
+        assert!(matches!(
+            load_object_bytes(&binary),
+            Err(RiscuError::InvalidRiscu(_))
+        ));
+    }
 }
@@ -0,0 +1,246 @@
+use super::Program;
+use crate::{
+    iterators::{InstructionDecodingError, TryInstructionIter},
+    DecodingError, Instruction, IsaProfile,
+};
+use core::fmt;
+
+/// The findings of [`Program::check_riscu`], ordered by address.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RiscuReport {
+    pub findings: Vec<Finding>,
+}
+
+impl RiscuReport {
+    /// Returns true if the program is a valid RISC-U program.
+    pub fn is_conforming(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+impl fmt::Display for RiscuReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.findings
+            .iter()
+            .try_for_each(|finding| writeln!(f, "{}", finding))
+    }
+}
+
+/// A violation of RISC-U found at `address`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Finding {
+    pub address: u64,
+    pub kind: FindingKind,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}: {}: ", self.address, self.kind.name())?;
+
+        match self.kind {
+            FindingKind::NonRiscuInstruction(instruction) => {
+                write!(f, "`{}` is not a RISC-U instruction", instruction)
+            }
+            FindingKind::UndecodableInstruction(error) => write!(f, "{}", error),
+            FindingKind::MisalignedMemoryAccess(instruction) => write!(
+                f,
+                "offset of `{}` is not a multiple of 8",
+                instruction.disassemble(self.address)
+            ),
+            FindingKind::TargetOutOfRange(target) => {
+                write!(f, "target {:#x} is outside of the code", target)
+            }
+            FindingKind::MisalignedCodeSegment => {
+                write!(f, "code segment is not aligned to 8 bytes")
+            }
+            FindingKind::DataSegmentSize(size) => {
+                write!(f, "data segment size {} is not a multiple of 8", size)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FindingKind {
+    /// A valid RISC-V instruction which is not one of the 14 RISC-U instructions, including
+    /// compressed encodings of RISC-U instructions.
+    NonRiscuInstruction(Instruction),
+    /// The instruction can not be decoded at all.
+    UndecodableInstruction(DecodingError),
+    /// A `ld` or `sd` with an offset which is not double-word aligned.
+    MisalignedMemoryAccess(Instruction),
+    /// The target of a branch or jump lies outside of the instruction range.
+    TargetOutOfRange(u64),
+    /// The code segment does not start at a double-word boundary.
+    MisalignedCodeSegment,
+    /// The size of the data segment (in bytes) is not a multiple of a double word.
+    DataSegmentSize(u64),
+}
+
+impl FindingKind {
+    /// A stable identifier of the kind of finding.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FindingKind::NonRiscuInstruction(_) => "non-riscu-instruction",
+            FindingKind::UndecodableInstruction(_) => "undecodable-instruction",
+            FindingKind::MisalignedMemoryAccess(_) => "misaligned-memory-access",
+            FindingKind::TargetOutOfRange(_) => "target-out-of-range",
+            FindingKind::MisalignedCodeSegment => "misaligned-code-segment",
+            FindingKind::DataSegmentSize(_) => "data-segment-size",
+        }
+    }
+}
+
+pub(super) fn check_riscu(program: &Program) -> RiscuReport {
+    let mut findings = Vec::new();
+    let mut report = |address, kind| findings.push(Finding { address, kind });
+
+    if program.code.address & 0b111 != 0 {
+        report(program.code.address, FindingKind::MisalignedCodeSegment);
+    }
+
+    let instructions =
+        TryInstructionIter::new(program.instructions(), program.instruction_range.start);
+
+    for result in instructions {
+        let decoded = match result {
+            Ok(decoded) => decoded,
+            Err(InstructionDecodingError { address, error }) => {
+                report(address, FindingKind::UndecodableInstruction(error));
+                continue;
+            }
+        };
+
+        let address = decoded.address;
+        let instruction = decoded.instruction;
+
+        if decoded.is_compressed() || !IsaProfile::RISCU.accepts(&instruction) {
+            report(address, FindingKind::NonRiscuInstruction(instruction));
+        }
+
+        let offset = match instruction {
+            Instruction::Ld(i) => Some(i.imm()),
+            Instruction::Sd(s) => Some(s.imm()),
+            _ => None,
+        };
+
+        if matches!(offset, Some(offset) if offset % 8 != 0) {
+            report(address, FindingKind::MisalignedMemoryAccess(instruction));
+        }
+
+        if let Some(target) = instruction.control_flow(address).target() {
+            if !program.instruction_range.contains(&target) {
+                report(address, FindingKind::TargetOutOfRange(target));
+            }
+        }
+    }
+
+    let data_size = program.data.content.len() as u64;
+
+    if data_size & 0b111 != 0 {
+        report(
+            program.data.address,
+            FindingKind::DataSegmentSize(data_size),
+        );
+    }
+
+    findings.sort_by_key(|finding| finding.address);
+
+    RiscuReport { findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble::assemble, ProgramSegment};
+
+    #[test]
+    fn conforming_program() {
+        let program = assemble(
+            "
+            _start:
+                ld a0, 8(gp)
+                beq a0, zero, end
+                jal ra, _start
+            end:
+                addi a7, zero, 93
+                ecall
+            .data
+                .dword 1, 2
+            ",
+        )
+        .unwrap();
+
+        assert!(program.check_riscu().is_conforming());
+    }
+
+    #[test]
+    fn findings() {
+        let mut program = assemble(
+            "
+                lw a0, 0(sp)
+                sd a0, 4(sp)
+                jal zero, .+64
+                ecall
+            .data
+                .word 1
+            ",
+        )
+        .unwrap();
+
+        program.code.content.extend_from_slice(&[0x2e, 0x95]); // c.add a0,a1
+        program.instruction_range.end += 2;
+
+        let kinds = program
+            .check_riscu()
+            .findings
+            .iter()
+            .map(|finding| (finding.address - program.code.address, finding.kind.name()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (0, "non-riscu-instruction"),
+                (4, "misaligned-memory-access"),
+                (8, "target-out-of-range"),
+                (16, "non-riscu-instruction"),
+                (
+                    program.data.address - program.code.address,
+                    "data-segment-size"
+                ),
+            ]
+        );
+
+        // the partial double word is still part of the decoded data segment
+        assert_eq!(program.decode().unwrap().data.content, vec![1]);
+    }
+
+    #[test]
+    fn misaligned_code_segment() {
+        let program = Program::new(
+            ProgramSegment {
+                address: 0x10004,
+                content: 0x00000073_u32.to_le_bytes().to_vec(),
+            },
+            ProgramSegment {
+                address: 0x11000,
+                content: vec![0; 8],
+            },
+        );
+
+        let report = program.check_riscu();
+
+        assert_eq!(
+            report.findings,
+            vec![Finding {
+                address: 0x10004,
+                kind: FindingKind::MisalignedCodeSegment,
+            }]
+        );
+        assert_eq!(
+            report.to_string(),
+            "0x10004: misaligned-code-segment: code segment is not aligned to 8 bytes\n"
+        );
+    }
+}
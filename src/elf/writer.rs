@@ -0,0 +1,346 @@
+use super::{Program, ProgramSegment, RiscuError};
+use byteorder::{LittleEndian, WriteBytesExt};
+use goblin::elf::{
+    header::{
+        ELFCLASS32, ELFCLASS64, ELFDATA2LSB, ELFMAG, ELFOSABI_NONE, EM_RISCV, ET_EXEC, EV_CURRENT,
+        SIZEOF_IDENT,
+    },
+    program_header::{PF_R, PF_W, PF_X, PT_LOAD},
+};
+use std::io::Write;
+
+pub(crate) const PAGE_SIZE: u64 = 4096;
+
+const ELF_HEADER_SIZE: u16 = 64;
+const PROGRAM_HEADER_SIZE: u16 = 56;
+const ELF32_HEADER_SIZE: u16 = 52;
+const PROGRAM_HEADER32_SIZE: u16 = 32;
+const PROGRAM_HEADER_COUNT: u16 = 2;
+
+/// Emit the program as an ELF64 (or ELF32 for RV32 programs) binary with the layout Selfie uses:
+/// an ELF header followed by the program headers of the code (R+X) and data (R+W) segment, no
+/// section headers and every segment starting on its own page in the file.
+pub(super) fn write_elf<W>(program: &Program, writer: &mut W) -> Result<(), RiscuError>
+where
+    W: Write,
+{
+    let code = &program.code;
+    let data = &program.data;
+
+    let end = |segment: &ProgramSegment<u8>| {
+        segment
+            .address
+            .checked_add(segment.content.len() as u64)
+            .ok_or(RiscuError::InvalidRiscu(
+                "segment exceeds the 64-bit address space",
+            ))
+    };
+
+    let (code_end, data_end) = (end(code)?, end(data)?);
+
+    if data.address < code_end {
+        return Err(RiscuError::InvalidRiscu(
+            "data segment has to be located after the code segment",
+        ));
+    }
+
+    let is64 = program.is64;
+
+    if !is64 && data_end > u64::from(u32::MAX) {
+        return Err(RiscuError::InvalidRiscu(
+            "segments of ELF32 files have to be located below 4 GiB",
+        ));
+    }
+
+    let (header_size, program_header_size) = if is64 {
+        (ELF_HEADER_SIZE, PROGRAM_HEADER_SIZE)
+    } else {
+        (ELF32_HEADER_SIZE, PROGRAM_HEADER32_SIZE)
+    };
+
+    let headers_size = u64::from(header_size + PROGRAM_HEADER_COUNT * program_header_size);
+
+    let too_large = || RiscuError::InvalidRiscu("segments exceed the maximum file size");
+    let code_offset = file_offset(headers_size, code.address).ok_or_else(too_large)?;
+    let data_offset = code_offset
+        .checked_add(code.content.len() as u64)
+        .and_then(|end| file_offset(end, data.address))
+        .ok_or_else(too_large)?;
+
+    let mut buffer = Vec::with_capacity((data_offset + data.content.len() as u64) as usize);
+
+    write_elf_header(&mut buffer, program.entry_point, is64);
+    write_program_header(&mut buffer, code, code_offset, PF_R | PF_X, is64);
+    write_program_header(&mut buffer, data, data_offset, PF_R | PF_W, is64);
+
+    buffer.resize(code_offset as usize, 0);
+    buffer.extend_from_slice(&code.content);
+    buffer.resize(data_offset as usize, 0);
+    buffer.extend_from_slice(&data.content);
+
+    writer
+        .write_all(&buffer)
+        .map_err(RiscuError::CouldNotWriteFile)
+}
+
+/// The first page aligned file offset after `end`, which is congruent to `address` modulo the
+/// page size as required for loadable segments.
+fn file_offset(end: u64, address: u64) -> Option<u64> {
+    let offset = round_up(end, PAGE_SIZE)?.checked_add(address % PAGE_SIZE)?;
+
+    if offset - end >= PAGE_SIZE {
+        Some(offset - PAGE_SIZE)
+    } else {
+        Some(offset)
+    }
+}
+
+/// Round `n` up to the next multiple of `alignment`, which has to be a power of two, or return
+/// `None` if that does not fit into 64 bits.
+pub(crate) fn round_up(n: u64, alignment: u64) -> Option<u64> {
+    Some(n.checked_add(alignment - 1)? & !(alignment - 1))
+}
+
+// Writing into a Vec<u8> can not fail, hence all results are unwrapped.
+fn write_address(buffer: &mut Vec<u8>, value: u64, is64: bool) {
+    if is64 {
+        buffer.write_u64::<LittleEndian>(value).unwrap();
+    } else {
+        buffer.write_u32::<LittleEndian>(value as u32).unwrap();
+    }
+}
+
+fn write_elf_header(buffer: &mut Vec<u8>, entry_point: u64, is64: bool) {
+    let (header_size, program_header_size) = if is64 {
+        (ELF_HEADER_SIZE, PROGRAM_HEADER_SIZE)
+    } else {
+        (ELF32_HEADER_SIZE, PROGRAM_HEADER32_SIZE)
+    };
+
+    let mut ident = [0_u8; SIZEOF_IDENT];
+
+    ident[..4].copy_from_slice(ELFMAG);
+    ident[4] = if is64 { ELFCLASS64 } else { ELFCLASS32 };
+    ident[5] = ELFDATA2LSB;
+    ident[6] = EV_CURRENT;
+    ident[7] = ELFOSABI_NONE;
+
+    buffer.extend_from_slice(&ident);
+    buffer.write_u16::<LittleEndian>(ET_EXEC).unwrap(); // e_type
+    buffer.write_u16::<LittleEndian>(EM_RISCV).unwrap(); // e_machine
+    buffer.write_u32::<LittleEndian>(EV_CURRENT.into()).unwrap(); // e_version
+    write_address(buffer, entry_point, is64); // e_entry
+    write_address(buffer, header_size.into(), is64); // e_phoff
+    write_address(buffer, 0, is64); // e_shoff
+    buffer.write_u32::<LittleEndian>(0).unwrap(); // e_flags
+    buffer.write_u16::<LittleEndian>(header_size).unwrap(); // e_ehsize
+    buffer
+        .write_u16::<LittleEndian>(program_header_size)
+        .unwrap(); // e_phentsize
+    buffer
+        .write_u16::<LittleEndian>(PROGRAM_HEADER_COUNT)
+        .unwrap(); // e_phnum
+    buffer.write_u16::<LittleEndian>(0).unwrap(); // e_shentsize
+    buffer.write_u16::<LittleEndian>(0).unwrap(); // e_shnum
+    buffer.write_u16::<LittleEndian>(0).unwrap(); // e_shstrndx
+}
+
+fn write_program_header(
+    buffer: &mut Vec<u8>,
+    segment: &ProgramSegment<u8>,
+    offset: u64,
+    flags: u32,
+    is64: bool,
+) {
+    let size = segment.content.len() as u64;
+
+    buffer.write_u32::<LittleEndian>(PT_LOAD).unwrap(); // p_type
+
+    // the flags precede the offset in ELF64 and follow the sizes in ELF32
+    if is64 {
+        buffer.write_u32::<LittleEndian>(flags).unwrap(); // p_flags
+    }
+
+    write_address(buffer, offset, is64); // p_offset
+    write_address(buffer, segment.address, is64); // p_vaddr
+    write_address(buffer, segment.address, is64); // p_paddr
+    write_address(buffer, size, is64); // p_filesz
+    write_address(buffer, size, is64); // p_memsz
+
+    if !is64 {
+        buffer.write_u32::<LittleEndian>(flags).unwrap(); // p_flags
+    }
+
+    write_address(buffer, PAGE_SIZE, is64); // p_align
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{isa::Xlen, load_object_bytes, Instruction, Register};
+
+    fn test_program() -> Program {
+        let code = [
+            Instruction::new_lui(Register::Gp, 0x11),
+            Instruction::new_addi(Register::A7, Register::Zero, 93),
+            Instruction::new_ld(Register::A0, Register::Gp, -8),
+            Instruction::new_ecall(),
+        ]
+        .iter()
+        .flat_map(|i| u32::from(*i).to_le_bytes())
+        .collect::<Vec<_>>();
+
+        Program::new(
+            ProgramSegment {
+                address: 0x10000,
+                content: code,
+            },
+            ProgramSegment {
+                address: 0x11000,
+                content: 42_u64.to_le_bytes().to_vec(),
+            },
+        )
+    }
+
+    #[test]
+    fn file_offsets_are_congruent_to_addresses() {
+        assert_eq!(file_offset(176, 0x10000), Some(0x1000));
+        assert_eq!(file_offset(0x1010, 0x11000), Some(0x2000));
+        assert_eq!(file_offset(0x1010, 0x10010), Some(0x1010));
+        assert_eq!(file_offset(0x1010, 0x10018), Some(0x1018));
+        assert_eq!(file_offset(0x1010, 0x10008), Some(0x2008));
+        assert_eq!(file_offset(u64::MAX - 8, 0x10000), None);
+    }
+
+    #[test]
+    fn write_and_load_round_trip() {
+        let program = test_program();
+
+        let mut binary = Vec::new();
+        program.write_elf(&mut binary).unwrap();
+
+        let loaded = load_object_bytes(&binary).unwrap();
+
+        assert_eq!(loaded, program);
+
+        let mut rewritten = Vec::new();
+        loaded.write_elf(&mut rewritten).unwrap();
+
+        assert_eq!(rewritten, binary);
+    }
+
+    /// A minimal binary in the layout of Selfie, spelled out field by field.
+    fn selfie_binary() -> Vec<u8> {
+        let mut binary = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+        binary.write_u16::<LittleEndian>(2).unwrap(); // ET_EXEC
+        binary.write_u16::<LittleEndian>(0xf3).unwrap(); // EM_RISCV
+        binary.write_u32::<LittleEndian>(1).unwrap();
+        binary.write_u64::<LittleEndian>(0x10000).unwrap(); // e_entry
+        binary.write_u64::<LittleEndian>(64).unwrap(); // e_phoff
+        binary.write_u64::<LittleEndian>(0).unwrap(); // e_shoff
+        binary.write_u32::<LittleEndian>(0).unwrap();
+        for field in [64, 56, 2, 0, 0, 0].iter() {
+            binary.write_u16::<LittleEndian>(*field).unwrap();
+        }
+
+        // type, flags, offset, address, physical address, file size, memory size, alignment
+        for (flags, offset, address) in [(5, 0x1000, 0x10000), (6, 0x2000, 0x11000)].iter() {
+            binary.write_u32::<LittleEndian>(1).unwrap();
+            binary.write_u32::<LittleEndian>(*flags).unwrap();
+            for field in [*offset, *address, *address, 8, 8, 0x1000].iter() {
+                binary.write_u64::<LittleEndian>(*field).unwrap();
+            }
+        }
+
+        binary.resize(0x1000, 0);
+        binary.write_u32::<LittleEndian>(0x02a0_0513).unwrap(); // addi a0,zero,42
+        binary.write_u32::<LittleEndian>(0x05d0_0893).unwrap(); // addi a7,zero,93
+        binary.resize(0x2000, 0);
+        binary.write_u64::<LittleEndian>(42).unwrap();
+
+        binary
+    }
+
+    #[test]
+    fn load_and_write_selfie_binary() {
+        let binary = selfie_binary();
+
+        let program = load_object_bytes(&binary).unwrap();
+
+        assert_eq!(program.entry_point, 0x10000);
+        assert_eq!(
+            program.code.content,
+            [
+                Instruction::new_addi(Register::A0, Register::Zero, 42),
+                Instruction::new_addi(Register::A7, Register::Zero, 93),
+            ]
+            .iter()
+            .flat_map(|i| u32::from(*i).to_le_bytes())
+            .collect::<Vec<_>>()
+        );
+
+        let mut rewritten = Vec::new();
+        program.write_elf(&mut rewritten).unwrap();
+
+        assert_eq!(rewritten, binary);
+    }
+
+    #[test]
+    fn write_and_load_elf32() {
+        let mut program = test_program();
+        program.is64 = false;
+
+        let mut binary = Vec::new();
+        program.write_elf(&mut binary).unwrap();
+
+        assert_eq!(binary[4], ELFCLASS32);
+
+        let loaded = load_object_bytes(&binary).unwrap();
+
+        assert_eq!(loaded, program);
+        assert_eq!(loaded.xlen(), Xlen::Rv32);
+
+        let decoded = loaded.decode().unwrap();
+
+        assert_eq!(decoded.word_size(), 4);
+        assert_eq!(decoded.data.content, vec![42, 0]);
+    }
+
+    #[test]
+    fn reject_segments_beyond_address_space() {
+        let mut program = test_program();
+        program.code.address = u64::MAX - 8;
+
+        assert!(matches!(
+            program.write_elf(&mut Vec::new()),
+            Err(RiscuError::InvalidRiscu(_))
+        ));
+
+        let mut program = test_program();
+        program.data.address = u64::MAX - 4;
+
+        assert!(matches!(
+            program.write_elf(&mut Vec::new()),
+            Err(RiscuError::InvalidRiscu(_))
+        ));
+
+        program.is64 = false;
+
+        assert!(matches!(
+            program.write_elf(&mut Vec::new()),
+            Err(RiscuError::InvalidRiscu(_))
+        ));
+    }
+
+    #[test]
+    fn reject_overlapping_segments() {
+        let mut program = test_program();
+        program.data.address = program.code.address + 8;
+
+        assert!(matches!(
+            program.write_elf(&mut Vec::new()),
+            Err(RiscuError::InvalidRiscu(_))
+        ));
+    }
+}
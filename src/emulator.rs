@@ -0,0 +1,737 @@
+//! # Execute RISC-U programs
+//!
+//! A reference interpreter for the 14 RISC-U instructions as specified in the README, including
+//! their compressed forms. System calls follow the ABI of Selfie: the number of the system call
+//! is passed in `a7`, arguments in `a0`-`a3` and the result is returned in `a0`. Supported are
+//! `exit`, `read`, `write`, `openat` and `brk`.
+//!
+//! The machine is initialized the way Selfie does it: code and data are loaded at their
+//! addresses, the program break is set to the end of the data segment and the stack starts at
+//! the top of the 4GB address space holding `argc`, the `argv` pointers and the argument
+//! strings.
+
+mod memory;
+
+pub use memory::Memory;
+
+use crate::{
+    decode,
+    elf::{round_up, PAGE_SIZE},
+    instruction_length, DecodedProgram, DecodingError, Instruction, Register, SelfieSyscall,
+    WORD_SIZE,
+};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+};
+use thiserror::Error;
+
+/// The size of the virtual address space of a RISC-U machine.
+pub const VIRTUAL_MEMORY_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+
+// flags of the openat system call as used by Selfie (Linux values)
+const O_ACCMODE: u64 = 0b11;
+const O_WRONLY: u64 = 1;
+const O_RDWR: u64 = 2;
+const O_CREAT: u64 = 64;
+const O_TRUNC: u64 = 512;
+const O_APPEND: u64 = 1024;
+
+#[derive(Debug, Error)]
+pub enum EmulatorError {
+    #[error("Failed to decode instruction at {pc:#x}: {error}")]
+    DecodingError { pc: u64, error: DecodingError },
+
+    #[error("Instruction {instruction:?} at {pc:#x} is not part of RISC-U")]
+    UnsupportedInstruction { pc: u64, instruction: Instruction },
+
+    #[error("Unaligned memory access to {address:#x} at {pc:#x}")]
+    UnalignedAccess { pc: u64, address: u64 },
+
+    #[error("Memory access to {address:#x} at {pc:#x} is outside of the virtual address space")]
+    InvalidAddress { pc: u64, address: u64 },
+
+    #[error("Division by zero at {pc:#x}")]
+    DivisionByZero { pc: u64 },
+
+    #[error("Unknown system call {number} at {pc:#x}")]
+    UnknownSyscall { pc: u64, number: u64 },
+}
+
+/// The state of the machine after executing an instruction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StepResult {
+    Running,
+    Exited(i32),
+}
+
+enum FileDescriptor {
+    Input,
+    Output,
+    Error,
+    File(File),
+}
+
+pub struct Emulator<'a> {
+    registers: [u64; 32],
+    pc: u64,
+    memory: Memory,
+    program_break: u64,
+    instruction_count: u64,
+    input: Box<dyn Read + 'a>,
+    output: Box<dyn Write + 'a>,
+    files: HashMap<u64, FileDescriptor>,
+}
+
+impl<'a> Emulator<'a> {
+    /// Create a machine ready to execute the program starting at the first instruction of the
+    /// code segment without any arguments. Reading from file descriptor 0 and writing to file
+    /// descriptor 1 is connected to stdin and stdout of this process.
+    pub fn new(program: &DecodedProgram) -> Self {
+        Self::with_arguments::<&str>(program, &[])
+    }
+
+    /// Create a machine with the given arguments (including the name of the program as first
+    /// argument) passed on the stack.
+    pub fn with_arguments<S>(program: &DecodedProgram, arguments: &[S]) -> Self
+    where
+        S: AsRef<str>,
+    {
+        let mut memory = Memory::new();
+
+        memory.write_bytes(program.code.address, &program.code.content);
+
+        program
+            .data
+            .content
+            .iter()
+            .enumerate()
+            .for_each(|(n, word)| {
+                let address = program.data.address + (n * program.word_size()) as u64;
+
+                memory.write_bytes(address, &word.to_le_bytes()[..program.word_size()])
+            });
+
+        let program_break =
+            program.data.address + (program.data.content.len() * program.word_size()) as u64;
+
+        let files = vec![
+            (0, FileDescriptor::Input),
+            (1, FileDescriptor::Output),
+            (2, FileDescriptor::Error),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut emulator = Emulator {
+            registers: [0; 32],
+            pc: program.entry_point,
+            memory,
+            program_break,
+            instruction_count: 0,
+            input: Box::new(io::stdin()),
+            output: Box::new(io::stdout()),
+            files,
+        };
+
+        emulator.push_arguments(arguments);
+
+        emulator
+    }
+
+    /// Replace the source of reads from file descriptor 0.
+    pub fn with_input<R>(mut self, input: R) -> Self
+    where
+        R: Read + 'a,
+    {
+        self.input = Box::new(input);
+        self
+    }
+
+    /// Replace the destination of writes to file descriptor 1.
+    pub fn with_output<W>(mut self, output: W) -> Self
+    where
+        W: Write + 'a,
+    {
+        self.output = Box::new(output);
+        self
+    }
+
+    pub fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, pc: u64) {
+        self.pc = pc;
+    }
+
+    pub fn register(&self, register: Register) -> u64 {
+        self.registers[u32::from(register) as usize]
+    }
+
+    pub fn set_register(&mut self, register: Register, value: u64) {
+        if register != Register::Zero {
+            self.registers[u32::from(register) as usize] = value;
+        }
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+
+    pub fn program_break(&self) -> u64 {
+        self.program_break
+    }
+
+    /// The number of instructions executed so far.
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    /// Execute until the program exits and return its exit code.
+    pub fn run(&mut self) -> Result<i32, EmulatorError> {
+        loop {
+            if let StepResult::Exited(code) = self.step()? {
+                return Ok(code);
+            }
+        }
+    }
+
+    /// Execute a single instruction.
+    pub fn step(&mut self) -> Result<StepResult, EmulatorError> {
+        let pc = self.pc;
+
+        let (instruction, length) = self.fetch()?;
+        let next_pc = pc.wrapping_add(length);
+
+        self.instruction_count += 1;
+
+        match instruction {
+            Instruction::Lui(u) => {
+                let value = ((u.imm() << 12) as i32) as i64 as u64;
+                self.set_register(u.rd(), value);
+            }
+            Instruction::Addi(i) => {
+                let value = self.register(i.rs1()).wrapping_add(i.imm() as i64 as u64);
+                self.set_register(i.rd(), value);
+            }
+            Instruction::Ld(i) => {
+                let address = self.register(i.rs1()).wrapping_add(i.imm() as i64 as u64);
+                self.check_address(address)?;
+                let value = self.memory.read_u64(address);
+                self.set_register(i.rd(), value);
+            }
+            Instruction::Sd(s) => {
+                let address = self.register(s.rs1()).wrapping_add(s.imm() as i64 as u64);
+                self.check_address(address)?;
+                let value = self.register(s.rs2());
+                self.memory.write_u64(address, value);
+            }
+            Instruction::Add(r) => {
+                let value = self.register(r.rs1()).wrapping_add(self.register(r.rs2()));
+                self.set_register(r.rd(), value);
+            }
+            Instruction::Sub(r) => {
+                let value = self.register(r.rs1()).wrapping_sub(self.register(r.rs2()));
+                self.set_register(r.rd(), value);
+            }
+            Instruction::Mul(r) => {
+                let value = self.register(r.rs1()).wrapping_mul(self.register(r.rs2()));
+                self.set_register(r.rd(), value);
+            }
+            Instruction::Divu(r) => {
+                let value = self
+                    .register(r.rs1())
+                    .checked_div(self.register(r.rs2()))
+                    .ok_or(EmulatorError::DivisionByZero { pc })?;
+                self.set_register(r.rd(), value);
+            }
+            Instruction::Remu(r) => {
+                let value = self
+                    .register(r.rs1())
+                    .checked_rem(self.register(r.rs2()))
+                    .ok_or(EmulatorError::DivisionByZero { pc })?;
+                self.set_register(r.rd(), value);
+            }
+            Instruction::Sltu(r) => {
+                let value = self.register(r.rs1()) < self.register(r.rs2());
+                self.set_register(r.rd(), value.into());
+            }
+            Instruction::Beq(b) => {
+                if self.register(b.rs1()) == self.register(b.rs2()) {
+                    self.pc = pc.wrapping_add(b.imm() as i64 as u64);
+                    return Ok(StepResult::Running);
+                }
+            }
+            Instruction::Jal(j) => {
+                self.set_register(j.rd(), next_pc);
+                self.pc = pc.wrapping_add(j.imm() as i64 as u64);
+                return Ok(StepResult::Running);
+            }
+            Instruction::Jalr(i) => {
+                let target = self.register(i.rs1()).wrapping_add(i.imm() as i64 as u64) & !1;
+                self.set_register(i.rd(), next_pc);
+                self.pc = target;
+                return Ok(StepResult::Running);
+            }
+            Instruction::Ecall(_) => {
+                if let StepResult::Exited(code) = self.syscall()? {
+                    return Ok(StepResult::Exited(code));
+                }
+            }
+            instruction => {
+                return Err(EmulatorError::UnsupportedInstruction { pc, instruction });
+            }
+        }
+
+        self.pc = next_pc;
+
+        Ok(StepResult::Running)
+    }
+
+    fn fetch(&self) -> Result<(Instruction, u64), EmulatorError> {
+        let pc = self.pc;
+
+        if pc & 0b1 != 0 {
+            return Err(EmulatorError::UnalignedAccess { pc, address: pc });
+        }
+
+        let half_word = u16::from_le_bytes(self.memory.read(pc));
+
+        let (raw, length) = match instruction_length(half_word) {
+            2 => (u32::from(half_word), 2),
+            4 => (u32::from_le_bytes(self.memory.read(pc)), 4),
+            _ => {
+                return Err(EmulatorError::DecodingError {
+                    pc,
+                    error: DecodingError::Unimplemented,
+                })
+            }
+        };
+
+        decode(raw)
+            .map(|instruction| (instruction, length))
+            .map_err(|error| EmulatorError::DecodingError { pc, error })
+    }
+
+    /// RISC-U memory can only be accessed in double words.
+    fn check_address(&self, address: u64) -> Result<(), EmulatorError> {
+        if address & (WORD_SIZE as u64 - 1) != 0 {
+            Err(EmulatorError::UnalignedAccess {
+                pc: self.pc,
+                address,
+            })
+        } else if address >= VIRTUAL_MEMORY_SIZE {
+            Err(EmulatorError::InvalidAddress {
+                pc: self.pc,
+                address,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn push_arguments<S>(&mut self, arguments: &[S])
+    where
+        S: AsRef<str>,
+    {
+        let mut sp = VIRTUAL_MEMORY_SIZE;
+
+        // copy the argument strings (zero terminated) to the top of the stack
+        let pointers = arguments
+            .iter()
+            .map(|argument| {
+                let bytes = argument.as_ref().as_bytes();
+                let length = round_up(bytes.len() as u64 + 1, WORD_SIZE as u64)
+                    .expect("arguments are smaller than the address space");
+
+                sp -= length;
+
+                self.memory.write_bytes(sp, bytes);
+                self.memory.write_byte(sp + bytes.len() as u64, 0);
+
+                sp
+            })
+            .collect::<Vec<_>>();
+
+        let word = WORD_SIZE as u64;
+
+        // an empty environment
+        sp -= word;
+        self.memory.write_u64(sp, 0);
+
+        // argv terminated by a null pointer
+        sp -= word;
+        self.memory.write_u64(sp, 0);
+
+        for pointer in pointers.iter().rev() {
+            sp -= word;
+            self.memory.write_u64(sp, *pointer);
+        }
+
+        // argc
+        sp -= word;
+        self.memory.write_u64(sp, pointers.len() as u64);
+
+        self.set_register(Register::Sp, sp);
+    }
+
+    fn syscall(&mut self) -> Result<StepResult, EmulatorError> {
+        let number = self.register(Register::A7);
+
+        let a0 = self.register(Register::A0);
+        let a1 = self.register(Register::A1);
+        let a2 = self.register(Register::A2);
+
+        let syscall = SelfieSyscall::from_number(number).ok_or(EmulatorError::UnknownSyscall {
+            pc: self.pc,
+            number,
+        })?;
+
+        let result = match syscall {
+            SelfieSyscall::Exit => return Ok(StepResult::Exited(a0 as i32)),
+            SelfieSyscall::Read => self.read(a0, a1, a2),
+            SelfieSyscall::Write => self.write(a0, a1, a2),
+            SelfieSyscall::Openat => self.openat(a1, a2),
+            SelfieSyscall::Brk => self.brk(a0),
+        };
+
+        self.set_register(Register::A0, result);
+
+        Ok(StepResult::Running)
+    }
+
+    /// Read up to `size` bytes into `buffer` in chunks of a page, so that a huge `size` passed
+    /// by the program is never allocated at once.
+    fn read(&mut self, fd: u64, buffer: u64, size: u64) -> u64 {
+        if !in_address_space(buffer, size) {
+            return -1_i64 as u64;
+        }
+
+        let mut bytes = vec![0; size.min(PAGE_SIZE) as usize];
+        let mut total = 0;
+
+        loop {
+            let length = (size - total).min(PAGE_SIZE) as usize;
+
+            let result = match self.files.get_mut(&fd) {
+                Some(FileDescriptor::Input) => read_fully(&mut self.input, &mut bytes[..length]),
+                Some(FileDescriptor::File(file)) => read_fully(file, &mut bytes[..length]),
+                _ => return -1_i64 as u64,
+            };
+
+            match result {
+                Ok(read) => {
+                    self.memory.write_bytes(buffer + total, &bytes[..read]);
+                    total += read as u64;
+
+                    if read < length || total == size {
+                        break;
+                    }
+                }
+                Err(_) => return -1_i64 as u64,
+            }
+        }
+
+        total
+    }
+
+    fn write(&mut self, fd: u64, buffer: u64, size: u64) -> u64 {
+        if !in_address_space(buffer, size) {
+            return -1_i64 as u64;
+        }
+
+        let mut total = 0;
+
+        loop {
+            let length = (size - total).min(PAGE_SIZE);
+            let bytes = self.memory.read_bytes(buffer + total, length);
+
+            let result = match self.files.get_mut(&fd) {
+                Some(FileDescriptor::Output) => self.output.write_all(&bytes),
+                Some(FileDescriptor::Error) => io::stderr().write_all(&bytes),
+                Some(FileDescriptor::File(file)) => file.write_all(&bytes),
+                _ => return -1_i64 as u64,
+            };
+
+            if result.is_err() {
+                return -1_i64 as u64;
+            }
+
+            total += length;
+
+            if total == size {
+                break;
+            }
+        }
+
+        size
+    }
+
+    fn openat(&mut self, path: u64, flags: u64) -> u64 {
+        let path = (path..)
+            .map(|address| self.memory.read_byte(address))
+            .take_while(|b| *b != 0)
+            .collect::<Vec<_>>();
+
+        let path = String::from_utf8_lossy(&path).into_owned();
+
+        let file = OpenOptions::new()
+            .read(flags & O_ACCMODE != O_WRONLY)
+            .write(flags & O_ACCMODE == O_WRONLY || flags & O_ACCMODE == O_RDWR)
+            .create(flags & O_CREAT != 0)
+            .truncate(flags & O_TRUNC != 0)
+            .append(flags & O_APPEND != 0)
+            .open(path);
+
+        match file {
+            Ok(file) => {
+                let fd = (0..).find(|fd| !self.files.contains_key(fd)).unwrap();
+                self.files.insert(fd, FileDescriptor::File(file));
+                fd
+            }
+            Err(_) => -1_i64 as u64,
+        }
+    }
+
+    /// Set the program break to `address` if it lies between the current program break and the
+    /// stack pointer. The (new) program break is returned.
+    fn brk(&mut self, address: u64) -> u64 {
+        if address >= self.program_break && address < self.register(Register::Sp) {
+            self.program_break = address;
+        }
+
+        self.program_break
+    }
+}
+
+/// Returns true if the `size` bytes at `buffer` lie within the virtual address space.
+fn in_address_space(buffer: u64, size: u64) -> bool {
+    matches!(buffer.checked_add(size), Some(end) if end <= VIRTUAL_MEMORY_SIZE)
+}
+
+/// Read until the buffer is full or the end of the file is reached.
+fn read_fully<R: Read + ?Sized>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assemble::assemble;
+
+    fn run(source: &str, arguments: &[&str]) -> (Result<i32, EmulatorError>, Vec<u8>) {
+        let program = assemble(source).unwrap().decode().unwrap();
+        let mut output = Vec::new();
+
+        let result = Emulator::with_arguments(&program, arguments)
+            .with_output(&mut output)
+            .run();
+
+        (result, output)
+    }
+
+    #[test]
+    fn factorial() {
+        let (result, _) = run(
+            "
+                addi a0, zero, 10       # n
+                addi a1, zero, 1        # result
+                addi t0, zero, 1
+            loop:
+                beq a0, zero, done
+                mul a1, a1, a0
+                sub a0, a0, t0
+                jal zero, loop
+            done:
+                lui t1, 0x376           # 3628800 = 10!
+                addi t1, t1, -256
+                sub a0, a1, t1          # exit with 0 if the result is correct
+                sltu a0, zero, a0
+                addi a7, zero, 93
+                ecall
+            ",
+            &[],
+        );
+
+        assert_eq!(result.unwrap(), 0);
+    }
+
+    #[test]
+    fn hello_world() {
+        let (result, output) = run(
+            "
+                lui a1, 0x11            # address of the data segment
+                addi a0, zero, 1        # stdout
+                addi a2, zero, 6
+                addi a7, zero, 64
+                ecall
+                addi a0, a0, -6         # exit code 0 if all bytes were written
+                addi a7, zero, 93
+                ecall
+            .data
+                .ascii \"Hello\\n\"
+                .align 3
+            ",
+            &[],
+        );
+
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(output, b"Hello\n");
+    }
+
+    #[test]
+    fn start_at_entry_point() {
+        let mut program = assemble(
+            "
+                addi a0, zero, 1
+            _start:
+                addi a7, zero, 93
+                ecall
+            ",
+        )
+        .unwrap();
+
+        program.entry_point = program.address_of("_start").unwrap();
+
+        let program = program.decode().unwrap();
+
+        assert_eq!(Emulator::new(&program).run().unwrap(), 0);
+    }
+
+    #[test]
+    fn arguments_and_stack() {
+        let (result, output) = run(
+            "
+                ld a0, 0(sp)            # argc
+                ld a1, 16(sp)           # argv[1]
+                sd a0, -8(sp)
+                ld a2, -8(sp)
+                addi a0, zero, 1
+                addi a7, zero, 64
+                ecall                   # write argc bytes of argv[1]
+                jal ra, exit
+                addi a0, zero, 1
+            exit:
+                addi a0, zero, 42
+                addi a7, zero, 93
+                ecall
+            ",
+            &["test", "ab"],
+        );
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(output, b"ab");
+    }
+
+    #[test]
+    fn program_break() {
+        let program = assemble(
+            "
+                addi a0, zero, 0
+                addi a7, zero, 214
+                ecall                   # query the program break
+                addi a0, a0, 64
+                ecall                   # increase the program break
+                addi a0, a0, -128
+                ecall                   # invalid: below the program break
+            ",
+        )
+        .unwrap()
+        .decode()
+        .unwrap();
+
+        let mut emulator = Emulator::new(&program);
+        let initial = emulator.program_break();
+
+        (0..3).for_each(|_| assert_eq!(emulator.step().unwrap(), StepResult::Running));
+        assert_eq!(emulator.register(Register::A0), initial);
+
+        (0..2).for_each(|_| assert_eq!(emulator.step().unwrap(), StepResult::Running));
+        assert_eq!(emulator.register(Register::A0), initial + 64);
+
+        (0..2).for_each(|_| assert_eq!(emulator.step().unwrap(), StepResult::Running));
+        assert_eq!(emulator.register(Register::A0), initial + 64);
+        assert_eq!(emulator.program_break(), initial + 64);
+        assert_eq!(emulator.instruction_count(), 7);
+    }
+
+    #[test]
+    fn huge_read_and_write_sizes() {
+        let exit_code = |source: &str| {
+            let program = assemble(source).unwrap().decode().unwrap();
+            let mut output = Vec::new();
+
+            let result = Emulator::new(&program)
+                .with_input(&b"abc"[..])
+                .with_output(&mut output)
+                .run();
+
+            (result.unwrap(), output)
+        };
+
+        let syscall = |number: u64, size: &str| {
+            format!(
+                "
+                    addi a0, zero, {}
+                    lui a1, 0x20
+                    {}
+                    addi a7, zero, {}
+                    ecall
+                    addi a7, zero, 93
+                    ecall
+                ",
+                number - 63,
+                size,
+                number
+            )
+        };
+
+        // beyond the address space
+        assert_eq!(exit_code(&syscall(63, "addi a2, zero, -1")), (-1, vec![]));
+        assert_eq!(exit_code(&syscall(64, "addi a2, zero, -1")), (-1, vec![]));
+
+        // within the address space, transferred in chunks
+        assert_eq!(exit_code(&syscall(63, "lui a2, 0x40000")), (3, vec![]));
+        assert_eq!(
+            exit_code(&syscall(64, "lui a2, 0x100")),
+            (0x100000, vec![0; 0x100000])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            run("addi a0, zero, 1\ndivu a0, a0, zero", &[]).0,
+            Err(EmulatorError::DivisionByZero { pc: 0x10004 })
+        ));
+        assert!(matches!(
+            run("ld a0, 4(zero)", &[]).0,
+            Err(EmulatorError::UnalignedAccess { address: 4, .. })
+        ));
+        assert!(matches!(
+            run("xor a0, a0, a0", &[]).0,
+            Err(EmulatorError::UnsupportedInstruction { pc: 0x10000, .. })
+        ));
+        assert!(matches!(
+            run("addi a7, zero, 1\necall", &[]).0,
+            Err(EmulatorError::UnknownSyscall { number: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::elf::PAGE_SIZE;
+use std::collections::HashMap;
+
+/// A sparse byte-addressed memory. Pages are allocated on first write and
+/// reading memory which was never written yields zeros.
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    pages: HashMap<u64, Box<[u8; PAGE_SIZE as usize]>>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read_byte(&self, address: u64) -> u8 {
+        self.pages
+            .get(&(address / PAGE_SIZE))
+            .map_or(0, |page| page[(address % PAGE_SIZE) as usize])
+    }
+
+    pub fn write_byte(&mut self, address: u64, value: u8) {
+        let page = self
+            .pages
+            .entry(address / PAGE_SIZE)
+            .or_insert_with(|| Box::new([0; PAGE_SIZE as usize]));
+
+        page[(address % PAGE_SIZE) as usize] = value;
+    }
+
+    pub fn read_bytes(&self, address: u64, length: u64) -> Vec<u8> {
+        (0..length)
+            .map(|offset| self.read_byte(address.wrapping_add(offset)))
+            .collect()
+    }
+
+    pub fn write_bytes(&mut self, address: u64, bytes: &[u8]) {
+        bytes
+            .iter()
+            .enumerate()
+            .for_each(|(offset, b)| self.write_byte(address.wrapping_add(offset as u64), *b));
+    }
+
+    /// Read a little endian value of `N` bytes.
+    pub fn read<const N: usize>(&self, address: u64) -> [u8; N] {
+        let mut bytes = [0; N];
+
+        bytes
+            .iter_mut()
+            .enumerate()
+            .for_each(|(offset, b)| *b = self.read_byte(address.wrapping_add(offset as u64)));
+
+        bytes
+    }
+
+    pub fn read_u64(&self, address: u64) -> u64 {
+        u64::from_le_bytes(self.read(address))
+    }
+
+    pub fn write_u64(&mut self, address: u64, value: u64) {
+        self.write_bytes(address, &value.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparse_memory() {
+        let mut memory = Memory::new();
+
+        assert_eq!(memory.read_u64(0xffff_fff8), 0);
+
+        memory.write_u64(0x1ffc, 0x1122_3344_5566_7788);
+
+        assert_eq!(memory.read_u64(0x1ffc), 0x1122_3344_5566_7788);
+        assert_eq!(memory.read_byte(0x2000), 0x44);
+        assert_eq!(memory.read_bytes(0x1ffe, 3), vec![0x66, 0x55, 0x44]);
+        assert_eq!(memory.pages.len(), 2);
+    }
+}
@@ -0,0 +1,612 @@
+//! # Stack frames and the calling convention
+//!
+//! Every function recovered by the [`CallGraph`] is checked against the prologues and epilogues
+//! Selfie emits: the return address and the frame pointer `s0` are saved on the stack, `s0`
+//! points into the frame and the epilogue restores all of them before returning. Parameters are
+//! passed on the stack and popped by the callee, so `sp` may be higher at a return than at the
+//! entry of a function, but it has to be the same on every return path. Besides that the
+//! callee-saved registers `s0`-`s11` have to hold their original value at every return.
+//!
+//! Values are tracked relative to the state at the entry of a function, including the values
+//! saved in its stack frame. Callees are assumed to follow the calling convention, and stores
+//! through anything but `sp` or a copy of it are assumed not to modify the frame.
+//!
+//! ```
+//! use riscu::assemble::assemble;
+//!
+//! let program = assemble(
+//!     "
+//!         jal ra, main
+//!         ecall
+//!     main:
+//!         addi sp, sp, -16
+//!         sd ra, 8(sp)
+//!         sd s1, 0(sp)
+//!         addi s1, zero, 1
+//!         ld ra, 8(sp)
+//!         addi sp, sp, 16
+//!         jalr zero, 0(ra)
+//!     ",
+//! )
+//! .unwrap()
+//! .decode()
+//! .unwrap();
+//!
+//! let report = program.check_frames();
+//! let main = &report.frames[1];
+//!
+//! assert_eq!(main.size, 16);
+//! assert!(main.is_balanced());
+//! assert_eq!(
+//!     report.to_string(),
+//!     "0x10020: clobbered-callee-saved: `s1` is not restored before returning from 0x10008\n"
+//! );
+//! ```
+
+use crate::{
+    callgraph::{CallGraph, Function},
+    cfg::BasicBlock,
+    dataflow::{effects, solve_function, Analysis, Direction, Lattice, ARGUMENTS, CALLEE_SAVED},
+    isa::Xlen,
+    iterators::DecodedInstruction,
+    ControlFlow, DecodedProgram, Instruction, InstructionClass, Register,
+};
+use core::fmt;
+use std::collections::BTreeMap;
+
+/// The findings of [`DecodedProgram::check_frames`] with the stack frame of every function.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FrameReport {
+    /// The frames of all functions ordered by their entry.
+    pub frames: Vec<StackFrame>,
+    /// The violations of the calling convention ordered by function and address.
+    pub findings: Vec<FrameFinding>,
+}
+
+impl FrameReport {
+    /// Analyse the stack frames of all functions of `program`.
+    pub fn new(program: &DecodedProgram) -> FrameReport {
+        let call_graph = program.call_graph();
+        let analysis = Frames { xlen: program.xlen };
+
+        let mut report = FrameReport::default();
+
+        for function in call_graph.functions() {
+            let (frame, findings) = analysis.frame(&call_graph, function);
+
+            report.frames.push(frame);
+            report.findings.extend(findings);
+        }
+
+        report
+    }
+
+    /// Returns true if no function violates the calling convention.
+    pub fn is_conforming(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// The frame of the function starting at `entry`.
+    pub fn frame(&self, entry: u64) -> Option<&StackFrame> {
+        self.frames.iter().find(|frame| frame.entry == entry)
+    }
+}
+
+impl fmt::Display for FrameReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.findings
+            .iter()
+            .try_for_each(|finding| writeln!(f, "{}", finding))
+    }
+}
+
+/// The stack frame of a function. Offsets are relative to `sp` at the entry of the function.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StackFrame {
+    /// The address of the first instruction of the function.
+    pub entry: u64,
+    /// The number of bytes `sp` is lowered at most, including arguments pushed for calls.
+    pub size: u64,
+    /// The slots in which the return address and callee-saved registers are saved, ordered by
+    /// offset.
+    pub saved: Vec<SavedRegister>,
+    /// The argument registers read before they are overwritten.
+    pub argument_registers: Vec<Register>,
+    /// The offsets of all slots at or above `sp` at the entry, i.e. of parameters passed on the
+    /// stack, which are accessed.
+    pub stack_arguments: Vec<i64>,
+    /// The returns of the function with the offset of `sp`, if it is known.
+    pub returns: Vec<(u64, Option<i64>)>,
+}
+
+impl StackFrame {
+    /// Returns true if `sp` has the same known offset on every return path, which is not below
+    /// its value at the entry.
+    pub fn is_balanced(&self) -> bool {
+        match self.returns.first() {
+            Some((_, Some(offset))) if *offset >= 0 => self
+                .returns
+                .iter()
+                .all(|(_, other)| *other == Some(*offset)),
+            Some(_) => false,
+            None => true,
+        }
+    }
+}
+
+/// A register saved in the stack frame.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SavedRegister {
+    pub register: Register,
+    pub offset: i64,
+}
+
+/// A violation of the calling convention found at `address` in the function starting at
+/// `function`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FrameFinding {
+    pub function: u64,
+    pub address: u64,
+    pub kind: FrameFindingKind,
+}
+
+impl fmt::Display for FrameFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}: {}: ", self.address, self.kind.name())?;
+
+        match self.kind {
+            FrameFindingKind::UnbalancedStack(Some(offset)) if offset < 0 => write!(
+                f,
+                "`sp` is {} bytes below its value at the entry of {:#x}",
+                -offset, self.function
+            ),
+            FrameFindingKind::UnbalancedStack(Some(offset)) => write!(
+                f,
+                "`sp` is {} bytes above its value at the entry of {:#x}, unlike on other returns",
+                offset, self.function
+            ),
+            FrameFindingKind::UnbalancedStack(None) => write!(
+                f,
+                "`sp` is not known relative to its value at the entry of {:#x}",
+                self.function
+            ),
+            FrameFindingKind::ClobberedCalleeSaved(register) => write!(
+                f,
+                "`{}` is not restored before returning from {:#x}",
+                register, self.function
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FrameFindingKind {
+    /// At a return `sp` is below its value at the entry, not known or differs from the other
+    /// returns, given by its offset from the entry.
+    UnbalancedStack(Option<i64>),
+    /// A callee-saved register does not hold its value from the entry at a return.
+    ClobberedCalleeSaved(Register),
+}
+
+impl FrameFindingKind {
+    /// A stable identifier of the kind of finding.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FrameFindingKind::UnbalancedStack(_) => "unbalanced-stack",
+            FrameFindingKind::ClobberedCalleeSaved(_) => "clobbered-callee-saved",
+        }
+    }
+}
+
+impl DecodedProgram {
+    /// Analyse the stack frames of all functions, see [`FrameReport::new`].
+    pub fn check_frames(&self) -> FrameReport {
+        FrameReport::new(self)
+    }
+}
+
+/// The value of a register or stack slot in terms of the entry of the function.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Origin {
+    /// The value the register held at the entry.
+    Entry(Register),
+    /// The value of `sp` at the entry plus an offset.
+    StackPointer(i64),
+    Unknown,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct FrameState {
+    reached: bool,
+    registers: [Origin; 32],
+    /// The values stored in the frame by their offset, together with the width of the store.
+    slots: BTreeMap<i64, (u8, Origin)>,
+}
+
+impl FrameState {
+    fn get(&self, register: Register) -> Origin {
+        self.registers[register as usize]
+    }
+
+    fn set(&mut self, register: Register, origin: Origin) {
+        if register != Register::Zero {
+            self.registers[register as usize] = origin;
+        }
+    }
+
+    /// The offset of the address `offset(base)` in the frame.
+    fn slot(&self, base: Register, offset: i32) -> Option<i64> {
+        match self.get(base) {
+            Origin::StackPointer(base) => Some(base + offset as i64),
+            _ => None,
+        }
+    }
+
+    fn stack_pointer(&self) -> Option<i64> {
+        self.slot(Register::Sp, 0)
+    }
+}
+
+impl Lattice for FrameState {
+    fn bottom() -> Self {
+        FrameState {
+            reached: false,
+            registers: [Origin::Unknown; 32],
+            slots: BTreeMap::new(),
+        }
+    }
+
+    fn join(&mut self, other: &Self) -> bool {
+        if !other.reached {
+            return false;
+        }
+
+        if !self.reached {
+            *self = other.clone();
+            return true;
+        }
+
+        let before = self.clone();
+
+        for (origin, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            if origin != other {
+                *origin = Origin::Unknown;
+            }
+        }
+
+        self.slots
+            .retain(|offset, slot| other.slots.get(offset) == Some(slot));
+
+        *self != before
+    }
+}
+
+/// The forward analysis of the values in registers and the stack frame.
+#[derive(Clone, Copy)]
+struct Frames {
+    xlen: Xlen,
+}
+
+impl Analysis for Frames {
+    type Domain = FrameState;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn boundary(&self, _block: &BasicBlock) -> FrameState {
+        let mut state = FrameState {
+            reached: true,
+            ..FrameState::bottom()
+        };
+
+        (1..32_u32)
+            .map(Register::from)
+            .for_each(|register| state.set(register, Origin::Entry(register)));
+
+        state.set(Register::Sp, Origin::StackPointer(0));
+
+        state
+    }
+
+    fn transfer(&self, decoded: &DecodedInstruction, state: &mut FrameState) {
+        use Instruction::*;
+
+        if !state.reached {
+            return;
+        }
+
+        let instruction = decoded.instruction;
+        let access = instruction.memory_access();
+
+        let value = match instruction {
+            Addi(i) => match state.get(i.rs1()) {
+                Origin::StackPointer(offset) => Origin::StackPointer(offset + i.imm() as i64),
+                origin if i.imm() == 0 => origin,
+                _ => Origin::Unknown,
+            },
+            Add(r) if r.rs2() == Register::Zero => state.get(r.rs1()),
+            Add(r) if r.rs1() == Register::Zero => state.get(r.rs2()),
+            // only whole registers restored from the frame keep their origin
+            _ if instruction.kind() == InstructionClass::Load => access
+                .and_then(|access| {
+                    let slot = state.slot(access.base, access.offset)?;
+
+                    match state.slots.get(&slot) {
+                        Some((width, origin))
+                            if *width == access.width
+                                && access.width as usize == self.xlen.bytes() =>
+                        {
+                            Some(*origin)
+                        }
+                        _ => None,
+                    }
+                })
+                .unwrap_or(Origin::Unknown),
+            _ => Origin::Unknown,
+        };
+
+        if instruction.kind() == InstructionClass::Store {
+            if let Some(access) = access {
+                if let Some(slot) = state.slot(access.base, access.offset) {
+                    let stored = match instruction {
+                        Sb(s) | Sh(s) | Sw(s) | Sd(s) => state.get(s.rs2()),
+                        _ => Origin::Unknown,
+                    };
+                    let end = slot + access.width as i64;
+
+                    state.slots.retain(|offset, (width, _)| {
+                        *offset + *width as i64 <= slot || *offset >= end
+                    });
+                    state.slots.insert(slot, (access.width, stored));
+                }
+            }
+        }
+
+        if matches!(
+            instruction.control_flow(decoded.address),
+            ControlFlow::Call { .. } | ControlFlow::IndirectCall { .. }
+        ) {
+            // the callee may use the stack below `sp`
+            match state.stack_pointer() {
+                Some(sp) => state.slots.retain(|offset, _| *offset >= sp),
+                None => state.slots.clear(),
+            }
+        }
+
+        let effects = effects(&instruction);
+
+        effects
+            .writes
+            .union(&effects.clobbers)
+            .iter()
+            .for_each(|register| state.set(register, Origin::Unknown));
+
+        if let Some(rd) = instruction.rd() {
+            state.set(rd, value);
+        }
+    }
+}
+
+impl Frames {
+    fn frame(
+        &self,
+        call_graph: &CallGraph,
+        function: &Function,
+    ) -> (StackFrame, Vec<FrameFinding>) {
+        let cfg = call_graph.cfg();
+        let results = solve_function(cfg, function, *self);
+
+        let mut frame = StackFrame {
+            entry: function.entry,
+            size: 0,
+            saved: Vec::new(),
+            argument_registers: Vec::new(),
+            stack_arguments: Vec::new(),
+            returns: Vec::new(),
+        };
+        let mut findings = Vec::new();
+        let mut lowest = 0;
+
+        for id in function.blocks.iter() {
+            let mut state = results.entry(*id).clone();
+
+            for decoded in cfg.block(*id).instructions.iter() {
+                let instruction = decoded.instruction;
+
+                for register in instruction.sources() {
+                    if ARGUMENTS.contains(&register)
+                        && state.get(register) == Origin::Entry(register)
+                    {
+                        frame.argument_registers.push(register);
+                    }
+                }
+
+                if let Some(access) = instruction.memory_access() {
+                    if let Some(slot) = state.slot(access.base, access.offset) {
+                        if slot >= 0 {
+                            frame.stack_arguments.push(slot);
+                        }
+                    }
+
+                    if let Instruction::Sd(s) | Instruction::Sw(s) = instruction {
+                        match (state.get(s.rs2()), state.slot(access.base, access.offset)) {
+                            (Origin::Entry(register), Some(offset))
+                                if register == Register::Ra || CALLEE_SAVED.contains(&register) =>
+                            {
+                                frame.saved.push(SavedRegister { register, offset })
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                if instruction.control_flow(decoded.address) == ControlFlow::Return {
+                    let offset = state.stack_pointer();
+
+                    frame.returns.push((decoded.address, offset));
+
+                    for register in CALLEE_SAVED.iter() {
+                        if state.reached && state.get(*register) != Origin::Entry(*register) {
+                            findings.push(FrameFinding {
+                                function: function.entry,
+                                address: decoded.address,
+                                kind: FrameFindingKind::ClobberedCalleeSaved(*register),
+                            });
+                        }
+                    }
+                }
+
+                self.transfer(decoded, &mut state);
+
+                if let Some(sp) = state.stack_pointer() {
+                    lowest = lowest.min(sp);
+                }
+            }
+        }
+
+        let expected = frame.returns.first().and_then(|(_, offset)| *offset);
+
+        for (address, offset) in frame.returns.iter() {
+            let balanced = matches!((offset, expected), (Some(offset), Some(expected))
+                if *offset >= 0 && *offset == expected);
+
+            if !balanced {
+                findings.push(FrameFinding {
+                    function: function.entry,
+                    address: *address,
+                    kind: FrameFindingKind::UnbalancedStack(*offset),
+                });
+            }
+        }
+
+        findings.sort_by_key(|finding| finding.address);
+
+        frame.size = -lowest as u64;
+        frame.saved.sort_by_key(|saved| saved.offset);
+        frame.saved.dedup();
+        frame.argument_registers.sort();
+        frame.argument_registers.dedup();
+        frame.stack_arguments.sort_unstable();
+        frame.stack_arguments.dedup();
+
+        (frame, findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assemble::assemble, Register::*};
+
+    const PROGRAM: &str = "
+        _start:
+            jal ra, main
+            addi a7, zero, 93
+            ecall
+        main:
+            addi sp, sp, -8
+            sd ra, 0(sp)
+            addi sp, sp, -8
+            sd s0, 0(sp)
+            addi s0, sp, 0
+            addi sp, sp, -8
+            addi t0, zero, 1
+            addi sp, sp, -8
+            sd t0, 0(sp)
+            jal ra, leaf
+            addi sp, s0, 0
+            ld s0, 0(sp)
+            addi sp, sp, 8
+            ld ra, 0(sp)
+            addi sp, sp, 8
+            jalr zero, 0(ra)
+        leaf:
+            addi sp, sp, -8
+            sd ra, 0(sp)
+            ld a0, 8(sp)
+            add a1, a1, a0
+            ld ra, 0(sp)
+            addi sp, sp, 16
+            jalr zero, 0(ra)
+        broken:
+            addi sp, sp, -16
+            sd ra, 0(sp)
+            addi s1, zero, 3
+            beq a0, zero, out
+            addi sp, sp, 8
+        out:
+            ld ra, 0(sp)
+            addi sp, sp, 16
+            jalr zero, 0(ra)
+        ";
+
+    fn report() -> FrameReport {
+        assemble(PROGRAM).unwrap().decode().unwrap().check_frames()
+    }
+
+    #[test]
+    fn frames() {
+        let report = report();
+
+        let main = report.frame(0x1000c).unwrap();
+        assert_eq!(main.size, 32);
+        assert_eq!(
+            main.saved,
+            vec![
+                SavedRegister {
+                    register: Fp,
+                    offset: -16
+                },
+                SavedRegister {
+                    register: Ra,
+                    offset: -8
+                },
+            ]
+        );
+        assert!(main.argument_registers.is_empty());
+        assert_eq!(main.returns, vec![(0x10048, Some(0))]);
+        assert!(main.is_balanced());
+
+        let leaf = report.frame(0x1004c).unwrap();
+        assert_eq!(leaf.size, 8);
+        assert_eq!(leaf.argument_registers, vec![A1]);
+        assert_eq!(leaf.stack_arguments, vec![0]);
+        assert_eq!(leaf.returns, vec![(0x10064, Some(8))]);
+        assert!(leaf.is_balanced());
+
+        let broken = report.frame(0x10068).unwrap();
+        assert_eq!(broken.size, 16);
+        assert_eq!(broken.argument_registers, vec![A0]);
+        assert!(!broken.is_balanced());
+
+        assert!(report.frame(0x10000).unwrap().returns.is_empty());
+    }
+
+    #[test]
+    fn findings() {
+        let report = report();
+
+        assert!(!report.is_conforming());
+        assert_eq!(
+            report.findings,
+            vec![
+                FrameFinding {
+                    function: 0x10068,
+                    address: 0x10084,
+                    kind: FrameFindingKind::ClobberedCalleeSaved(S1),
+                },
+                FrameFinding {
+                    function: 0x10068,
+                    address: 0x10084,
+                    kind: FrameFindingKind::UnbalancedStack(None),
+                },
+            ]
+        );
+        assert_eq!(
+            report.to_string(),
+            "0x10084: clobbered-callee-saved: `s1` is not restored before returning from \
+             0x10068\n\
+             0x10084: unbalanced-stack: `sp` is not known relative to its value at the entry of \
+             0x10068\n"
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::types::*;
-use crate::Register;
+use crate::{Csr, FRegister, Register};
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Instruction {
@@ -93,6 +93,21 @@ pub enum Instruction {
     // System
     Ecall(IType),
     Ebreak(IType),
+    Sret(IType),
+    Mret(IType),
+    Wfi(IType),
+    SfenceVma(RType),
+
+    // Zifencei
+    FenceI(IType),
+
+    // Zicsr
+    Csrrw(IType),
+    Csrrs(IType),
+    Csrrc(IType),
+    Csrrwi(IType),
+    Csrrsi(IType),
+    Csrrci(IType),
 
     // Amo
     Lrw(RType),
@@ -117,6 +132,123 @@ pub enum Instruction {
     Amomaxd(RType),
     Amominud(RType),
     Amomaxud(RType),
+
+    // F extension
+    Flw(IType),
+    Fsw(SType),
+    Fmadds(R4Type),
+    Fmsubs(R4Type),
+    Fnmsubs(R4Type),
+    Fnmadds(R4Type),
+    Fadds(RType),
+    Fsubs(RType),
+    Fmuls(RType),
+    Fdivs(RType),
+    Fsqrts(RType),
+    Fsgnjs(RType),
+    Fsgnjns(RType),
+    Fsgnjxs(RType),
+    Fmins(RType),
+    Fmaxs(RType),
+    Fcvtws(RType),
+    Fcvtwus(RType),
+    Fcvtls(RType),
+    Fcvtlus(RType),
+    Fcvtsw(RType),
+    Fcvtswu(RType),
+    Fcvtsl(RType),
+    Fcvtslu(RType),
+    Feqs(RType),
+    Flts(RType),
+    Fles(RType),
+    Fclasss(RType),
+    Fmvxw(RType),
+    Fmvwx(RType),
+
+    // D extension
+    Fld(IType),
+    Fsd(SType),
+    Fmaddd(R4Type),
+    Fmsubd(R4Type),
+    Fnmsubd(R4Type),
+    Fnmaddd(R4Type),
+    Faddd(RType),
+    Fsubd(RType),
+    Fmuld(RType),
+    Fdivd(RType),
+    Fsqrtd(RType),
+    Fsgnjd(RType),
+    Fsgnjnd(RType),
+    Fsgnjxd(RType),
+    Fmind(RType),
+    Fmaxd(RType),
+    Fcvtwd(RType),
+    Fcvtwud(RType),
+    Fcvtld(RType),
+    Fcvtlud(RType),
+    Fcvtdw(RType),
+    Fcvtdwu(RType),
+    Fcvtdl(RType),
+    Fcvtdlu(RType),
+    Feqd(RType),
+    Fltd(RType),
+    Fled(RType),
+    Fclassd(RType),
+    Fmvxd(RType),
+    Fmvdx(RType),
+    Fcvtsd(RType),
+    Fcvtds(RType),
+
+    // Zba extension
+    Adduw(RType),
+    Sh1add(RType),
+    Sh2add(RType),
+    Sh3add(RType),
+    Sh1adduw(RType),
+    Sh2adduw(RType),
+    Sh3adduw(RType),
+    Slliuw(IType),
+
+    // Zbb extension
+    Andn(RType),
+    Orn(RType),
+    Xnor(RType),
+    Clz(IType),
+    Clzw(IType),
+    Ctz(IType),
+    Ctzw(IType),
+    Cpop(IType),
+    Cpopw(IType),
+    Max(RType),
+    Maxu(RType),
+    Min(RType),
+    Minu(RType),
+    Sextb(IType),
+    Sexth(IType),
+    Zexth(RType),
+    Rol(RType),
+    Rolw(RType),
+    Ror(RType),
+    Rorw(RType),
+    Rori(IType),
+    Roriw(IType),
+    Orcb(IType),
+    Rev8(IType),
+
+    // Zbc extension
+    Clmul(RType),
+    Clmulh(RType),
+    Clmulr(RType),
+
+    // Zbs extension
+    Bclr(RType),
+    Bclri(IType),
+    Bext(RType),
+    Bexti(IType),
+    Binv(RType),
+    Binvi(IType),
+    Bset(RType),
+    Bseti(IType),
 }
 
 // opcodes
@@ -134,6 +266,13 @@ const OP_JAL: u32 = 111; // 1101111, J format (JAL)
 const OP_SYSTEM: u32 = 115; // 1110011, I format (ECALL)
 const OP_AMO: u32 = 47; // 0101111, R format (AMO)
 const OP_FENCE: u32 = 15; // 0001111, I format (FENCE)
+const OP_LOAD_FP: u32 = 7; // 0000111, I format (FLW, FLD)
+const OP_STORE_FP: u32 = 39; // 0100111, S format (FSW, FSD)
+const OP_MADD: u32 = 67; // 1000011, R4 format (FMADD.S, FMADD.D)
+const OP_MSUB: u32 = 71; // 1000111, R4 format (FMSUB.S, FMSUB.D)
+const OP_NMSUB: u32 = 75; // 1001011, R4 format (FNMSUB.S, FNMSUB.D)
+const OP_NMADD: u32 = 79; // 1001111, R4 format (FNMADD.S, FNMADD.D)
+const OP_FP: u32 = 83; // 1010011, R format (FADD.S, FADD.D, FCVT.W.S, FMV.X.D, ...)
 
 // f3-codes
 const F3_ADDI: u32 = 0; // 000
@@ -188,6 +327,27 @@ const F3_SYSTEM: u32 = 0; // 000
 const F3_AMO32: u32 = 2; //010
 const F3_AMO64: u32 = 3; //011
 const F3_FENCE: u32 = 0; // 000
+const F3_FENCE_I: u32 = 1; // 001
+const F3_CSRRW: u32 = 1; // 001
+const F3_CSRRS: u32 = 2; // 010
+const F3_CSRRC: u32 = 3; // 011
+const F3_CSRRWI: u32 = 5; // 101
+const F3_CSRRSI: u32 = 6; // 110
+const F3_CSRRCI: u32 = 7; // 111
+const F3_FLW: u32 = 2; // 010
+const F3_FLD: u32 = 3; // 011
+const F3_FSW: u32 = 2; // 010
+const F3_FSD: u32 = 3; // 011
+const F3_FSGNJ: u32 = 0; // 000
+const F3_FSGNJN: u32 = 1; // 001
+const F3_FSGNJX: u32 = 2; // 010
+const F3_FMIN: u32 = 0; // 000
+const F3_FMAX: u32 = 1; // 001
+const F3_FEQ: u32 = 2; // 010
+const F3_FLT: u32 = 1; // 001
+const F3_FLE: u32 = 0; // 000
+const F3_FMV: u32 = 0; // 000
+const F3_FCLASS: u32 = 1; // 001
 
 // f7-codes
 const F7_ADD_ADDW: u32 = 0; // 0000000
@@ -208,132 +368,204 @@ const F7_DIV_DIVW: u32 = 1; // 0000001
 const F7_DIVU_DIVUW: u32 = 1; // 0000001
 const F7_REM_REMW: u32 = 1; // 0000001
 const F7_REMU_REMUW: u32 = 1; // 0000001
-const F7_LRW_LRD: u32 = 2; // 00010 aq rl
-const F7_SCW_SCD: u32 = 3; // 00011 aq rl
-const F7_AMOSWAPW_AMOSWAPD: u32 = 3; // 00001 aq rl
+const F7_LRW_LRD: u32 = 8; // 00010 aq rl
+const F7_SCW_SCD: u32 = 12; // 00011 aq rl
+const F7_AMOSWAPW_AMOSWAPD: u32 = 4; // 00001 aq rl
 const F7_AMOADDW_AMOADDD: u32 = 0; // 00000 aq rl
-const F7_AMOXORW_AMOXORD: u32 = 4; // 00100 aq rl
-const F7_AMOANDW_AMOANDD: u32 = 12; // 01100 aq rl
-const F7_AMOORW_AMOORD: u32 = 8; // 01000 aq rl
-const F7_AMOMINW_AMOMIND: u32 = 16; // 10000 aq rl
-const F7_AMOMAXW_AMOMAXD: u32 = 20; // 10100 aq rl
-const F7_AMOMINUW_AMOMINUD: u32 = 24; // 11000 aq rl
-const F7_AMOMAXUW_AMOMAXUD: u32 = 28; // 11100 aq rl
+const F7_AMOXORW_AMOXORD: u32 = 16; // 00100 aq rl
+const F7_AMOANDW_AMOANDD: u32 = 48; // 01100 aq rl
+const F7_AMOORW_AMOORD: u32 = 32; // 01000 aq rl
+const F7_AMOMINW_AMOMIND: u32 = 64; // 10000 aq rl
+const F7_AMOMAXW_AMOMAXD: u32 = 80; // 10100 aq rl
+const F7_AMOMINUW_AMOMINUD: u32 = 96; // 11000 aq rl
+const F7_AMOMAXUW_AMOMAXUD: u32 = 112; // 11100 aq rl
+
+// f5-codes of floating-point instructions, which are followed by the format in the funct7 field
+const F5_FADD: u32 = 0; // 00000
+const F5_FSUB: u32 = 1; // 00001
+const F5_FMUL: u32 = 2; // 00010
+const F5_FDIV: u32 = 3; // 00011
+const F5_FSQRT: u32 = 11; // 01011
+const F5_FSGNJ: u32 = 4; // 00100
+const F5_FMIN_FMAX: u32 = 5; // 00101
+const F5_FCVT_FP_FP: u32 = 8; // 01000
+const F5_FCMP: u32 = 20; // 10100
+const F5_FCVT_INT_FP: u32 = 24; // 11000
+const F5_FCVT_FP_INT: u32 = 26; // 11010
+const F5_FMV_X_FCLASS: u32 = 28; // 11100
+const F5_FMV_FP_X: u32 = 30; // 11110
+
+// floating-point formats
+const FMT_S: u32 = 0; // 00
+const FMT_D: u32 = 1; // 01
+
+// rs2-codes selecting the integer type of conversions
+const RS2_W: u32 = 0; // 00000
+const RS2_WU: u32 = 1; // 00001
+const RS2_L: u32 = 2; // 00010
+const RS2_LU: u32 = 3; // 00011
+
+// f12-codes of system instructions
+const F12_SRET: i32 = 258; // 000100000010
+const F12_MRET: i32 = 770; // 001100000010
+const F12_WFI: i32 = 261; // 000100000101
+const F7_SFENCE_VMA: u32 = 9; // 0001001
+
+// codes of the bit-manipulation extensions
+const F3_ADDUW: u32 = 0; // 000
+const F3_SH1ADD: u32 = 2; // 010
+const F3_SH2ADD: u32 = 4; // 100
+const F3_SH3ADD: u32 = 6; // 110
+const F3_SLLIUW: u32 = 1; // 001
+const F3_ANDN: u32 = 7; // 111
+const F3_ORN: u32 = 6; // 110
+const F3_XNOR: u32 = 4; // 100
+const F3_COUNT_SEXT: u32 = 1; // 001
+const F3_MAX: u32 = 6; // 110
+const F3_MAXU: u32 = 7; // 111
+const F3_MIN: u32 = 4; // 100
+const F3_MINU: u32 = 5; // 101
+const F3_ZEXTH: u32 = 4; // 100
+const F3_ROL: u32 = 1; // 001
+const F3_ROR: u32 = 5; // 101
+const F3_ORCB_REV8: u32 = 5; // 101
+const F3_CLMUL: u32 = 1; // 001
+const F3_CLMULR: u32 = 2; // 010
+const F3_CLMULH: u32 = 3; // 011
+const F3_BCLR: u32 = 1; // 001
+const F3_BEXT: u32 = 5; // 101
+const F3_BINV: u32 = 1; // 001
+const F3_BSET: u32 = 1; // 001
+const F7_ADDUW_ZEXTH: u32 = 4; // 0000100
+const F7_SHADD: u32 = 16; // 0010000
+const F7_ANDN_ORN_XNOR: u32 = 32; // 0100000
+const F7_MINMAX_CLMUL: u32 = 5; // 0000101
+const F7_ROTATE: u32 = 48; // 0110000
+const F7_BCLR_BEXT: u32 = 36; // 0100100
+const F7_BINV: u32 = 52; // 0110100
+const F7_BSET: u32 = 20; // 0010100
+const F12_CLZ: i32 = 1536; // 011000000000
+const F12_CTZ: i32 = 1537; // 011000000001
+const F12_CPOP: i32 = 1538; // 011000000010
+const F12_SEXTB: i32 = 1540; // 011000000100
+const F12_SEXTH: i32 = 1541; // 011000000101
+const F12_ORCB: i32 = 647; // 001010000111
+const F12_REV8: i32 = 1720; // 011010111000
 
 impl Instruction {
     pub fn new_nop() -> Instruction {
         Self::new_addi(Register::Zero, Register::Zero, 0)
     }
     pub fn new_add(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Add(RType::new(F7_ADD_ADDW, F3_ADD_ADDW, OP_OP, rs1, rs2, rd))
+        Instruction::Add(RType::new(F7_ADD_ADDW, F3_ADD_ADDW, OP_OP, rd, rs1, rs2))
     }
     pub fn new_sub(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Sub(RType::new(F7_SUB_SUBW, F3_SUB_SUBW, OP_OP, rs1, rs2, rd))
+        Instruction::Sub(RType::new(F7_SUB_SUBW, F3_SUB_SUBW, OP_OP, rd, rs1, rs2))
     }
     pub fn new_sll(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Sll(RType::new(F7_SLL_SLLW, F3_SLL_SLLW, OP_OP, rs1, rs2, rd))
+        Instruction::Sll(RType::new(F7_SLL_SLLW, F3_SLL_SLLW, OP_OP, rd, rs1, rs2))
     }
     pub fn new_slt(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Slt(RType::new(F7_SLT, F3_SLT, OP_OP, rs1, rs2, rd))
+        Instruction::Slt(RType::new(F7_SLT, F3_SLT, OP_OP, rd, rs1, rs2))
     }
     pub fn new_sltu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Sltu(RType::new(F7_SLTU, F3_SLTU, OP_OP, rs1, rs2, rd))
+        Instruction::Sltu(RType::new(F7_SLTU, F3_SLTU, OP_OP, rd, rs1, rs2))
     }
     pub fn new_xor(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Xor(RType::new(F7_XOR, F3_XOR, OP_OP, rs1, rs2, rd))
+        Instruction::Xor(RType::new(F7_XOR, F3_XOR, OP_OP, rd, rs1, rs2))
     }
     pub fn new_srl(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Srl(RType::new(F7_SRL_SRLW, F3_SRL_SRLW, OP_OP, rs1, rs2, rd))
+        Instruction::Srl(RType::new(F7_SRL_SRLW, F3_SRL_SRLW, OP_OP, rd, rs1, rs2))
     }
     pub fn new_sra(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Sra(RType::new(F7_SRA_SRAW, F3_SRA_SRAW, OP_OP, rs1, rs2, rd))
+        Instruction::Sra(RType::new(F7_SRA_SRAW, F3_SRA_SRAW, OP_OP, rd, rs1, rs2))
     }
     pub fn new_or(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Or(RType::new(F7_OR, F3_OR, OP_OP, rs1, rs2, rd))
+        Instruction::Or(RType::new(F7_OR, F3_OR, OP_OP, rd, rs1, rs2))
     }
     pub fn new_and(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::And(RType::new(F7_AND, F3_AND, OP_OP, rs1, rs2, rd))
+        Instruction::And(RType::new(F7_AND, F3_AND, OP_OP, rd, rs1, rs2))
     }
     pub fn new_mul(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Mul(RType::new(F7_MUL_MULW, F3_MUL_MULW, OP_OP, rs1, rs2, rd))
+        Instruction::Mul(RType::new(F7_MUL_MULW, F3_MUL_MULW, OP_OP, rd, rs1, rs2))
     }
     pub fn new_mulh(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Mulh(RType::new(F7_MULH, F3_MULH, OP_OP, rs1, rs2, rd))
+        Instruction::Mulh(RType::new(F7_MULH, F3_MULH, OP_OP, rd, rs1, rs2))
     }
     pub fn new_mulhsu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Mulhsu(RType::new(F7_MULHSU, F3_MULHSU, OP_OP, rs1, rs2, rd))
+        Instruction::Mulhsu(RType::new(F7_MULHSU, F3_MULHSU, OP_OP, rd, rs1, rs2))
     }
     pub fn new_mulhu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Mulhu(RType::new(F7_MULHU, F3_MULHU, OP_OP, rs1, rs2, rd))
+        Instruction::Mulhu(RType::new(F7_MULHU, F3_MULHU, OP_OP, rd, rs1, rs2))
     }
     pub fn new_div(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Div(RType::new(F7_DIV_DIVW, F3_DIV_DIVW, OP_OP, rs1, rs2, rd))
+        Instruction::Div(RType::new(F7_DIV_DIVW, F3_DIV_DIVW, OP_OP, rd, rs1, rs2))
     }
     pub fn new_divu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
         Instruction::Divu(RType::new(
             F7_DIVU_DIVUW,
             F3_DIVU_DIVUW,
             OP_OP,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_rem(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Rem(RType::new(F7_REM_REMW, F3_REM_REMW, OP_OP, rs1, rs2, rd))
+        Instruction::Rem(RType::new(F7_REM_REMW, F3_REM_REMW, OP_OP, rd, rs1, rs2))
     }
     pub fn new_remu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
         Instruction::Remu(RType::new(
             F7_REMU_REMUW,
             F3_REMU_REMUW,
             OP_OP,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_addw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Addw(RType::new(F7_ADD_ADDW, F3_ADD_ADDW, OP_OP32, rs1, rs2, rd))
+        Instruction::Addw(RType::new(F7_ADD_ADDW, F3_ADD_ADDW, OP_OP32, rd, rs1, rs2))
     }
     pub fn new_subw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Subw(RType::new(F7_SUB_SUBW, F3_SUB_SUBW, OP_OP32, rs1, rs2, rd))
+        Instruction::Subw(RType::new(F7_SUB_SUBW, F3_SUB_SUBW, OP_OP32, rd, rs1, rs2))
     }
     pub fn new_sllw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Sllw(RType::new(F7_SLL_SLLW, F3_SLL_SLLW, OP_OP32, rs1, rs2, rd))
+        Instruction::Sllw(RType::new(F7_SLL_SLLW, F3_SLL_SLLW, OP_OP32, rd, rs1, rs2))
     }
     pub fn new_srlw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Srlw(RType::new(F7_SRL_SRLW, F3_SRL_SRLW, OP_OP32, rs1, rs2, rd))
+        Instruction::Srlw(RType::new(F7_SRL_SRLW, F3_SRL_SRLW, OP_OP32, rd, rs1, rs2))
     }
     pub fn new_sraw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Sraw(RType::new(F7_SRA_SRAW, F3_SRA_SRAW, OP_OP32, rs1, rs2, rd))
+        Instruction::Sraw(RType::new(F7_SRA_SRAW, F3_SRA_SRAW, OP_OP32, rd, rs1, rs2))
     }
     pub fn new_mulw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Mulw(RType::new(F7_MUL_MULW, F3_MUL_MULW, OP_OP32, rs1, rs2, rd))
+        Instruction::Mulw(RType::new(F7_MUL_MULW, F3_MUL_MULW, OP_OP32, rd, rs1, rs2))
     }
     pub fn new_divw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Divw(RType::new(F7_DIV_DIVW, F3_DIV_DIVW, OP_OP32, rs1, rs2, rd))
+        Instruction::Divw(RType::new(F7_DIV_DIVW, F3_DIV_DIVW, OP_OP32, rd, rs1, rs2))
     }
     pub fn new_divuw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
         Instruction::Divuw(RType::new(
             F7_DIVU_DIVUW,
             F3_DIVU_DIVUW,
             OP_OP32,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_remw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Remw(RType::new(F7_REM_REMW, F3_REM_REMW, OP_OP32, rs1, rs2, rd))
+        Instruction::Remw(RType::new(F7_REM_REMW, F3_REM_REMW, OP_OP32, rd, rs1, rs2))
     }
     pub fn new_remuw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
         Instruction::Remuw(RType::new(
             F7_REMU_REMUW,
             F3_REMU_REMUW,
             OP_OP32,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_addi(rd: Register, rs1: Register, immediate: i32) -> Instruction {
@@ -359,7 +591,7 @@ impl Instruction {
     }
     pub fn new_srli(rd: Register, rs1: Register, immediate: i32) -> Instruction {
         Instruction::Srli(IType::new(
-            immediate | (F7_SRL_SRLW << 25) as i32,
+            immediate | (F7_SRL_SRLW << 5) as i32,
             F3_SRLI_SRAI,
             OP_IMM,
             rd,
@@ -368,7 +600,7 @@ impl Instruction {
     }
     pub fn new_srai(rd: Register, rs1: Register, immediate: i32) -> Instruction {
         Instruction::Srai(IType::new(
-            immediate | (F7_SRA_SRAW << 25) as i32,
+            immediate | (F7_SRA_SRAW << 5) as i32,
             F3_SRLI_SRAI,
             OP_IMM,
             rd,
@@ -382,10 +614,22 @@ impl Instruction {
         Instruction::Slliw(IType::new(immediate, F3_SLLIW, OP_IMM32, rd, rs1))
     }
     pub fn new_srliw(rd: Register, rs1: Register, immediate: i32) -> Instruction {
-        Instruction::Srliw(IType::new(immediate, F3_SRLIW, OP_IMM32, rd, rs1))
+        Instruction::Srliw(IType::new(
+            immediate | (F7_SRL_SRLW << 5) as i32,
+            F3_SRLIW,
+            OP_IMM32,
+            rd,
+            rs1,
+        ))
     }
     pub fn new_sraiw(rd: Register, rs1: Register, immediate: i32) -> Instruction {
-        Instruction::Sraiw(IType::new(immediate, F3_SRAIW, OP_IMM32, rd, rs1))
+        Instruction::Sraiw(IType::new(
+            immediate | (F7_SRA_SRAW << 5) as i32,
+            F3_SRAIW,
+            OP_IMM32,
+            rd,
+            rs1,
+        ))
     }
     pub fn new_lb(rd: Register, rs1: Register, immediate: i32) -> Instruction {
         Instruction::Lb(IType::new(immediate, F3_LB, OP_LD, rd, rs1))
@@ -419,13 +663,77 @@ impl Instruction {
     }
     pub fn new_ebreak() -> Instruction {
         Instruction::Ebreak(IType::new(
-            0,
+            1, // 000000000001
+            F3_SYSTEM,
+            OP_SYSTEM,
+            Register::Zero,
+            Register::Zero,
+        ))
+    }
+    pub fn new_sret() -> Instruction {
+        Instruction::Sret(IType::new(
+            F12_SRET,
+            F3_SYSTEM,
+            OP_SYSTEM,
+            Register::Zero,
+            Register::Zero,
+        ))
+    }
+    pub fn new_mret() -> Instruction {
+        Instruction::Mret(IType::new(
+            F12_MRET,
+            F3_SYSTEM,
+            OP_SYSTEM,
+            Register::Zero,
+            Register::Zero,
+        ))
+    }
+    pub fn new_wfi() -> Instruction {
+        Instruction::Wfi(IType::new(
+            F12_WFI,
+            F3_SYSTEM,
+            OP_SYSTEM,
+            Register::Zero,
+            Register::Zero,
+        ))
+    }
+    pub fn new_sfence_vma(rs1: Register, rs2: Register) -> Instruction {
+        Instruction::SfenceVma(RType::new(
+            F7_SFENCE_VMA,
             F3_SYSTEM,
             OP_SYSTEM,
             Register::Zero,
-            Register::from(1), // 000000000001
+            rs1,
+            rs2,
         ))
     }
+    pub fn new_fence_i() -> Instruction {
+        Instruction::FenceI(IType::new(
+            0,
+            F3_FENCE_I,
+            OP_FENCE,
+            Register::Zero,
+            Register::Zero,
+        ))
+    }
+    pub fn new_csrrw(rd: Register, csr: Csr, rs1: Register) -> Instruction {
+        Instruction::Csrrw(IType::new(csr_imm(csr), F3_CSRRW, OP_SYSTEM, rd, rs1))
+    }
+    pub fn new_csrrs(rd: Register, csr: Csr, rs1: Register) -> Instruction {
+        Instruction::Csrrs(IType::new(csr_imm(csr), F3_CSRRS, OP_SYSTEM, rd, rs1))
+    }
+    pub fn new_csrrc(rd: Register, csr: Csr, rs1: Register) -> Instruction {
+        Instruction::Csrrc(IType::new(csr_imm(csr), F3_CSRRC, OP_SYSTEM, rd, rs1))
+    }
+    pub fn new_csrrwi(rd: Register, csr: Csr, zimm: u32) -> Instruction {
+        Instruction::Csrrwi(IType::new(csr_imm(csr), F3_CSRRWI, OP_SYSTEM, rd, zimm))
+    }
+    pub fn new_csrrsi(rd: Register, csr: Csr, zimm: u32) -> Instruction {
+        Instruction::Csrrsi(IType::new(csr_imm(csr), F3_CSRRSI, OP_SYSTEM, rd, zimm))
+    }
+    pub fn new_csrrci(rd: Register, csr: Csr, zimm: u32) -> Instruction {
+        Instruction::Csrrci(IType::new(csr_imm(csr), F3_CSRRCI, OP_SYSTEM, rd, zimm))
+    }
     pub fn new_jalr(rd: Register, rs1: Register, immediate: i32) -> Instruction {
         Instruction::Jalr(IType::new(immediate, F3_JALR, OP_JALR, rd, rs1))
     }
@@ -466,22 +774,22 @@ impl Instruction {
         Instruction::Lui(UType::new(immediate, OP_LUI, rd))
     }
     pub fn new_auipc(rd: Register, immediate: i32) -> Instruction {
-        Instruction::Lui(UType::new(immediate, OP_AUIPC, rd))
+        Instruction::Auipc(UType::new(immediate, OP_AUIPC, rd))
     }
     pub fn new_lrw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Lrw(RType::new(F7_LRW_LRD, F3_AMO32, OP_AMO, rs1, rs2, rd))
+        Instruction::Lrw(RType::new(F7_LRW_LRD, F3_AMO32, OP_AMO, rd, rs1, rs2))
     }
     pub fn new_scw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Scw(RType::new(F7_SCW_SCD, F3_AMO32, OP_AMO, rs1, rs2, rd))
+        Instruction::Scw(RType::new(F7_SCW_SCD, F3_AMO32, OP_AMO, rd, rs1, rs2))
     }
     pub fn new_amoswapw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
         Instruction::Amoswapw(RType::new(
             F7_AMOSWAPW_AMOSWAPD,
             F3_AMO32,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amoaddw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -489,9 +797,9 @@ impl Instruction {
             F7_AMOADDW_AMOADDD,
             F3_AMO32,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amoxorw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -499,9 +807,9 @@ impl Instruction {
             F7_AMOXORW_AMOXORD,
             F3_AMO32,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amoandw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -509,22 +817,22 @@ impl Instruction {
             F7_AMOANDW_AMOANDD,
             F3_AMO32,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amoorw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Amoorw(RType::new(F7_AMOORW_AMOORD, F3_AMO32, OP_AMO, rs1, rs2, rd))
+        Instruction::Amoorw(RType::new(F7_AMOORW_AMOORD, F3_AMO32, OP_AMO, rd, rs1, rs2))
     }
     pub fn new_amominw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
         Instruction::Amominw(RType::new(
             F7_AMOMINW_AMOMIND,
             F3_AMO32,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amomaxw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -532,9 +840,9 @@ impl Instruction {
             F7_AMOMAXW_AMOMAXD,
             F3_AMO32,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amominuw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -542,9 +850,9 @@ impl Instruction {
             F7_AMOMINUW_AMOMINUD,
             F3_AMO32,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amomaxuw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -552,25 +860,25 @@ impl Instruction {
             F7_AMOMAXUW_AMOMAXUD,
             F3_AMO32,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_lrd(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Lrd(RType::new(F7_LRW_LRD, F3_AMO64, OP_AMO, rs1, rs2, rd))
+        Instruction::Lrd(RType::new(F7_LRW_LRD, F3_AMO64, OP_AMO, rd, rs1, rs2))
     }
     pub fn new_scd(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Scd(RType::new(F7_SCW_SCD, F3_AMO64, OP_AMO, rs1, rs2, rd))
+        Instruction::Scd(RType::new(F7_SCW_SCD, F3_AMO64, OP_AMO, rd, rs1, rs2))
     }
     pub fn new_amoswapd(rd: Register, rs1: Register, rs2: Register) -> Instruction {
         Instruction::Amoswapd(RType::new(
             F7_AMOSWAPW_AMOSWAPD,
             F3_AMO64,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amoaddd(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -578,9 +886,9 @@ impl Instruction {
             F7_AMOADDW_AMOADDD,
             F3_AMO64,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amoxord(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -588,9 +896,9 @@ impl Instruction {
             F7_AMOXORW_AMOXORD,
             F3_AMO64,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amoandd(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -598,22 +906,22 @@ impl Instruction {
             F7_AMOANDW_AMOANDD,
             F3_AMO64,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amoord(rd: Register, rs1: Register, rs2: Register) -> Instruction {
-        Instruction::Amoord(RType::new(F7_AMOORW_AMOORD, F3_AMO64, OP_AMO, rs1, rs2, rd))
+        Instruction::Amoord(RType::new(F7_AMOORW_AMOORD, F3_AMO64, OP_AMO, rd, rs1, rs2))
     }
     pub fn new_amomind(rd: Register, rs1: Register, rs2: Register) -> Instruction {
         Instruction::Amomind(RType::new(
             F7_AMOMINW_AMOMIND,
             F3_AMO64,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amomaxd(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -621,9 +929,9 @@ impl Instruction {
             F7_AMOMAXW_AMOMAXD,
             F3_AMO64,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amominud(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -631,9 +939,9 @@ impl Instruction {
             F7_AMOMINUW_AMOMINUD,
             F3_AMO64,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_amomaxud(rd: Register, rs1: Register, rs2: Register) -> Instruction {
@@ -641,9 +949,9 @@ impl Instruction {
             F7_AMOMAXUW_AMOMAXUD,
             F3_AMO64,
             OP_AMO,
+            rd,
             rs1,
             rs2,
-            rd,
         ))
     }
     pub fn new_fence(rd: Register, rs1: Register, immediate: i32) -> Instruction {
@@ -651,6 +959,808 @@ impl Instruction {
         // TODO: Implement me properly (imm[11:0] split into fm, pred, succ)!
         Instruction::Fence(IType::new(immediate, F3_FENCE, OP_FENCE, rd, rs1))
     }
+    pub fn new_flw(rd: FRegister, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Flw(IType::new(immediate, F3_FLW, OP_LOAD_FP, rd, rs1))
+    }
+    pub fn new_fsw(rs1: Register, rs2: FRegister, immediate: i32) -> Instruction {
+        Instruction::Fsw(SType::new(immediate, F3_FSW, OP_STORE_FP, rs1, rs2))
+    }
+    pub fn new_fmadds(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fmadds(R4Type::new(FMT_S, OP_MADD, rd, rs1, rs2, rs3, rm))
+    }
+    pub fn new_fmsubs(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fmsubs(R4Type::new(FMT_S, OP_MSUB, rd, rs1, rs2, rs3, rm))
+    }
+    pub fn new_fnmsubs(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fnmsubs(R4Type::new(FMT_S, OP_NMSUB, rd, rs1, rs2, rs3, rm))
+    }
+    pub fn new_fnmadds(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fnmadds(R4Type::new(FMT_S, OP_NMADD, rd, rs1, rs2, rs3, rm))
+    }
+    pub fn new_fadds(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fadds(RType::new(
+            (F5_FADD << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fsubs(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fsubs(RType::new(
+            (F5_FSUB << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fmuls(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fmuls(RType::new(
+            (F5_FMUL << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fdivs(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fdivs(RType::new(
+            (F5_FDIV << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fsqrts(rd: FRegister, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fsqrts(RType::new(
+            (F5_FSQRT << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            0_u32,
+        ))
+    }
+    pub fn new_fsgnjs(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fsgnjs(RType::new(
+            (F5_FSGNJ << 2) | FMT_S,
+            F3_FSGNJ,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fsgnjns(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fsgnjns(RType::new(
+            (F5_FSGNJ << 2) | FMT_S,
+            F3_FSGNJN,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fsgnjxs(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fsgnjxs(RType::new(
+            (F5_FSGNJ << 2) | FMT_S,
+            F3_FSGNJX,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fmins(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fmins(RType::new(
+            (F5_FMIN_FMAX << 2) | FMT_S,
+            F3_FMIN,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fmaxs(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fmaxs(RType::new(
+            (F5_FMIN_FMAX << 2) | FMT_S,
+            F3_FMAX,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fcvtws(rd: Register, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtws(RType::new(
+            (F5_FCVT_INT_FP << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_W,
+        ))
+    }
+    pub fn new_fcvtwus(rd: Register, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtwus(RType::new(
+            (F5_FCVT_INT_FP << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_WU,
+        ))
+    }
+    pub fn new_fcvtls(rd: Register, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtls(RType::new(
+            (F5_FCVT_INT_FP << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_L,
+        ))
+    }
+    pub fn new_fcvtlus(rd: Register, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtlus(RType::new(
+            (F5_FCVT_INT_FP << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_LU,
+        ))
+    }
+    pub fn new_fcvtsw(rd: FRegister, rs1: Register, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtsw(RType::new(
+            (F5_FCVT_FP_INT << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_W,
+        ))
+    }
+    pub fn new_fcvtswu(rd: FRegister, rs1: Register, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtswu(RType::new(
+            (F5_FCVT_FP_INT << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_WU,
+        ))
+    }
+    pub fn new_fcvtsl(rd: FRegister, rs1: Register, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtsl(RType::new(
+            (F5_FCVT_FP_INT << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_L,
+        ))
+    }
+    pub fn new_fcvtslu(rd: FRegister, rs1: Register, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtslu(RType::new(
+            (F5_FCVT_FP_INT << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_LU,
+        ))
+    }
+    pub fn new_feqs(rd: Register, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Feqs(RType::new(
+            (F5_FCMP << 2) | FMT_S,
+            F3_FEQ,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_flts(rd: Register, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Flts(RType::new(
+            (F5_FCMP << 2) | FMT_S,
+            F3_FLT,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fles(rd: Register, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fles(RType::new(
+            (F5_FCMP << 2) | FMT_S,
+            F3_FLE,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fclasss(rd: Register, rs1: FRegister) -> Instruction {
+        Instruction::Fclasss(RType::new(
+            (F5_FMV_X_FCLASS << 2) | FMT_S,
+            F3_FCLASS,
+            OP_FP,
+            rd,
+            rs1,
+            0_u32,
+        ))
+    }
+    pub fn new_fmvxw(rd: Register, rs1: FRegister) -> Instruction {
+        Instruction::Fmvxw(RType::new(
+            (F5_FMV_X_FCLASS << 2) | FMT_S,
+            F3_FMV,
+            OP_FP,
+            rd,
+            rs1,
+            0_u32,
+        ))
+    }
+    pub fn new_fmvwx(rd: FRegister, rs1: Register) -> Instruction {
+        Instruction::Fmvwx(RType::new(
+            (F5_FMV_FP_X << 2) | FMT_S,
+            F3_FMV,
+            OP_FP,
+            rd,
+            rs1,
+            0_u32,
+        ))
+    }
+    pub fn new_fld(rd: FRegister, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Fld(IType::new(immediate, F3_FLD, OP_LOAD_FP, rd, rs1))
+    }
+    pub fn new_fsd(rs1: Register, rs2: FRegister, immediate: i32) -> Instruction {
+        Instruction::Fsd(SType::new(immediate, F3_FSD, OP_STORE_FP, rs1, rs2))
+    }
+    pub fn new_fmaddd(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fmaddd(R4Type::new(FMT_D, OP_MADD, rd, rs1, rs2, rs3, rm))
+    }
+    pub fn new_fmsubd(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fmsubd(R4Type::new(FMT_D, OP_MSUB, rd, rs1, rs2, rs3, rm))
+    }
+    pub fn new_fnmsubd(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fnmsubd(R4Type::new(FMT_D, OP_NMSUB, rd, rs1, rs2, rs3, rm))
+    }
+    pub fn new_fnmaddd(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fnmaddd(R4Type::new(FMT_D, OP_NMADD, rd, rs1, rs2, rs3, rm))
+    }
+    pub fn new_faddd(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Faddd(RType::new(
+            (F5_FADD << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fsubd(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fsubd(RType::new(
+            (F5_FSUB << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fmuld(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fmuld(RType::new(
+            (F5_FMUL << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fdivd(
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rm: RoundingMode,
+    ) -> Instruction {
+        Instruction::Fdivd(RType::new(
+            (F5_FDIV << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fsqrtd(rd: FRegister, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fsqrtd(RType::new(
+            (F5_FSQRT << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            0_u32,
+        ))
+    }
+    pub fn new_fsgnjd(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fsgnjd(RType::new(
+            (F5_FSGNJ << 2) | FMT_D,
+            F3_FSGNJ,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fsgnjnd(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fsgnjnd(RType::new(
+            (F5_FSGNJ << 2) | FMT_D,
+            F3_FSGNJN,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fsgnjxd(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fsgnjxd(RType::new(
+            (F5_FSGNJ << 2) | FMT_D,
+            F3_FSGNJX,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fmind(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fmind(RType::new(
+            (F5_FMIN_FMAX << 2) | FMT_D,
+            F3_FMIN,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fmaxd(rd: FRegister, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fmaxd(RType::new(
+            (F5_FMIN_FMAX << 2) | FMT_D,
+            F3_FMAX,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fcvtwd(rd: Register, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtwd(RType::new(
+            (F5_FCVT_INT_FP << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_W,
+        ))
+    }
+    pub fn new_fcvtwud(rd: Register, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtwud(RType::new(
+            (F5_FCVT_INT_FP << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_WU,
+        ))
+    }
+    pub fn new_fcvtld(rd: Register, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtld(RType::new(
+            (F5_FCVT_INT_FP << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_L,
+        ))
+    }
+    pub fn new_fcvtlud(rd: Register, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtlud(RType::new(
+            (F5_FCVT_INT_FP << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_LU,
+        ))
+    }
+    pub fn new_fcvtdw(rd: FRegister, rs1: Register) -> Instruction {
+        Instruction::Fcvtdw(RType::new(
+            (F5_FCVT_FP_INT << 2) | FMT_D,
+            RoundingMode::Rne as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_W,
+        ))
+    }
+    pub fn new_fcvtdwu(rd: FRegister, rs1: Register) -> Instruction {
+        Instruction::Fcvtdwu(RType::new(
+            (F5_FCVT_FP_INT << 2) | FMT_D,
+            RoundingMode::Rne as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_WU,
+        ))
+    }
+    pub fn new_fcvtdl(rd: FRegister, rs1: Register, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtdl(RType::new(
+            (F5_FCVT_FP_INT << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_L,
+        ))
+    }
+    pub fn new_fcvtdlu(rd: FRegister, rs1: Register, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtdlu(RType::new(
+            (F5_FCVT_FP_INT << 2) | FMT_D,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            RS2_LU,
+        ))
+    }
+    pub fn new_feqd(rd: Register, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Feqd(RType::new(
+            (F5_FCMP << 2) | FMT_D,
+            F3_FEQ,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fltd(rd: Register, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fltd(RType::new(
+            (F5_FCMP << 2) | FMT_D,
+            F3_FLT,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fled(rd: Register, rs1: FRegister, rs2: FRegister) -> Instruction {
+        Instruction::Fled(RType::new(
+            (F5_FCMP << 2) | FMT_D,
+            F3_FLE,
+            OP_FP,
+            rd,
+            rs1,
+            rs2,
+        ))
+    }
+    pub fn new_fclassd(rd: Register, rs1: FRegister) -> Instruction {
+        Instruction::Fclassd(RType::new(
+            (F5_FMV_X_FCLASS << 2) | FMT_D,
+            F3_FCLASS,
+            OP_FP,
+            rd,
+            rs1,
+            0_u32,
+        ))
+    }
+    pub fn new_fmvxd(rd: Register, rs1: FRegister) -> Instruction {
+        Instruction::Fmvxd(RType::new(
+            (F5_FMV_X_FCLASS << 2) | FMT_D,
+            F3_FMV,
+            OP_FP,
+            rd,
+            rs1,
+            0_u32,
+        ))
+    }
+    pub fn new_fmvdx(rd: FRegister, rs1: Register) -> Instruction {
+        Instruction::Fmvdx(RType::new(
+            (F5_FMV_FP_X << 2) | FMT_D,
+            F3_FMV,
+            OP_FP,
+            rd,
+            rs1,
+            0_u32,
+        ))
+    }
+    pub fn new_fcvtsd(rd: FRegister, rs1: FRegister, rm: RoundingMode) -> Instruction {
+        Instruction::Fcvtsd(RType::new(
+            (F5_FCVT_FP_FP << 2) | FMT_S,
+            rm as u32,
+            OP_FP,
+            rd,
+            rs1,
+            FMT_D,
+        ))
+    }
+    pub fn new_fcvtds(rd: FRegister, rs1: FRegister) -> Instruction {
+        Instruction::Fcvtds(RType::new(
+            (F5_FCVT_FP_FP << 2) | FMT_D,
+            RoundingMode::Rne as u32,
+            OP_FP,
+            rd,
+            rs1,
+            FMT_S,
+        ))
+    }
+    pub fn new_adduw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Adduw(RType::new(F7_ADDUW_ZEXTH, F3_ADDUW, OP_OP32, rd, rs1, rs2))
+    }
+    pub fn new_sh1add(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Sh1add(RType::new(F7_SHADD, F3_SH1ADD, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_sh2add(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Sh2add(RType::new(F7_SHADD, F3_SH2ADD, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_sh3add(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Sh3add(RType::new(F7_SHADD, F3_SH3ADD, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_sh1adduw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Sh1adduw(RType::new(F7_SHADD, F3_SH1ADD, OP_OP32, rd, rs1, rs2))
+    }
+    pub fn new_sh2adduw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Sh2adduw(RType::new(F7_SHADD, F3_SH2ADD, OP_OP32, rd, rs1, rs2))
+    }
+    pub fn new_sh3adduw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Sh3adduw(RType::new(F7_SHADD, F3_SH3ADD, OP_OP32, rd, rs1, rs2))
+    }
+    pub fn new_slliuw(rd: Register, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Slliuw(IType::new(
+            immediate | (F7_ADDUW_ZEXTH << 5) as i32,
+            F3_SLLIUW,
+            OP_IMM32,
+            rd,
+            rs1,
+        ))
+    }
+    pub fn new_andn(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Andn(RType::new(F7_ANDN_ORN_XNOR, F3_ANDN, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_orn(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Orn(RType::new(F7_ANDN_ORN_XNOR, F3_ORN, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_xnor(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Xnor(RType::new(F7_ANDN_ORN_XNOR, F3_XNOR, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_clz(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Clz(IType::new(F12_CLZ, F3_COUNT_SEXT, OP_IMM, rd, rs1))
+    }
+    pub fn new_clzw(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Clzw(IType::new(F12_CLZ, F3_COUNT_SEXT, OP_IMM32, rd, rs1))
+    }
+    pub fn new_ctz(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Ctz(IType::new(F12_CTZ, F3_COUNT_SEXT, OP_IMM, rd, rs1))
+    }
+    pub fn new_ctzw(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Ctzw(IType::new(F12_CTZ, F3_COUNT_SEXT, OP_IMM32, rd, rs1))
+    }
+    pub fn new_cpop(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Cpop(IType::new(F12_CPOP, F3_COUNT_SEXT, OP_IMM, rd, rs1))
+    }
+    pub fn new_cpopw(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Cpopw(IType::new(F12_CPOP, F3_COUNT_SEXT, OP_IMM32, rd, rs1))
+    }
+    pub fn new_max(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Max(RType::new(F7_MINMAX_CLMUL, F3_MAX, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_maxu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Maxu(RType::new(F7_MINMAX_CLMUL, F3_MAXU, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_min(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Min(RType::new(F7_MINMAX_CLMUL, F3_MIN, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_minu(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Minu(RType::new(F7_MINMAX_CLMUL, F3_MINU, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_sextb(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Sextb(IType::new(F12_SEXTB, F3_COUNT_SEXT, OP_IMM, rd, rs1))
+    }
+    pub fn new_sexth(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Sexth(IType::new(F12_SEXTH, F3_COUNT_SEXT, OP_IMM, rd, rs1))
+    }
+    pub fn new_zexth(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Zexth(RType::new(
+            F7_ADDUW_ZEXTH,
+            F3_ZEXTH,
+            OP_OP32,
+            rd,
+            rs1,
+            Register::Zero,
+        ))
+    }
+    pub fn new_rol(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Rol(RType::new(F7_ROTATE, F3_ROL, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_rolw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Rolw(RType::new(F7_ROTATE, F3_ROL, OP_OP32, rd, rs1, rs2))
+    }
+    pub fn new_ror(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Ror(RType::new(F7_ROTATE, F3_ROR, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_rorw(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Rorw(RType::new(F7_ROTATE, F3_ROR, OP_OP32, rd, rs1, rs2))
+    }
+    pub fn new_rori(rd: Register, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Rori(IType::new(
+            immediate | (F7_ROTATE << 5) as i32,
+            F3_ROR,
+            OP_IMM,
+            rd,
+            rs1,
+        ))
+    }
+    pub fn new_roriw(rd: Register, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Roriw(IType::new(
+            immediate | (F7_ROTATE << 5) as i32,
+            F3_ROR,
+            OP_IMM32,
+            rd,
+            rs1,
+        ))
+    }
+    pub fn new_orcb(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Orcb(IType::new(F12_ORCB, F3_ORCB_REV8, OP_IMM, rd, rs1))
+    }
+    pub fn new_rev8(rd: Register, rs1: Register) -> Instruction {
+        Instruction::Rev8(IType::new(F12_REV8, F3_ORCB_REV8, OP_IMM, rd, rs1))
+    }
+    pub fn new_clmul(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Clmul(RType::new(F7_MINMAX_CLMUL, F3_CLMUL, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_clmulh(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Clmulh(RType::new(F7_MINMAX_CLMUL, F3_CLMULH, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_clmulr(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Clmulr(RType::new(F7_MINMAX_CLMUL, F3_CLMULR, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_bclr(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Bclr(RType::new(F7_BCLR_BEXT, F3_BCLR, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_bclri(rd: Register, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Bclri(IType::new(
+            immediate | (F7_BCLR_BEXT << 5) as i32,
+            F3_BCLR,
+            OP_IMM,
+            rd,
+            rs1,
+        ))
+    }
+    pub fn new_bext(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Bext(RType::new(F7_BCLR_BEXT, F3_BEXT, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_bexti(rd: Register, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Bexti(IType::new(
+            immediate | (F7_BCLR_BEXT << 5) as i32,
+            F3_BEXT,
+            OP_IMM,
+            rd,
+            rs1,
+        ))
+    }
+    pub fn new_binv(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Binv(RType::new(F7_BINV, F3_BINV, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_binvi(rd: Register, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Binvi(IType::new(
+            immediate | (F7_BINV << 5) as i32,
+            F3_BINV,
+            OP_IMM,
+            rd,
+            rs1,
+        ))
+    }
+    pub fn new_bset(rd: Register, rs1: Register, rs2: Register) -> Instruction {
+        Instruction::Bset(RType::new(F7_BSET, F3_BSET, OP_OP, rd, rs1, rs2))
+    }
+    pub fn new_bseti(rd: Register, rs1: Register, immediate: i32) -> Instruction {
+        Instruction::Bseti(IType::new(
+            immediate | (F7_BSET << 5) as i32,
+            F3_BSET,
+            OP_IMM,
+            rd,
+            rs1,
+        ))
+    }
 }
 
 impl From<Instruction> for u32 {
@@ -721,6 +1831,17 @@ impl From<Instruction> for u32 {
             Instruction::Remuw(RType(x)) => x,
             Instruction::Ecall(IType(x)) => x,
             Instruction::Ebreak(IType(x)) => x,
+            Instruction::Sret(IType(x)) => x,
+            Instruction::Mret(IType(x)) => x,
+            Instruction::Wfi(IType(x)) => x,
+            Instruction::SfenceVma(RType(x)) => x,
+            Instruction::FenceI(IType(x)) => x,
+            Instruction::Csrrw(IType(x)) => x,
+            Instruction::Csrrs(IType(x)) => x,
+            Instruction::Csrrc(IType(x)) => x,
+            Instruction::Csrrwi(IType(x)) => x,
+            Instruction::Csrrsi(IType(x)) => x,
+            Instruction::Csrrci(IType(x)) => x,
             Instruction::Lrw(RType(x)) => x,
             Instruction::Scw(RType(x)) => x,
             Instruction::Amoswapw(RType(x)) => x,
@@ -743,6 +1864,116 @@ impl From<Instruction> for u32 {
             Instruction::Amomaxd(RType(x)) => x,
             Instruction::Amominud(RType(x)) => x,
             Instruction::Amomaxud(RType(x)) => x,
+            Instruction::Flw(IType(x)) => x,
+            Instruction::Fsw(SType(x)) => x,
+            Instruction::Fmadds(R4Type(x)) => x,
+            Instruction::Fmsubs(R4Type(x)) => x,
+            Instruction::Fnmsubs(R4Type(x)) => x,
+            Instruction::Fnmadds(R4Type(x)) => x,
+            Instruction::Fadds(RType(x)) => x,
+            Instruction::Fsubs(RType(x)) => x,
+            Instruction::Fmuls(RType(x)) => x,
+            Instruction::Fdivs(RType(x)) => x,
+            Instruction::Fsqrts(RType(x)) => x,
+            Instruction::Fsgnjs(RType(x)) => x,
+            Instruction::Fsgnjns(RType(x)) => x,
+            Instruction::Fsgnjxs(RType(x)) => x,
+            Instruction::Fmins(RType(x)) => x,
+            Instruction::Fmaxs(RType(x)) => x,
+            Instruction::Fcvtws(RType(x)) => x,
+            Instruction::Fcvtwus(RType(x)) => x,
+            Instruction::Fcvtls(RType(x)) => x,
+            Instruction::Fcvtlus(RType(x)) => x,
+            Instruction::Fcvtsw(RType(x)) => x,
+            Instruction::Fcvtswu(RType(x)) => x,
+            Instruction::Fcvtsl(RType(x)) => x,
+            Instruction::Fcvtslu(RType(x)) => x,
+            Instruction::Feqs(RType(x)) => x,
+            Instruction::Flts(RType(x)) => x,
+            Instruction::Fles(RType(x)) => x,
+            Instruction::Fclasss(RType(x)) => x,
+            Instruction::Fmvxw(RType(x)) => x,
+            Instruction::Fmvwx(RType(x)) => x,
+            Instruction::Fld(IType(x)) => x,
+            Instruction::Fsd(SType(x)) => x,
+            Instruction::Fmaddd(R4Type(x)) => x,
+            Instruction::Fmsubd(R4Type(x)) => x,
+            Instruction::Fnmsubd(R4Type(x)) => x,
+            Instruction::Fnmaddd(R4Type(x)) => x,
+            Instruction::Faddd(RType(x)) => x,
+            Instruction::Fsubd(RType(x)) => x,
+            Instruction::Fmuld(RType(x)) => x,
+            Instruction::Fdivd(RType(x)) => x,
+            Instruction::Fsqrtd(RType(x)) => x,
+            Instruction::Fsgnjd(RType(x)) => x,
+            Instruction::Fsgnjnd(RType(x)) => x,
+            Instruction::Fsgnjxd(RType(x)) => x,
+            Instruction::Fmind(RType(x)) => x,
+            Instruction::Fmaxd(RType(x)) => x,
+            Instruction::Fcvtwd(RType(x)) => x,
+            Instruction::Fcvtwud(RType(x)) => x,
+            Instruction::Fcvtld(RType(x)) => x,
+            Instruction::Fcvtlud(RType(x)) => x,
+            Instruction::Fcvtdw(RType(x)) => x,
+            Instruction::Fcvtdwu(RType(x)) => x,
+            Instruction::Fcvtdl(RType(x)) => x,
+            Instruction::Fcvtdlu(RType(x)) => x,
+            Instruction::Feqd(RType(x)) => x,
+            Instruction::Fltd(RType(x)) => x,
+            Instruction::Fled(RType(x)) => x,
+            Instruction::Fclassd(RType(x)) => x,
+            Instruction::Fmvxd(RType(x)) => x,
+            Instruction::Fmvdx(RType(x)) => x,
+            Instruction::Fcvtsd(RType(x)) => x,
+            Instruction::Fcvtds(RType(x)) => x,
+            Instruction::Adduw(RType(x)) => x,
+            Instruction::Sh1add(RType(x)) => x,
+            Instruction::Sh2add(RType(x)) => x,
+            Instruction::Sh3add(RType(x)) => x,
+            Instruction::Sh1adduw(RType(x)) => x,
+            Instruction::Sh2adduw(RType(x)) => x,
+            Instruction::Sh3adduw(RType(x)) => x,
+            Instruction::Slliuw(IType(x)) => x,
+            Instruction::Andn(RType(x)) => x,
+            Instruction::Orn(RType(x)) => x,
+            Instruction::Xnor(RType(x)) => x,
+            Instruction::Clz(IType(x)) => x,
+            Instruction::Clzw(IType(x)) => x,
+            Instruction::Ctz(IType(x)) => x,
+            Instruction::Ctzw(IType(x)) => x,
+            Instruction::Cpop(IType(x)) => x,
+            Instruction::Cpopw(IType(x)) => x,
+            Instruction::Max(RType(x)) => x,
+            Instruction::Maxu(RType(x)) => x,
+            Instruction::Min(RType(x)) => x,
+            Instruction::Minu(RType(x)) => x,
+            Instruction::Sextb(IType(x)) => x,
+            Instruction::Sexth(IType(x)) => x,
+            Instruction::Zexth(RType(x)) => x,
+            Instruction::Rol(RType(x)) => x,
+            Instruction::Rolw(RType(x)) => x,
+            Instruction::Ror(RType(x)) => x,
+            Instruction::Rorw(RType(x)) => x,
+            Instruction::Rori(IType(x)) => x,
+            Instruction::Roriw(IType(x)) => x,
+            Instruction::Orcb(IType(x)) => x,
+            Instruction::Rev8(IType(x)) => x,
+            Instruction::Clmul(RType(x)) => x,
+            Instruction::Clmulh(RType(x)) => x,
+            Instruction::Clmulr(RType(x)) => x,
+            Instruction::Bclr(RType(x)) => x,
+            Instruction::Bclri(IType(x)) => x,
+            Instruction::Bext(RType(x)) => x,
+            Instruction::Bexti(IType(x)) => x,
+            Instruction::Binv(RType(x)) => x,
+            Instruction::Binvi(IType(x)) => x,
+            Instruction::Bset(RType(x)) => x,
+            Instruction::Bseti(IType(x)) => x,
         }
     }
 }
+
+/// The CSR address as the sign-extended immediate of an I-type instruction.
+fn csr_imm(csr: Csr) -> i32 {
+    ((u32::from(csr) << 20) as i32) >> 20
+}
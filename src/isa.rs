@@ -0,0 +1,524 @@
+//! # ISA profiles
+//!
+//! An [`IsaProfile`] describes which instructions a program may use: either the tiny RISC-U subset
+//! of Selfie or the base integer ISA together with a set of standard [`Extension`]s. Profiles are
+//! parsed from ISA strings as used by `-march`:
+//!
+//! ```
+//! use riscu::isa::{Extension, IsaProfile};
+//!
+//! let profile: IsaProfile = "rv64imac_zicsr".parse().unwrap();
+//!
+//! assert!(profile.contains(Extension::C));
+//! assert!(!profile.contains(Extension::F));
+//! assert_eq!("riscu".parse::<IsaProfile>().unwrap(), IsaProfile::RISCU);
+//! ```
+
+use crate::Instruction;
+use core::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// A standard extension of the RV64I base integer ISA.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Extension {
+    /// The base integer ISA itself.
+    I,
+    /// Integer multiplication and division.
+    M,
+    /// Atomic instructions.
+    A,
+    /// Single-precision floating point.
+    F,
+    /// Double-precision floating point.
+    D,
+    /// Compressed instructions.
+    C,
+    /// Control and status register instructions. The privileged instructions (`mret`, `sret`,
+    /// `wfi` and `sfence.vma`) are accounted to this extension as well.
+    Zicsr,
+    /// Instruction-fetch fence.
+    Zifencei,
+    /// Address generation.
+    Zba,
+    /// Basic bit-manipulation.
+    Zbb,
+    /// Carry-less multiplication.
+    Zbc,
+    /// Single-bit instructions.
+    Zbs,
+}
+
+impl Extension {
+    const ALL: [Extension; 12] = [
+        Extension::I,
+        Extension::M,
+        Extension::A,
+        Extension::F,
+        Extension::D,
+        Extension::C,
+        Extension::Zicsr,
+        Extension::Zifencei,
+        Extension::Zba,
+        Extension::Zbb,
+        Extension::Zbc,
+        Extension::Zbs,
+    ];
+
+    /// The name of the extension as used in ISA strings.
+    pub fn name(self) -> &'static str {
+        match self {
+            Extension::I => "i",
+            Extension::M => "m",
+            Extension::A => "a",
+            Extension::F => "f",
+            Extension::D => "d",
+            Extension::C => "c",
+            Extension::Zicsr => "zicsr",
+            Extension::Zifencei => "zifencei",
+            Extension::Zba => "zba",
+            Extension::Zbb => "zbb",
+            Extension::Zbc => "zbc",
+            Extension::Zbs => "zbs",
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+impl fmt::Display for Extension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The width of the integer registers of a base ISA.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Xlen {
+    Rv32,
+    Rv64,
+}
+
+impl Xlen {
+    pub fn bits(self) -> u32 {
+        match self {
+            Xlen::Rv32 => 32,
+            Xlen::Rv64 => 64,
+        }
+    }
+
+    /// The size of a register (and of a word in memory) in bytes.
+    pub fn bytes(self) -> usize {
+        self.bits() as usize / 8
+    }
+}
+
+impl fmt::Display for Xlen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rv{}", self.bits())
+    }
+}
+
+/// The instructions a decoder accepts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct IsaProfile {
+    extensions: u16,
+    riscu: bool,
+    xlen: Xlen,
+}
+
+impl IsaProfile {
+    /// The 14 instructions of RISC-U, all of them uncompressed.
+    pub const RISCU: IsaProfile = IsaProfile {
+        extensions: 1 << Extension::I as u16 | 1 << Extension::M as u16,
+        riscu: true,
+        xlen: Xlen::Rv64,
+    };
+
+    /// The 32-bit base integer ISA.
+    pub const RV32I: IsaProfile = IsaProfile::RV64I.with_xlen(Xlen::Rv32);
+
+    /// The 32-bit base integer ISA with multiplication, atomics and compressed instructions.
+    pub const RV32IMAC: IsaProfile = IsaProfile::RV64IMAC.with_xlen(Xlen::Rv32);
+
+    /// The base integer ISA.
+    pub const RV64I: IsaProfile = IsaProfile::with_bits(1 << Extension::I as u16);
+
+    /// The base integer ISA with multiplication, atomics and compressed instructions.
+    pub const RV64IMAC: IsaProfile = IsaProfile::with_bits(
+        1 << Extension::I as u16
+            | 1 << Extension::M as u16
+            | 1 << Extension::A as u16
+            | 1 << Extension::C as u16,
+    );
+
+    /// The general-purpose ISA (IMAFD, Zicsr and Zifencei) with compressed instructions.
+    pub const RV64GC: IsaProfile = IsaProfile::with_bits(
+        1 << Extension::I as u16
+            | 1 << Extension::M as u16
+            | 1 << Extension::A as u16
+            | 1 << Extension::F as u16
+            | 1 << Extension::D as u16
+            | 1 << Extension::C as u16
+            | 1 << Extension::Zicsr as u16
+            | 1 << Extension::Zifencei as u16,
+    );
+
+    const fn with_bits(extensions: u16) -> Self {
+        Self {
+            extensions,
+            riscu: false,
+            xlen: Xlen::Rv64,
+        }
+    }
+
+    /// The base integer ISA together with the given extensions.
+    pub fn new(extensions: &[Extension]) -> Self {
+        extensions
+            .iter()
+            .fold(Self::RV64I, |profile, extension| profile.with(*extension))
+    }
+
+    /// This profile extended by `extension`. Extending RISC-U yields the corresponding RV64
+    /// profile, as RISC-U is no longer a meaningful restriction then.
+    pub fn with(self, extension: Extension) -> Self {
+        Self {
+            extensions: self.extensions | extension.bit(),
+            riscu: false,
+            xlen: self.xlen,
+        }
+    }
+
+    /// This profile with integer registers of width `xlen`. RISC-U is a subset of RV64 and is
+    /// returned unchanged.
+    pub const fn with_xlen(self, xlen: Xlen) -> Self {
+        if self.riscu {
+            return self;
+        }
+
+        Self { xlen, ..self }
+    }
+
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
+    }
+
+    /// Returns true if instructions of `extension` are part of this profile. For RISC-U only a
+    /// subset of the instructions of I and M is.
+    pub fn contains(&self, extension: Extension) -> bool {
+        self.extensions & extension.bit() != 0
+    }
+
+    /// The extensions of this profile in canonical order.
+    pub fn extensions(&self) -> impl Iterator<Item = Extension> + '_ {
+        Extension::ALL
+            .iter()
+            .copied()
+            .filter(move |extension| self.contains(*extension))
+    }
+
+    /// Returns true for the RISC-U profile.
+    pub fn is_riscu(&self) -> bool {
+        self.riscu
+    }
+
+    /// Returns true if `instruction` is part of this profile. Whether compressed encodings are
+    /// acceptable depends on [`Extension::C`] being part of the profile.
+    pub fn accepts(&self, instruction: &Instruction) -> bool {
+        if self.riscu {
+            instruction.is_riscu()
+        } else {
+            self.contains(instruction.extension())
+                && (self.xlen == Xlen::Rv64 || !instruction.requires_rv64())
+        }
+    }
+}
+
+impl fmt::Display for IsaProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.riscu {
+            return write!(f, "riscu");
+        }
+
+        write!(f, "{}", self.xlen)?;
+
+        for extension in self.extensions() {
+            match extension.name() {
+                name if name.len() == 1 => write!(f, "{}", name)?,
+                name => write!(f, "_{}", name)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum IsaParseError {
+    #[error("ISA string has to start with rv32 or rv64 followed by i or g (or be riscu)")]
+    InvalidBase,
+
+    #[error("Unknown extension `{0}` in ISA string")]
+    UnknownExtension(String),
+}
+
+impl FromStr for IsaProfile {
+    type Err = IsaParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+
+        if s == "riscu" {
+            return Ok(IsaProfile::RISCU);
+        }
+
+        let mut parts = s.split('_');
+        let base = parts.next().unwrap_or_default();
+
+        let (xlen, single) = if let Some(single) = base.strip_prefix("rv64") {
+            (Xlen::Rv64, single)
+        } else if let Some(single) = base.strip_prefix("rv32") {
+            (Xlen::Rv32, single)
+        } else {
+            return Err(IsaParseError::InvalidBase);
+        };
+
+        let mut profile = match single.chars().next() {
+            Some('i') => IsaProfile::RV64I,
+            Some('g') => IsaProfile::new(&[
+                Extension::M,
+                Extension::A,
+                Extension::F,
+                Extension::D,
+                Extension::Zicsr,
+                Extension::Zifencei,
+            ]),
+            _ => return Err(IsaParseError::InvalidBase),
+        }
+        .with_xlen(xlen);
+
+        for letter in single.chars().skip(1) {
+            let extension = match letter {
+                'm' => Extension::M,
+                'a' => Extension::A,
+                'f' => Extension::F,
+                'd' => Extension::D,
+                'c' => Extension::C,
+                _ => return Err(IsaParseError::UnknownExtension(letter.to_string())),
+            };
+
+            profile = profile.with(extension);
+        }
+
+        for name in parts.filter(|name| !name.is_empty()) {
+            let extension = Extension::ALL
+                .iter()
+                .copied()
+                .find(|extension| extension.name().len() > 1 && extension.name() == name)
+                .ok_or_else(|| IsaParseError::UnknownExtension(name.to_string()))?;
+
+            profile = profile.with(extension);
+        }
+
+        Ok(profile)
+    }
+}
+
+impl Instruction {
+    /// The extension which introduced this instruction.
+    pub fn extension(&self) -> Extension {
+        use Instruction::*;
+
+        match self {
+            Lui(_) | Auipc(_) | Jal(_) | Jalr(_) | Beq(_) | Bne(_) | Blt(_) | Bge(_) | Bltu(_)
+            | Bgeu(_) | Lb(_) | Lh(_) | Lw(_) | Ld(_) | Lbu(_) | Lhu(_) | Lwu(_) | Sb(_)
+            | Sh(_) | Sw(_) | Sd(_) | Fence(_) | Addi(_) | Slti(_) | Sltiu(_) | Xori(_)
+            | Ori(_) | Andi(_) | Slli(_) | Srli(_) | Srai(_) | Addiw(_) | Slliw(_) | Srliw(_)
+            | Sraiw(_) | Add(_) | Sub(_) | Sll(_) | Slt(_) | Sltu(_) | Xor(_) | Srl(_) | Sra(_)
+            | Or(_) | And(_) | Addw(_) | Subw(_) | Sllw(_) | Srlw(_) | Sraw(_) | Ecall(_)
+            | Ebreak(_) => Extension::I,
+            Mul(_) | Mulh(_) | Mulhsu(_) | Mulhu(_) | Div(_) | Divu(_) | Rem(_) | Remu(_)
+            | Mulw(_) | Divw(_) | Divuw(_) | Remw(_) | Remuw(_) => Extension::M,
+            Sret(_) | Mret(_) | Wfi(_) | SfenceVma(_) | Csrrw(_) | Csrrs(_) | Csrrc(_)
+            | Csrrwi(_) | Csrrsi(_) | Csrrci(_) => Extension::Zicsr,
+            FenceI(_) => Extension::Zifencei,
+            Lrw(_) | Scw(_) | Amoswapw(_) | Amoaddw(_) | Amoxorw(_) | Amoandw(_) | Amoorw(_)
+            | Amominw(_) | Amomaxw(_) | Amominuw(_) | Amomaxuw(_) | Lrd(_) | Scd(_)
+            | Amoswapd(_) | Amoaddd(_) | Amoxord(_) | Amoandd(_) | Amoord(_) | Amomind(_)
+            | Amomaxd(_) | Amominud(_) | Amomaxud(_) => Extension::A,
+            Flw(_) | Fsw(_) | Fmadds(_) | Fmsubs(_) | Fnmsubs(_) | Fnmadds(_) | Fadds(_)
+            | Fsubs(_) | Fmuls(_) | Fdivs(_) | Fsqrts(_) | Fsgnjs(_) | Fsgnjns(_) | Fsgnjxs(_)
+            | Fmins(_) | Fmaxs(_) | Fcvtws(_) | Fcvtwus(_) | Fcvtls(_) | Fcvtlus(_) | Fcvtsw(_)
+            | Fcvtswu(_) | Fcvtsl(_) | Fcvtslu(_) | Feqs(_) | Flts(_) | Fles(_) | Fclasss(_)
+            | Fmvxw(_) | Fmvwx(_) => Extension::F,
+            Fld(_) | Fsd(_) | Fmaddd(_) | Fmsubd(_) | Fnmsubd(_) | Fnmaddd(_) | Faddd(_)
+            | Fsubd(_) | Fmuld(_) | Fdivd(_) | Fsqrtd(_) | Fsgnjd(_) | Fsgnjnd(_) | Fsgnjxd(_)
+            | Fmind(_) | Fmaxd(_) | Fcvtwd(_) | Fcvtwud(_) | Fcvtld(_) | Fcvtlud(_) | Fcvtdw(_)
+            | Fcvtdwu(_) | Fcvtdl(_) | Fcvtdlu(_) | Feqd(_) | Fltd(_) | Fled(_) | Fclassd(_)
+            | Fmvxd(_) | Fmvdx(_) | Fcvtsd(_) | Fcvtds(_) => Extension::D,
+            Adduw(_) | Sh1add(_) | Sh2add(_) | Sh3add(_) | Sh1adduw(_) | Sh2adduw(_)
+            | Sh3adduw(_) | Slliuw(_) => Extension::Zba,
+            Andn(_) | Orn(_) | Xnor(_) | Clz(_) | Clzw(_) | Ctz(_) | Ctzw(_) | Cpop(_)
+            | Cpopw(_) | Max(_) | Maxu(_) | Min(_) | Minu(_) | Sextb(_) | Sexth(_) | Zexth(_)
+            | Rol(_) | Rolw(_) | Ror(_) | Rorw(_) | Rori(_) | Roriw(_) | Orcb(_) | Rev8(_) => {
+                Extension::Zbb
+            }
+            Clmul(_) | Clmulh(_) | Clmulr(_) => Extension::Zbc,
+            Bclr(_) | Bclri(_) | Bext(_) | Bexti(_) | Binv(_) | Binvi(_) | Bset(_) | Bseti(_) => {
+                Extension::Zbs
+            }
+        }
+    }
+
+    /// Returns true if this instruction only exists in RV64, like `ld` or the word operations
+    /// `addw`, `addiw` etc. (and their counterparts in extensions).
+    pub fn requires_rv64(&self) -> bool {
+        use Instruction::*;
+
+        matches!(
+            self,
+            Ld(_)
+                | Lwu(_)
+                | Sd(_)
+                | Addiw(_)
+                | Slliw(_)
+                | Srliw(_)
+                | Sraiw(_)
+                | Addw(_)
+                | Subw(_)
+                | Sllw(_)
+                | Srlw(_)
+                | Sraw(_)
+                | Mulw(_)
+                | Divw(_)
+                | Divuw(_)
+                | Remw(_)
+                | Remuw(_)
+                | Lrd(_)
+                | Scd(_)
+                | Amoswapd(_)
+                | Amoaddd(_)
+                | Amoxord(_)
+                | Amoandd(_)
+                | Amoord(_)
+                | Amomind(_)
+                | Amomaxd(_)
+                | Amominud(_)
+                | Amomaxud(_)
+                | Fcvtls(_)
+                | Fcvtlus(_)
+                | Fcvtsl(_)
+                | Fcvtslu(_)
+                | Fcvtld(_)
+                | Fcvtlud(_)
+                | Fcvtdl(_)
+                | Fcvtdlu(_)
+                | Fmvxd(_)
+                | Fmvdx(_)
+                | Adduw(_)
+                | Sh1adduw(_)
+                | Sh2adduw(_)
+                | Sh3adduw(_)
+                | Slliuw(_)
+                | Clzw(_)
+                | Ctzw(_)
+                | Cpopw(_)
+                | Rolw(_)
+                | Rorw(_)
+                | Roriw(_)
+        )
+    }
+
+    /// Returns true if this instruction is one of the 14 instructions of RISC-U.
+    pub fn is_riscu(&self) -> bool {
+        use Instruction::*;
+
+        matches!(
+            self,
+            Lui(_)
+                | Addi(_)
+                | Ld(_)
+                | Sd(_)
+                | Add(_)
+                | Sub(_)
+                | Mul(_)
+                | Divu(_)
+                | Remu(_)
+                | Sltu(_)
+                | Beq(_)
+                | Jal(_)
+                | Jalr(_)
+                | Ecall(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Register::*;
+
+    #[test]
+    fn parse_profiles() {
+        assert_eq!("riscu".parse(), Ok(IsaProfile::RISCU));
+        assert_eq!("rv64i".parse(), Ok(IsaProfile::RV64I));
+        assert_eq!("rv64imac".parse(), Ok(IsaProfile::RV64IMAC));
+        assert_eq!("rv32i".parse(), Ok(IsaProfile::RV32I));
+        assert_eq!("rv32imac".parse(), Ok(IsaProfile::RV32IMAC));
+        assert_eq!("RV64GC".parse(), Ok(IsaProfile::RV64GC));
+        assert_eq!("rv64imafdc_zicsr_zifencei".parse(), Ok(IsaProfile::RV64GC));
+        assert_eq!(
+            "rv64imac_zicsr".parse(),
+            Ok(IsaProfile::RV64IMAC.with(Extension::Zicsr))
+        );
+        assert_eq!(
+            "rv64gc_zba_zbb".parse(),
+            Ok(IsaProfile::RV64GC.with(Extension::Zba).with(Extension::Zbb))
+        );
+
+        assert_eq!(
+            "rv128i".parse::<IsaProfile>(),
+            Err(IsaParseError::InvalidBase)
+        );
+        assert_eq!(
+            "rv64imv".parse::<IsaProfile>(),
+            Err(IsaParseError::UnknownExtension("v".to_string()))
+        );
+        assert_eq!(
+            "rv64i_zfoo".parse::<IsaProfile>(),
+            Err(IsaParseError::UnknownExtension("zfoo".to_string()))
+        );
+    }
+
+    #[test]
+    fn display_profiles() {
+        assert_eq!(IsaProfile::RISCU.to_string(), "riscu");
+        assert_eq!(IsaProfile::RV64IMAC.to_string(), "rv64imac");
+        assert_eq!(IsaProfile::RV32IMAC.to_string(), "rv32imac");
+        assert_eq!(IsaProfile::RV64GC.to_string(), "rv64imafdc_zicsr_zifencei");
+        assert_eq!(
+            IsaProfile::new(&[Extension::M, Extension::Zbs]).to_string(),
+            "rv64im_zbs"
+        );
+    }
+
+    #[test]
+    fn accepted_instructions() {
+        let mulw = Instruction::new_mulw(A0, A0, A1);
+        let mul = Instruction::new_mul(A0, A0, A1);
+
+        assert!(IsaProfile::RISCU.accepts(&mul));
+        assert!(!IsaProfile::RISCU.accepts(&mulw));
+        assert!(!IsaProfile::RISCU.accepts(&Instruction::new_ebreak()));
+        assert!(!IsaProfile::RV64I.accepts(&mul));
+        assert!(IsaProfile::RV64IMAC.accepts(&mulw));
+        assert!(!IsaProfile::RV64IMAC.accepts(&Instruction::new_mret()));
+        assert!(IsaProfile::RV64GC.accepts(&Instruction::new_fence_i()));
+        assert!(!IsaProfile::RV64GC.accepts(&Instruction::new_clz(A0, A0)));
+        assert!(IsaProfile::RISCU.with(Extension::I).accepts(&mulw));
+        assert!(!IsaProfile::RV32IMAC.accepts(&mulw));
+        assert!(IsaProfile::RV32IMAC.accepts(&mul));
+        assert!(!IsaProfile::RV32I.accepts(&Instruction::new_ld(A0, Sp, 0)));
+    }
+}
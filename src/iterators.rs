@@ -1,27 +1,62 @@
 use byteorder::{ByteOrder, LittleEndian};
+use thiserror::Error;
 
-use crate::{decode, instruction_length, Instruction};
+use crate::{
+    decode_xlen, decompress::compressed_mnemonic_xlen, instruction_length, isa::Xlen,
+    DecodingError, Instruction,
+};
+
+/// An instruction together with its location and encoding in memory.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DecodedInstruction {
+    /// The address of the first byte of the instruction.
+    pub address: u64,
+    /// The raw encoding, compressed instructions occupy the lower 16 bits only.
+    pub raw: u32,
+    /// The length of the encoding in bytes.
+    pub length: usize,
+    /// The instruction, compressed instructions are expanded to their 32-bit equivalent.
+    pub instruction: Instruction,
+    /// The mnemonic of the compressed form (e.g. `c.addi`), if the instruction is compressed.
+    pub compressed_mnemonic: Option<&'static str>,
+}
+
+impl DecodedInstruction {
+    pub fn is_compressed(&self) -> bool {
+        self.length == 2
+    }
+
+    /// The address of the instruction following this one in memory.
+    pub fn next_address(&self) -> u64 {
+        self.address + self.length as u64
+    }
+}
+
+/// An error while decoding the instruction at `address`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Error)]
+#[error("Failed to decode instruction at {address:#x}: {error}")]
+pub struct InstructionDecodingError {
+    pub address: u64,
+    pub error: DecodingError,
+}
 
 /// An iterator for all PC values where an instruction begins.
 pub struct LocationIter<'a> {
-    memory_view: &'a [u8],
-    current_index: u64,
-    address: u64,
+    instructions: TryInstructionIter<'a>,
 }
 
 impl LocationIter<'_> {
     pub fn new(memory_view: &[u8], address: u64) -> LocationIter<'_> {
         LocationIter {
-            memory_view,
-            current_index: 0,
-            address,
+            instructions: TryInstructionIter::new(memory_view, address),
         }
     }
 
-    fn current_hword(&self) -> u16 {
-        let idx: usize = self.current_index as usize;
-        let begin = &self.memory_view[idx..idx + 2];
-        LittleEndian::read_u16(begin)
+    /// Decode instructions for the given XLEN instead of RV64.
+    pub fn with_xlen(self, xlen: Xlen) -> Self {
+        LocationIter {
+            instructions: self.instructions.with_xlen(xlen),
+        }
     }
 }
 
@@ -29,85 +64,227 @@ impl Iterator for LocationIter<'_> {
     type Item = u64;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if (self.current_index as usize) >= self.memory_view.len() {
-            // Ensure that we never go out of bound.
-            return None;
-        }
-
-        match instruction_length(self.current_hword()) {
-            2 => {
-                self.current_index += 2;
-                Some(self.address + self.current_index - 2)
-            }
-            4 => {
-                if (self.current_index as usize) <= self.memory_view.len() - 4 {
-                    self.current_index += 4;
-                    Some(self.address + self.current_index - 4)
-                } else {
-                    // There should be a 4 byte instruction but the end of the
-                    // slice is less than 4 byte long!
-                    panic!("Unaligned instruction!");
-                }
+        match self.instructions.next()? {
+            Ok(decoded) => Some(decoded.address),
+            Err(InstructionDecodingError {
+                error: DecodingError::Truncated,
+                ..
+            }) => {
+                // There should be an instruction but the end of the slice is
+                // shorter than the instruction!
+                panic!("Unaligned instruction!")
             }
-            l => panic!("Unimplemented instruction length: {}", l),
+            // The instruction could not be decoded, but its length is known.
+            Err(InstructionDecodingError { address, .. }) => Some(address),
         }
     }
 }
 
 /// An iterator for all instructions in the program.
+///
+/// Panics on instructions which can not be decoded, see [`TryInstructionIter`] for a fallible
+/// version.
 pub struct InstructionIter<'a> {
-    memory_view: &'a [u8],
-    current_index: u64,
+    instructions: TryInstructionIter<'a>,
 }
 
 impl InstructionIter<'_> {
-    pub fn new(memory_view: &[u8]) -> InstructionIter<'_> {
+    pub fn new(memory_view: &[u8], address: u64) -> InstructionIter<'_> {
         InstructionIter {
-            memory_view,
-            current_index: 0,
+            instructions: TryInstructionIter::new(memory_view, address),
         }
     }
 
-    fn current_hword(&self) -> u16 {
-        let idx: usize = self.current_index as usize;
-        let begin = &self.memory_view[idx..idx + 2];
-
-        LittleEndian::read_u16(begin)
+    /// Decode instructions for the given XLEN instead of RV64.
+    pub fn with_xlen(self, xlen: Xlen) -> Self {
+        InstructionIter {
+            instructions: self.instructions.with_xlen(xlen),
+        }
     }
+}
 
-    fn fetch_hword(&mut self) -> u16 {
-        let half_word = self.current_hword();
-
-        self.current_index += 2;
+impl Iterator for InstructionIter<'_> {
+    type Item = DecodedInstruction;
 
-        half_word
+    fn next(&mut self) -> Option<Self::Item> {
+        self.instructions
+            .next()
+            .map(|result| result.expect("valid instruction"))
     }
+}
 
-    fn fetch_word(&mut self) -> u32 {
-        let idx: usize = self.current_index as usize;
-        let begin = &self.memory_view[idx..idx + 4];
+/// An iterator for all instructions in the program, which never panics.
+///
+/// Instructions which can not be decoded are reported as errors and skipped, as long as their
+/// length is known. The iteration ends after an instruction is truncated by the end of the
+/// memory view.
+pub struct TryInstructionIter<'a> {
+    memory_view: &'a [u8],
+    current_index: u64,
+    address: u64,
+    xlen: Xlen,
+}
 
-        self.current_index += 4;
+impl TryInstructionIter<'_> {
+    pub fn new(memory_view: &[u8], address: u64) -> TryInstructionIter<'_> {
+        TryInstructionIter {
+            memory_view,
+            current_index: 0,
+            address,
+            xlen: Xlen::Rv64,
+        }
+    }
+
+    /// Decode instructions for the given XLEN instead of RV64.
+    pub fn with_xlen(self, xlen: Xlen) -> Self {
+        Self { xlen, ..self }
+    }
 
-        LittleEndian::read_u32(begin)
+    fn remaining(&self) -> &[u8] {
+        &self.memory_view[self.current_index as usize..]
     }
 }
 
-impl Iterator for InstructionIter<'_> {
-    type Item = Instruction;
+impl Iterator for TryInstructionIter<'_> {
+    type Item = Result<DecodedInstruction, InstructionDecodingError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_index >= self.memory_view.len() as u64 {
+        let remaining = self.remaining();
+
+        if remaining.is_empty() {
             return None;
         }
 
-        Some(
-            decode(match instruction_length(self.current_hword()) {
-                2 => self.fetch_hword().into(),
-                4 => self.fetch_word(),
-                l => panic!("Unimplemented instruction length: {}", l),
-            })
-            .expect("valid instruction"),
-        )
+        let address = self.address + self.current_index;
+        let error = |error| InstructionDecodingError { address, error };
+
+        let length = if remaining.len() < 2 {
+            None
+        } else {
+            Some(instruction_length(LittleEndian::read_u16(remaining)))
+                .filter(|length| *length <= remaining.len())
+        };
+
+        let length = match length {
+            Some(length) => length,
+            None => {
+                // Nothing after a truncated instruction can be decoded.
+                self.current_index = self.memory_view.len() as u64;
+
+                return Some(Err(error(DecodingError::Truncated)));
+            }
+        };
+
+        let raw = match length {
+            2 => LittleEndian::read_u16(remaining).into(),
+            4 => LittleEndian::read_u32(remaining),
+            _ => {
+                self.current_index += length as u64;
+
+                return Some(Err(error(DecodingError::Unimplemented)));
+            }
+        };
+
+        self.current_index += length as u64;
+
+        let result = decode_xlen(raw, self.xlen).map(|instruction| DecodedInstruction {
+            address,
+            raw,
+            length,
+            instruction,
+            compressed_mnemonic: if length == 2 {
+                compressed_mnemonic_xlen(raw as u16, self.xlen)
+            } else {
+                None
+            },
+        });
+
+        Some(result.map_err(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Register::*;
+
+    #[test]
+    fn fallible_instruction_iteration() {
+        let memory = [
+            0x13, 0x05, 0x81, 0x00, // addi a0,sp,8
+            0x01, 0x00, // c.nop
+            0x33, 0x00, 0x00, 0xfe, // unknown
+            0x1f, 0x00, 0x00, 0x00, 0x00, 0x00, // 48 bit instruction
+            0x93, 0x07, // truncated 32 bit instruction
+        ];
+
+        let items = TryInstructionIter::new(&memory, 0x1000).collect::<Vec<_>>();
+
+        assert_eq!(
+            items,
+            vec![
+                Ok(DecodedInstruction {
+                    address: 0x1000,
+                    raw: 0x00810513,
+                    length: 4,
+                    instruction: Instruction::new_addi(A0, Sp, 8),
+                    compressed_mnemonic: None,
+                }),
+                Ok(DecodedInstruction {
+                    address: 0x1004,
+                    raw: 0x0001,
+                    length: 2,
+                    instruction: Instruction::new_nop(),
+                    compressed_mnemonic: Some("c.nop"),
+                }),
+                Err(InstructionDecodingError {
+                    address: 0x1006,
+                    error: DecodingError::Unknown
+                }),
+                Err(InstructionDecodingError {
+                    address: 0x100a,
+                    error: DecodingError::Unimplemented
+                }),
+                Err(InstructionDecodingError {
+                    address: 0x1010,
+                    error: DecodingError::Truncated
+                }),
+            ]
+        );
+
+        assert_eq!(
+            LocationIter::new(&memory[..16], 0x1000).collect::<Vec<_>>(),
+            vec![0x1000, 0x1004, 0x1006, 0x100a]
+        );
+    }
+
+    #[test]
+    fn decoded_instructions_preserve_encoding() {
+        let memory = [
+            0x41, 0x11, // c.addi sp,sp,-16
+            0x06, 0xe4, // c.sdsp ra,8(sp)
+            0xef, 0x00, 0x00, 0x01, // jal ra,.+16
+            0x82, 0x80, // c.jr ra
+        ];
+
+        let decoded = InstructionIter::new(&memory, 0x10000).collect::<Vec<_>>();
+
+        assert_eq!(
+            decoded
+                .iter()
+                .map(|d| (d.address, d.raw, d.length, d.compressed_mnemonic))
+                .collect::<Vec<_>>(),
+            vec![
+                (0x10000, 0x1141, 2, Some("c.addi")),
+                (0x10002, 0xe406, 2, Some("c.sdsp")),
+                (0x10004, 0x010000ef, 4, None),
+                (0x10008, 0x8082, 2, Some("c.jr")),
+            ]
+        );
+
+        assert!(decoded[0].is_compressed());
+        assert_eq!(decoded[1].instruction, Instruction::new_sd(Sp, Ra, 8));
+        assert_eq!(decoded[2].next_address(), 0x10008);
+        assert_eq!(decoded[3].next_address(), 0x1000a);
     }
 }
@@ -1,12 +1,30 @@
+pub mod assemble;
+pub mod callgraph;
+pub mod cfg;
+pub mod compress;
+pub mod dataflow;
 pub mod decode;
 pub mod decompress;
+pub mod disassemble;
 pub mod elf;
+pub mod emulator;
+pub mod frame;
 pub mod instruction;
+pub mod isa;
 pub mod iterators;
+pub mod metadata;
+pub mod opcode;
 pub mod register;
+pub mod symbol;
+pub mod syscall;
 pub mod types;
 
 pub use decode::*;
 pub use elf::*;
 pub use instruction::Instruction;
-pub use register::Register;
+pub use isa::{Extension, IsaProfile};
+pub use metadata::{ControlFlow, InstructionClass, MemAccess};
+pub use opcode::Opcode;
+pub use register::{Csr, FRegister, Register};
+pub use symbol::{Symbol, SymbolKind};
+pub use syscall::{SelfieSyscall, Syscall, SyscallSite};
@@ -0,0 +1,501 @@
+//! # Instruction metadata
+//!
+//! Structured information about the operands and the effects of an [`Instruction`], so that
+//! analyses do not need to match on every single variant to find out which registers are read
+//! and written:
+//!
+//! ```
+//! use riscu::{metadata::ControlFlow, Instruction, Register};
+//!
+//! let instruction = Instruction::new_jal(Register::Ra, 16);
+//!
+//! assert_eq!(instruction.rd(), Some(Register::Ra));
+//! assert_eq!(instruction.sources().count(), 0);
+//! assert_eq!(instruction.control_flow(0x1000), ControlFlow::Call { target: 0x1010 });
+//! ```
+//!
+//! Only integer registers are reported as operands. Floating-point registers are a separate
+//! register file, so `fadd.s` has neither a destination nor sources in terms of [`Register`],
+//! while `flw` reads its base register and `fcvt.w.s` writes its integer destination.
+//! Implicit operands (like the syscall arguments of `ecall`) and CSRs are not reported either.
+
+use crate::{Instruction, Register};
+
+/// A coarse classification of instructions.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum InstructionClass {
+    /// Integer computations, including `lui`, `auipc` and the M and bit-manipulation extensions.
+    Alu,
+    /// Loads from memory into an integer or floating-point register.
+    Load,
+    /// Stores from an integer or floating-point register to memory.
+    Store,
+    /// Conditional branches.
+    Branch,
+    /// Unconditional jumps (`jal` and `jalr`).
+    Jump,
+    /// Environment calls, CSR accesses and privileged instructions.
+    System,
+    /// Atomic memory operations, including `lr` and `sc`.
+    Atomic,
+    /// Memory ordering instructions (`fence`, `fence.i` and `sfence.vma`).
+    Fence,
+    /// Floating-point computations, comparisons, conversions and moves.
+    FloatingPoint,
+}
+
+/// A memory access of a load, store or atomic instruction at address `base + offset`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MemAccess {
+    /// The number of bytes accessed.
+    pub width: u8,
+    /// True if the loaded value is sign-extended, i.e. for `lb`, `lh`, `lw`, `ld`, `lr` and the
+    /// AMOs. Always false for stores and floating-point loads.
+    pub signed: bool,
+    pub base: Register,
+    pub offset: i32,
+}
+
+/// The effect of an instruction on the program counter.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ControlFlow {
+    /// Execution continues with the next instruction. This includes `ecall` and `ebreak`.
+    FallThrough,
+    /// A conditional branch to `target`, otherwise execution continues with the next instruction.
+    Branch { target: u64 },
+    /// An unconditional jump to `target`.
+    Jump { target: u64 },
+    /// An unconditional jump to `base + offset`.
+    IndirectJump { base: Register, offset: i32 },
+    /// A function call to `target`, which returns to the next instruction.
+    Call { target: u64 },
+    /// A function call to `base + offset`, which returns to the next instruction.
+    IndirectCall { base: Register, offset: i32 },
+    /// A return from a function to the address in the link register.
+    Return,
+    /// A return from a trap handler (`mret` and `sret`).
+    TrapReturn,
+}
+
+impl ControlFlow {
+    /// The statically known target of a branch, jump or call.
+    pub fn target(&self) -> Option<u64> {
+        match *self {
+            ControlFlow::Branch { target }
+            | ControlFlow::Jump { target }
+            | ControlFlow::Call { target } => Some(target),
+            _ => None,
+        }
+    }
+
+    /// Returns true if execution may continue with the next instruction, either directly or
+    /// after a call returns.
+    pub fn falls_through(&self) -> bool {
+        matches!(
+            self,
+            ControlFlow::FallThrough
+                | ControlFlow::Branch { .. }
+                | ControlFlow::Call { .. }
+                | ControlFlow::IndirectCall { .. }
+        )
+    }
+}
+
+impl Instruction {
+    /// The integer register written by this instruction, which may be [`Register::Zero`].
+    pub fn rd(&self) -> Option<Register> {
+        use Instruction::*;
+
+        match self {
+            Lui(u) | Auipc(u) => Some(u.rd()),
+            Jal(j) => Some(j.rd()),
+            Jalr(i) | Lb(i) | Lh(i) | Lw(i) | Ld(i) | Lbu(i) | Lhu(i) | Lwu(i) | Addi(i)
+            | Slti(i) | Sltiu(i) | Xori(i) | Ori(i) | Andi(i) | Slli(i) | Srli(i) | Srai(i)
+            | Addiw(i) | Slliw(i) | Srliw(i) | Sraiw(i) | Csrrw(i) | Csrrs(i) | Csrrc(i)
+            | Csrrwi(i) | Csrrsi(i) | Csrrci(i) | Slliuw(i) | Clz(i) | Clzw(i) | Ctz(i)
+            | Ctzw(i) | Cpop(i) | Cpopw(i) | Sextb(i) | Sexth(i) | Rori(i) | Roriw(i) | Orcb(i)
+            | Rev8(i) | Bclri(i) | Bexti(i) | Binvi(i) | Bseti(i) => Some(i.rd()),
+            Add(r) | Sub(r) | Sll(r) | Slt(r) | Sltu(r) | Xor(r) | Srl(r) | Sra(r) | Or(r)
+            | And(r) | Mul(r) | Mulh(r) | Mulhsu(r) | Mulhu(r) | Div(r) | Divu(r) | Rem(r)
+            | Remu(r) | Addw(r) | Subw(r) | Sllw(r) | Srlw(r) | Sraw(r) | Mulw(r) | Divw(r)
+            | Divuw(r) | Remw(r) | Remuw(r) | Lrw(r) | Scw(r) | Amoswapw(r) | Amoaddw(r)
+            | Amoxorw(r) | Amoandw(r) | Amoorw(r) | Amominw(r) | Amomaxw(r) | Amominuw(r)
+            | Amomaxuw(r) | Lrd(r) | Scd(r) | Amoswapd(r) | Amoaddd(r) | Amoxord(r)
+            | Amoandd(r) | Amoord(r) | Amomind(r) | Amomaxd(r) | Amominud(r) | Amomaxud(r)
+            | Fcvtws(r) | Fcvtwus(r) | Fcvtls(r) | Fcvtlus(r) | Feqs(r) | Flts(r) | Fles(r)
+            | Fclasss(r) | Fmvxw(r) | Fcvtwd(r) | Fcvtwud(r) | Fcvtld(r) | Fcvtlud(r) | Feqd(r)
+            | Fltd(r) | Fled(r) | Fclassd(r) | Fmvxd(r) | Adduw(r) | Sh1add(r) | Sh2add(r)
+            | Sh3add(r) | Sh1adduw(r) | Sh2adduw(r) | Sh3adduw(r) | Andn(r) | Orn(r) | Xnor(r)
+            | Max(r) | Maxu(r) | Min(r) | Minu(r) | Zexth(r) | Rol(r) | Rolw(r) | Ror(r)
+            | Rorw(r) | Clmul(r) | Clmulh(r) | Clmulr(r) | Bclr(r) | Bext(r) | Binv(r)
+            | Bset(r) => Some(r.rd()),
+            Beq(_) | Bne(_) | Blt(_) | Bge(_) | Bltu(_) | Bgeu(_) | Sb(_) | Sh(_) | Sw(_)
+            | Sd(_) | Fence(_) | FenceI(_) | Ecall(_) | Ebreak(_) | Sret(_) | Mret(_) | Wfi(_)
+            | SfenceVma(_) | Flw(_) | Fsw(_) | Fmadds(_) | Fmsubs(_) | Fnmsubs(_) | Fnmadds(_)
+            | Fadds(_) | Fsubs(_) | Fmuls(_) | Fdivs(_) | Fsqrts(_) | Fsgnjs(_) | Fsgnjns(_)
+            | Fsgnjxs(_) | Fmins(_) | Fmaxs(_) | Fcvtsw(_) | Fcvtswu(_) | Fcvtsl(_)
+            | Fcvtslu(_) | Fmvwx(_) | Fld(_) | Fsd(_) | Fmaddd(_) | Fmsubd(_) | Fnmsubd(_)
+            | Fnmaddd(_) | Faddd(_) | Fsubd(_) | Fmuld(_) | Fdivd(_) | Fsqrtd(_) | Fsgnjd(_)
+            | Fsgnjnd(_) | Fsgnjxd(_) | Fmind(_) | Fmaxd(_) | Fcvtdw(_) | Fcvtdwu(_)
+            | Fcvtdl(_) | Fcvtdlu(_) | Fmvdx(_) | Fcvtsd(_) | Fcvtds(_) => None,
+        }
+    }
+
+    /// The integer registers read by this instruction, in operand order (`rs1` before `rs2`).
+    pub fn sources(&self) -> impl Iterator<Item = Register> {
+        let (rs1, rs2) = self.source_operands();
+
+        rs1.into_iter().chain(rs2)
+    }
+
+    fn source_operands(&self) -> (Option<Register>, Option<Register>) {
+        use Instruction::*;
+
+        match self {
+            Jalr(i) | Lb(i) | Lh(i) | Lw(i) | Ld(i) | Lbu(i) | Lhu(i) | Lwu(i) | Addi(i)
+            | Slti(i) | Sltiu(i) | Xori(i) | Ori(i) | Andi(i) | Slli(i) | Srli(i) | Srai(i)
+            | Addiw(i) | Slliw(i) | Srliw(i) | Sraiw(i) | Csrrw(i) | Csrrs(i) | Csrrc(i)
+            | Flw(i) | Fld(i) | Slliuw(i) | Clz(i) | Clzw(i) | Ctz(i) | Ctzw(i) | Cpop(i)
+            | Cpopw(i) | Sextb(i) | Sexth(i) | Rori(i) | Roriw(i) | Orcb(i) | Rev8(i)
+            | Bclri(i) | Bexti(i) | Binvi(i) | Bseti(i) => (Some(i.rs1()), None),
+            Lrw(r) | Lrd(r) | Fcvtsw(r) | Fcvtswu(r) | Fcvtsl(r) | Fcvtslu(r) | Fmvwx(r)
+            | Fcvtdw(r) | Fcvtdwu(r) | Fcvtdl(r) | Fcvtdlu(r) | Fmvdx(r) | Zexth(r) => {
+                (Some(r.rs1()), None)
+            }
+            Fsw(s) | Fsd(s) => (Some(s.rs1()), None),
+            Beq(b) | Bne(b) | Blt(b) | Bge(b) | Bltu(b) | Bgeu(b) => (Some(b.rs1()), Some(b.rs2())),
+            Sb(s) | Sh(s) | Sw(s) | Sd(s) => (Some(s.rs1()), Some(s.rs2())),
+            Add(r) | Sub(r) | Sll(r) | Slt(r) | Sltu(r) | Xor(r) | Srl(r) | Sra(r) | Or(r)
+            | And(r) | Mul(r) | Mulh(r) | Mulhsu(r) | Mulhu(r) | Div(r) | Divu(r) | Rem(r)
+            | Remu(r) | Addw(r) | Subw(r) | Sllw(r) | Srlw(r) | Sraw(r) | Mulw(r) | Divw(r)
+            | Divuw(r) | Remw(r) | Remuw(r) | SfenceVma(r) | Scw(r) | Amoswapw(r) | Amoaddw(r)
+            | Amoxorw(r) | Amoandw(r) | Amoorw(r) | Amominw(r) | Amomaxw(r) | Amominuw(r)
+            | Amomaxuw(r) | Scd(r) | Amoswapd(r) | Amoaddd(r) | Amoxord(r) | Amoandd(r)
+            | Amoord(r) | Amomind(r) | Amomaxd(r) | Amominud(r) | Amomaxud(r) | Adduw(r)
+            | Sh1add(r) | Sh2add(r) | Sh3add(r) | Sh1adduw(r) | Sh2adduw(r) | Sh3adduw(r)
+            | Andn(r) | Orn(r) | Xnor(r) | Max(r) | Maxu(r) | Min(r) | Minu(r) | Rol(r)
+            | Rolw(r) | Ror(r) | Rorw(r) | Clmul(r) | Clmulh(r) | Clmulr(r) | Bclr(r) | Bext(r)
+            | Binv(r) | Bset(r) => (Some(r.rs1()), Some(r.rs2())),
+            Lui(_) | Auipc(_) | Jal(_) | Fence(_) | FenceI(_) | Ecall(_) | Ebreak(_) | Sret(_)
+            | Mret(_) | Wfi(_) | Csrrwi(_) | Csrrsi(_) | Csrrci(_) | Fmadds(_) | Fmsubs(_)
+            | Fnmsubs(_) | Fnmadds(_) | Fadds(_) | Fsubs(_) | Fmuls(_) | Fdivs(_) | Fsqrts(_)
+            | Fsgnjs(_) | Fsgnjns(_) | Fsgnjxs(_) | Fmins(_) | Fmaxs(_) | Fcvtws(_)
+            | Fcvtwus(_) | Fcvtls(_) | Fcvtlus(_) | Feqs(_) | Flts(_) | Fles(_) | Fclasss(_)
+            | Fmvxw(_) | Fmaddd(_) | Fmsubd(_) | Fnmsubd(_) | Fnmaddd(_) | Faddd(_) | Fsubd(_)
+            | Fmuld(_) | Fdivd(_) | Fsqrtd(_) | Fsgnjd(_) | Fsgnjnd(_) | Fsgnjxd(_) | Fmind(_)
+            | Fmaxd(_) | Fcvtwd(_) | Fcvtwud(_) | Fcvtld(_) | Fcvtlud(_) | Feqd(_) | Fltd(_)
+            | Fled(_) | Fclassd(_) | Fmvxd(_) | Fcvtsd(_) | Fcvtds(_) => (None, None),
+        }
+    }
+
+    /// The class of this instruction.
+    pub fn kind(&self) -> InstructionClass {
+        use Instruction::*;
+
+        match self {
+            Jal(_) | Jalr(_) => InstructionClass::Jump,
+            Beq(_) | Bne(_) | Blt(_) | Bge(_) | Bltu(_) | Bgeu(_) => InstructionClass::Branch,
+            Lb(_) | Lh(_) | Lw(_) | Ld(_) | Lbu(_) | Lhu(_) | Lwu(_) | Flw(_) | Fld(_) => {
+                InstructionClass::Load
+            }
+            Sb(_) | Sh(_) | Sw(_) | Sd(_) | Fsw(_) | Fsd(_) => InstructionClass::Store,
+            Fence(_) | FenceI(_) | SfenceVma(_) => InstructionClass::Fence,
+            Ecall(_) | Ebreak(_) | Sret(_) | Mret(_) | Wfi(_) | Csrrw(_) | Csrrs(_) | Csrrc(_)
+            | Csrrwi(_) | Csrrsi(_) | Csrrci(_) => InstructionClass::System,
+            Lrw(_) | Scw(_) | Amoswapw(_) | Amoaddw(_) | Amoxorw(_) | Amoandw(_) | Amoorw(_)
+            | Amominw(_) | Amomaxw(_) | Amominuw(_) | Amomaxuw(_) | Lrd(_) | Scd(_)
+            | Amoswapd(_) | Amoaddd(_) | Amoxord(_) | Amoandd(_) | Amoord(_) | Amomind(_)
+            | Amomaxd(_) | Amominud(_) | Amomaxud(_) => InstructionClass::Atomic,
+            Fmadds(_) | Fmsubs(_) | Fnmsubs(_) | Fnmadds(_) | Fadds(_) | Fsubs(_) | Fmuls(_)
+            | Fdivs(_) | Fsqrts(_) | Fsgnjs(_) | Fsgnjns(_) | Fsgnjxs(_) | Fmins(_) | Fmaxs(_)
+            | Fcvtws(_) | Fcvtwus(_) | Fcvtls(_) | Fcvtlus(_) | Fcvtsw(_) | Fcvtswu(_)
+            | Fcvtsl(_) | Fcvtslu(_) | Feqs(_) | Flts(_) | Fles(_) | Fclasss(_) | Fmvxw(_)
+            | Fmvwx(_) | Fmaddd(_) | Fmsubd(_) | Fnmsubd(_) | Fnmaddd(_) | Faddd(_) | Fsubd(_)
+            | Fmuld(_) | Fdivd(_) | Fsqrtd(_) | Fsgnjd(_) | Fsgnjnd(_) | Fsgnjxd(_) | Fmind(_)
+            | Fmaxd(_) | Fcvtwd(_) | Fcvtwud(_) | Fcvtld(_) | Fcvtlud(_) | Fcvtdw(_)
+            | Fcvtdwu(_) | Fcvtdl(_) | Fcvtdlu(_) | Feqd(_) | Fltd(_) | Fled(_) | Fclassd(_)
+            | Fmvxd(_) | Fmvdx(_) | Fcvtsd(_) | Fcvtds(_) => InstructionClass::FloatingPoint,
+            Lui(_) | Auipc(_) | Addi(_) | Slti(_) | Sltiu(_) | Xori(_) | Ori(_) | Andi(_)
+            | Slli(_) | Srli(_) | Srai(_) | Addiw(_) | Slliw(_) | Srliw(_) | Sraiw(_) | Add(_)
+            | Sub(_) | Sll(_) | Slt(_) | Sltu(_) | Xor(_) | Srl(_) | Sra(_) | Or(_) | And(_)
+            | Mul(_) | Mulh(_) | Mulhsu(_) | Mulhu(_) | Div(_) | Divu(_) | Rem(_) | Remu(_)
+            | Addw(_) | Subw(_) | Sllw(_) | Srlw(_) | Sraw(_) | Mulw(_) | Divw(_) | Divuw(_)
+            | Remw(_) | Remuw(_) | Adduw(_) | Sh1add(_) | Sh2add(_) | Sh3add(_) | Sh1adduw(_)
+            | Sh2adduw(_) | Sh3adduw(_) | Slliuw(_) | Andn(_) | Orn(_) | Xnor(_) | Clz(_)
+            | Clzw(_) | Ctz(_) | Ctzw(_) | Cpop(_) | Cpopw(_) | Max(_) | Maxu(_) | Min(_)
+            | Minu(_) | Sextb(_) | Sexth(_) | Zexth(_) | Rol(_) | Rolw(_) | Ror(_) | Rorw(_)
+            | Rori(_) | Roriw(_) | Orcb(_) | Rev8(_) | Clmul(_) | Clmulh(_) | Clmulr(_)
+            | Bclr(_) | Bclri(_) | Bext(_) | Bexti(_) | Binv(_) | Binvi(_) | Bset(_) | Bseti(_) => {
+                InstructionClass::Alu
+            }
+        }
+    }
+
+    /// The memory accessed by loads, stores and atomic instructions.
+    pub fn memory_access(&self) -> Option<MemAccess> {
+        use Instruction::*;
+
+        let access = |width, signed, base, offset| {
+            Some(MemAccess {
+                width,
+                signed,
+                base,
+                offset,
+            })
+        };
+
+        match self {
+            Lb(i) => access(1, true, i.rs1(), i.imm()),
+            Lh(i) => access(2, true, i.rs1(), i.imm()),
+            Lw(i) => access(4, true, i.rs1(), i.imm()),
+            Ld(i) => access(8, true, i.rs1(), i.imm()),
+            Lbu(i) => access(1, false, i.rs1(), i.imm()),
+            Lhu(i) => access(2, false, i.rs1(), i.imm()),
+            Lwu(i) => access(4, false, i.rs1(), i.imm()),
+            Flw(i) => access(4, false, i.rs1(), i.imm()),
+            Fld(i) => access(8, false, i.rs1(), i.imm()),
+            Sb(s) => access(1, false, s.rs1(), s.imm()),
+            Sh(s) => access(2, false, s.rs1(), s.imm()),
+            Sw(s) | Fsw(s) => access(4, false, s.rs1(), s.imm()),
+            Sd(s) | Fsd(s) => access(8, false, s.rs1(), s.imm()),
+            Scw(r) => access(4, false, r.rs1(), 0),
+            Scd(r) => access(8, false, r.rs1(), 0),
+            Lrw(r) | Amoswapw(r) | Amoaddw(r) | Amoxorw(r) | Amoandw(r) | Amoorw(r)
+            | Amominw(r) | Amomaxw(r) | Amominuw(r) | Amomaxuw(r) => access(4, true, r.rs1(), 0),
+            Lrd(r) | Amoswapd(r) | Amoaddd(r) | Amoxord(r) | Amoandd(r) | Amoord(r)
+            | Amomind(r) | Amomaxd(r) | Amominud(r) | Amomaxud(r) => access(8, true, r.rs1(), 0),
+            _ => None,
+        }
+    }
+
+    /// How this instruction, located at `pc`, continues the execution.
+    ///
+    /// Jumps which link to `ra` or `t0` are calls and `jalr` jumping to one of these registers
+    /// without linking is a return, following the hints for return-address prediction in the
+    /// RISC-V specification.
+    pub fn control_flow(&self, pc: u64) -> ControlFlow {
+        use Instruction::*;
+
+        let target = |offset: i32| pc.wrapping_add(offset as i64 as u64);
+        let is_link = |register| matches!(register, Register::Ra | Register::T0);
+
+        match self {
+            Beq(b) | Bne(b) | Blt(b) | Bge(b) | Bltu(b) | Bgeu(b) => ControlFlow::Branch {
+                target: target(b.imm()),
+            },
+            Jal(j) if is_link(j.rd()) => ControlFlow::Call {
+                target: target(j.imm()),
+            },
+            Jal(j) => ControlFlow::Jump {
+                target: target(j.imm()),
+            },
+            Jalr(i) if is_link(i.rd()) => ControlFlow::IndirectCall {
+                base: i.rs1(),
+                offset: i.imm(),
+            },
+            Jalr(i) if i.rd() == Register::Zero && is_link(i.rs1()) => ControlFlow::Return,
+            Jalr(i) => ControlFlow::IndirectJump {
+                base: i.rs1(),
+                offset: i.imm(),
+            },
+            Mret(_) | Sret(_) => ControlFlow::TrapReturn,
+            _ => ControlFlow::FallThrough,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::RoundingMode, Csr, Register::*};
+
+    fn sources(instruction: Instruction) -> Vec<Register> {
+        instruction.sources().collect()
+    }
+
+    #[test]
+    fn registers() {
+        let add = Instruction::new_add(A0, A1, A2);
+        assert_eq!(add.rd(), Some(A0));
+        assert_eq!(sources(add), vec![A1, A2]);
+
+        let sd = Instruction::new_sd(Sp, Ra, 8);
+        assert_eq!(sd.rd(), None);
+        assert_eq!(sources(sd), vec![Sp, Ra]);
+
+        let beq = Instruction::new_beq(A0, Zero, -4);
+        assert_eq!(beq.rd(), None);
+        assert_eq!(sources(beq), vec![A0, Zero]);
+
+        let lui = Instruction::new_lui(Gp, 0x11000);
+        assert_eq!(lui.rd(), Some(Gp));
+        assert_eq!(sources(lui), vec![]);
+
+        let csrrw = Instruction::new_csrrw(A0, Csr::MSCRATCH, A1);
+        assert_eq!(csrrw.rd(), Some(A0));
+        assert_eq!(sources(csrrw), vec![A1]);
+
+        let csrrwi = Instruction::new_csrrwi(A0, Csr::MSCRATCH, 1);
+        assert_eq!(sources(csrrwi), vec![]);
+
+        let lr = Instruction::new_lrd(A0, A1, Zero);
+        assert_eq!(lr.rd(), Some(A0));
+        assert_eq!(sources(lr), vec![A1]);
+
+        assert_eq!(Instruction::new_ecall().rd(), None);
+        assert_eq!(sources(Instruction::new_ecall()), vec![]);
+        assert_eq!(sources(Instruction::new_clz(A0, A1)), vec![A1]);
+        assert_eq!(sources(Instruction::new_zexth(A0, A1)), vec![A1]);
+    }
+
+    #[test]
+    fn floating_point_registers() {
+        use crate::FRegister::*;
+
+        let flw = Instruction::new_flw(Fa0, Sp, 4);
+        assert_eq!(flw.rd(), None);
+        assert_eq!(sources(flw), vec![Sp]);
+
+        let fsd = Instruction::new_fsd(Sp, Fa0, 8);
+        assert_eq!(sources(fsd), vec![Sp]);
+
+        let fadd = Instruction::new_fadds(Fa0, Fa1, Fa2, RoundingMode::Dyn);
+        assert_eq!(fadd.rd(), None);
+        assert_eq!(sources(fadd), vec![]);
+        assert_eq!(fadd.kind(), InstructionClass::FloatingPoint);
+
+        let feq = Instruction::new_feqd(A0, Fa0, Fa1);
+        assert_eq!(feq.rd(), Some(A0));
+        assert_eq!(sources(feq), vec![]);
+
+        let fmv = Instruction::new_fmvdx(Fa0, A1);
+        assert_eq!(fmv.rd(), None);
+        assert_eq!(sources(fmv), vec![A1]);
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(Instruction::new_lui(A0, 0).kind(), InstructionClass::Alu);
+        assert_eq!(
+            Instruction::new_mul(A0, A0, A1).kind(),
+            InstructionClass::Alu
+        );
+        assert_eq!(
+            Instruction::new_ld(A0, Sp, 0).kind(),
+            InstructionClass::Load
+        );
+        assert_eq!(
+            Instruction::new_sb(Sp, A0, 0).kind(),
+            InstructionClass::Store
+        );
+        assert_eq!(
+            Instruction::new_bne(A0, A1, 8).kind(),
+            InstructionClass::Branch
+        );
+        assert_eq!(
+            Instruction::new_jalr(Zero, Ra, 0).kind(),
+            InstructionClass::Jump
+        );
+        assert_eq!(Instruction::new_ecall().kind(), InstructionClass::System);
+        assert_eq!(Instruction::new_mret().kind(), InstructionClass::System);
+        assert_eq!(
+            Instruction::new_amoaddw(A0, A1, A2).kind(),
+            InstructionClass::Atomic
+        );
+        assert_eq!(Instruction::new_fence_i().kind(), InstructionClass::Fence);
+    }
+
+    #[test]
+    fn memory_accesses() {
+        assert_eq!(
+            Instruction::new_lbu(A0, Sp, -1).memory_access(),
+            Some(MemAccess {
+                width: 1,
+                signed: false,
+                base: Sp,
+                offset: -1,
+            })
+        );
+        assert_eq!(
+            Instruction::new_lw(A0, A1, 4).memory_access(),
+            Some(MemAccess {
+                width: 4,
+                signed: true,
+                base: A1,
+                offset: 4,
+            })
+        );
+        assert_eq!(
+            Instruction::new_sd(Sp, Ra, 24).memory_access(),
+            Some(MemAccess {
+                width: 8,
+                signed: false,
+                base: Sp,
+                offset: 24,
+            })
+        );
+        assert_eq!(
+            Instruction::new_amoswapd(A0, A1, A2).memory_access(),
+            Some(MemAccess {
+                width: 8,
+                signed: true,
+                base: A1,
+                offset: 0,
+            })
+        );
+        assert_eq!(Instruction::new_add(A0, A1, A2).memory_access(), None);
+    }
+
+    #[test]
+    fn control_flow() {
+        let pc = 0x10000;
+
+        assert_eq!(
+            Instruction::new_add(A0, A1, A2).control_flow(pc),
+            ControlFlow::FallThrough
+        );
+        assert_eq!(
+            Instruction::new_beq(A0, Zero, -8).control_flow(pc),
+            ControlFlow::Branch { target: 0xfff8 }
+        );
+        assert_eq!(
+            Instruction::new_jal(Zero, 16).control_flow(pc),
+            ControlFlow::Jump { target: 0x10010 }
+        );
+        assert_eq!(
+            Instruction::new_jal(Ra, 16).control_flow(pc),
+            ControlFlow::Call { target: 0x10010 }
+        );
+        assert_eq!(
+            Instruction::new_jalr(Ra, A0, 8).control_flow(pc),
+            ControlFlow::IndirectCall {
+                base: A0,
+                offset: 8
+            }
+        );
+        assert_eq!(
+            Instruction::new_jalr(Zero, Ra, 0).control_flow(pc),
+            ControlFlow::Return
+        );
+        assert_eq!(
+            Instruction::new_jalr(Zero, A0, 0).control_flow(pc),
+            ControlFlow::IndirectJump {
+                base: A0,
+                offset: 0
+            }
+        );
+        assert_eq!(
+            Instruction::new_mret().control_flow(pc),
+            ControlFlow::TrapReturn
+        );
+        assert_eq!(
+            Instruction::new_ecall().control_flow(pc),
+            ControlFlow::FallThrough
+        );
+
+        assert_eq!(ControlFlow::Branch { target: 8 }.target(), Some(8));
+        assert!(ControlFlow::Branch { target: 8 }.falls_through());
+        assert!(!ControlFlow::Jump { target: 8 }.falls_through());
+        assert!(!ControlFlow::Return.falls_through());
+    }
+}
@@ -0,0 +1,1629 @@
+//! # Opcodes
+//!
+//! An [`Opcode`] is the operation of an [`Instruction`] without its operands, which makes it
+//! suitable for grouping, counting and table lookups by mnemonic. Together with [`Operands`] it
+//! can be turned back into an instruction:
+//!
+//! ```
+//! use riscu::{opcode::{Format, Opcode, Operands}, Instruction, Register};
+//!
+//! let opcode: Opcode = "addi".parse().unwrap();
+//! let operands = Operands {
+//!     rd: 10,
+//!     rs1: 2,
+//!     imm: 8,
+//!     ..Operands::default()
+//! };
+//!
+//! let instruction = Instruction::from_parts(opcode, operands).unwrap();
+//!
+//! assert_eq!(instruction, Instruction::new_addi(Register::A0, Register::Sp, 8));
+//! assert_eq!(instruction.opcode().format(), Format::I);
+//! assert_eq!(instruction.operands(), operands);
+//! ```
+
+use crate::{
+    decode,
+    types::{BType, IType, JType, RoundingMode, SType, UType},
+    Csr, FRegister, Instruction, InstructionClass, Register,
+};
+use core::{convert::TryFrom, fmt, str::FromStr};
+use thiserror::Error;
+
+/// The operation of an [`Instruction`], named like the corresponding variant.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialOrd, PartialEq)]
+pub enum Opcode {
+    // LUI
+    Lui,
+
+    // AUIPC
+    Auipc,
+
+    // Jal
+    Jal,
+
+    // Jalr
+    Jalr,
+
+    // Branch
+    Beq,
+    Bne,
+    Blt,
+    Bge,
+    Bltu,
+    Bgeu,
+
+    // Load
+    Lb,
+    Lh,
+    Lw,
+    Ld,
+    Lbu,
+    Lhu,
+    Lwu,
+
+    // Store
+    Sb,
+    Sh,
+    Sw,
+    Sd,
+
+    // Fence
+    Fence,
+
+    // OP-imm
+    Addi,
+    Slti,
+    Sltiu,
+    Xori,
+    Ori,
+    Andi,
+    Slli,
+    Srli,
+    Srai,
+
+    // OP-imm32
+    Addiw,
+    Slliw,
+    Srliw,
+    Sraiw,
+
+    // OP
+    Add,
+    Sub,
+    Sll,
+    Slt,
+    Sltu,
+    Xor,
+    Srl,
+    Sra,
+    Or,
+    And,
+    Mul,
+    Mulh,
+    Mulhsu,
+    Mulhu,
+    Div,
+    Divu,
+    Rem,
+    Remu,
+
+    // OP32
+    Addw,
+    Subw,
+    Sllw,
+    Srlw,
+    Sraw,
+    Mulw,
+    Divw,
+    Divuw,
+    Remw,
+    Remuw,
+
+    // System
+    Ecall,
+    Ebreak,
+    Sret,
+    Mret,
+    Wfi,
+    SfenceVma,
+
+    // Zifencei
+    FenceI,
+
+    // Zicsr
+    Csrrw,
+    Csrrs,
+    Csrrc,
+    Csrrwi,
+    Csrrsi,
+    Csrrci,
+
+    // Amo
+    Lrw,
+    Scw,
+    Amoswapw,
+    Amoaddw,
+    Amoxorw,
+    Amoandw,
+    Amoorw,
+    Amominw,
+    Amomaxw,
+    Amominuw,
+    Amomaxuw,
+    Lrd,
+    Scd,
+    Amoswapd,
+    Amoaddd,
+    Amoxord,
+    Amoandd,
+    Amoord,
+    Amomind,
+    Amomaxd,
+    Amominud,
+    Amomaxud,
+
+    // F extension
+    Flw,
+    Fsw,
+    Fmadds,
+    Fmsubs,
+    Fnmsubs,
+    Fnmadds,
+    Fadds,
+    Fsubs,
+    Fmuls,
+    Fdivs,
+    Fsqrts,
+    Fsgnjs,
+    Fsgnjns,
+    Fsgnjxs,
+    Fmins,
+    Fmaxs,
+    Fcvtws,
+    Fcvtwus,
+    Fcvtls,
+    Fcvtlus,
+    Fcvtsw,
+    Fcvtswu,
+    Fcvtsl,
+    Fcvtslu,
+    Feqs,
+    Flts,
+    Fles,
+    Fclasss,
+    Fmvxw,
+    Fmvwx,
+
+    // D extension
+    Fld,
+    Fsd,
+    Fmaddd,
+    Fmsubd,
+    Fnmsubd,
+    Fnmaddd,
+    Faddd,
+    Fsubd,
+    Fmuld,
+    Fdivd,
+    Fsqrtd,
+    Fsgnjd,
+    Fsgnjnd,
+    Fsgnjxd,
+    Fmind,
+    Fmaxd,
+    Fcvtwd,
+    Fcvtwud,
+    Fcvtld,
+    Fcvtlud,
+    Fcvtdw,
+    Fcvtdwu,
+    Fcvtdl,
+    Fcvtdlu,
+    Feqd,
+    Fltd,
+    Fled,
+    Fclassd,
+    Fmvxd,
+    Fmvdx,
+    Fcvtsd,
+    Fcvtds,
+
+    // Zba extension
+    Adduw,
+    Sh1add,
+    Sh2add,
+    Sh3add,
+    Sh1adduw,
+    Sh2adduw,
+    Sh3adduw,
+    Slliuw,
+
+    // Zbb extension
+    Andn,
+    Orn,
+    Xnor,
+    Clz,
+    Clzw,
+    Ctz,
+    Ctzw,
+    Cpop,
+    Cpopw,
+    Max,
+    Maxu,
+    Min,
+    Minu,
+    Sextb,
+    Sexth,
+    Zexth,
+    Rol,
+    Rolw,
+    Ror,
+    Rorw,
+    Rori,
+    Roriw,
+    Orcb,
+    Rev8,
+
+    // Zbc extension
+    Clmul,
+    Clmulh,
+    Clmulr,
+
+    // Zbs extension
+    Bclr,
+    Bclri,
+    Bext,
+    Bexti,
+    Binv,
+    Binvi,
+    Bset,
+    Bseti,
+}
+impl Opcode {
+    /// All opcodes in the order of their declaration.
+    pub const ALL: [Opcode; 203] = [
+        Opcode::Lui,
+        Opcode::Auipc,
+        Opcode::Jal,
+        Opcode::Jalr,
+        Opcode::Beq,
+        Opcode::Bne,
+        Opcode::Blt,
+        Opcode::Bge,
+        Opcode::Bltu,
+        Opcode::Bgeu,
+        Opcode::Lb,
+        Opcode::Lh,
+        Opcode::Lw,
+        Opcode::Ld,
+        Opcode::Lbu,
+        Opcode::Lhu,
+        Opcode::Lwu,
+        Opcode::Sb,
+        Opcode::Sh,
+        Opcode::Sw,
+        Opcode::Sd,
+        Opcode::Fence,
+        Opcode::Addi,
+        Opcode::Slti,
+        Opcode::Sltiu,
+        Opcode::Xori,
+        Opcode::Ori,
+        Opcode::Andi,
+        Opcode::Slli,
+        Opcode::Srli,
+        Opcode::Srai,
+        Opcode::Addiw,
+        Opcode::Slliw,
+        Opcode::Srliw,
+        Opcode::Sraiw,
+        Opcode::Add,
+        Opcode::Sub,
+        Opcode::Sll,
+        Opcode::Slt,
+        Opcode::Sltu,
+        Opcode::Xor,
+        Opcode::Srl,
+        Opcode::Sra,
+        Opcode::Or,
+        Opcode::And,
+        Opcode::Mul,
+        Opcode::Mulh,
+        Opcode::Mulhsu,
+        Opcode::Mulhu,
+        Opcode::Div,
+        Opcode::Divu,
+        Opcode::Rem,
+        Opcode::Remu,
+        Opcode::Addw,
+        Opcode::Subw,
+        Opcode::Sllw,
+        Opcode::Srlw,
+        Opcode::Sraw,
+        Opcode::Mulw,
+        Opcode::Divw,
+        Opcode::Divuw,
+        Opcode::Remw,
+        Opcode::Remuw,
+        Opcode::Ecall,
+        Opcode::Ebreak,
+        Opcode::Sret,
+        Opcode::Mret,
+        Opcode::Wfi,
+        Opcode::SfenceVma,
+        Opcode::FenceI,
+        Opcode::Csrrw,
+        Opcode::Csrrs,
+        Opcode::Csrrc,
+        Opcode::Csrrwi,
+        Opcode::Csrrsi,
+        Opcode::Csrrci,
+        Opcode::Lrw,
+        Opcode::Scw,
+        Opcode::Amoswapw,
+        Opcode::Amoaddw,
+        Opcode::Amoxorw,
+        Opcode::Amoandw,
+        Opcode::Amoorw,
+        Opcode::Amominw,
+        Opcode::Amomaxw,
+        Opcode::Amominuw,
+        Opcode::Amomaxuw,
+        Opcode::Lrd,
+        Opcode::Scd,
+        Opcode::Amoswapd,
+        Opcode::Amoaddd,
+        Opcode::Amoxord,
+        Opcode::Amoandd,
+        Opcode::Amoord,
+        Opcode::Amomind,
+        Opcode::Amomaxd,
+        Opcode::Amominud,
+        Opcode::Amomaxud,
+        Opcode::Flw,
+        Opcode::Fsw,
+        Opcode::Fmadds,
+        Opcode::Fmsubs,
+        Opcode::Fnmsubs,
+        Opcode::Fnmadds,
+        Opcode::Fadds,
+        Opcode::Fsubs,
+        Opcode::Fmuls,
+        Opcode::Fdivs,
+        Opcode::Fsqrts,
+        Opcode::Fsgnjs,
+        Opcode::Fsgnjns,
+        Opcode::Fsgnjxs,
+        Opcode::Fmins,
+        Opcode::Fmaxs,
+        Opcode::Fcvtws,
+        Opcode::Fcvtwus,
+        Opcode::Fcvtls,
+        Opcode::Fcvtlus,
+        Opcode::Fcvtsw,
+        Opcode::Fcvtswu,
+        Opcode::Fcvtsl,
+        Opcode::Fcvtslu,
+        Opcode::Feqs,
+        Opcode::Flts,
+        Opcode::Fles,
+        Opcode::Fclasss,
+        Opcode::Fmvxw,
+        Opcode::Fmvwx,
+        Opcode::Fld,
+        Opcode::Fsd,
+        Opcode::Fmaddd,
+        Opcode::Fmsubd,
+        Opcode::Fnmsubd,
+        Opcode::Fnmaddd,
+        Opcode::Faddd,
+        Opcode::Fsubd,
+        Opcode::Fmuld,
+        Opcode::Fdivd,
+        Opcode::Fsqrtd,
+        Opcode::Fsgnjd,
+        Opcode::Fsgnjnd,
+        Opcode::Fsgnjxd,
+        Opcode::Fmind,
+        Opcode::Fmaxd,
+        Opcode::Fcvtwd,
+        Opcode::Fcvtwud,
+        Opcode::Fcvtld,
+        Opcode::Fcvtlud,
+        Opcode::Fcvtdw,
+        Opcode::Fcvtdwu,
+        Opcode::Fcvtdl,
+        Opcode::Fcvtdlu,
+        Opcode::Feqd,
+        Opcode::Fltd,
+        Opcode::Fled,
+        Opcode::Fclassd,
+        Opcode::Fmvxd,
+        Opcode::Fmvdx,
+        Opcode::Fcvtsd,
+        Opcode::Fcvtds,
+        Opcode::Adduw,
+        Opcode::Sh1add,
+        Opcode::Sh2add,
+        Opcode::Sh3add,
+        Opcode::Sh1adduw,
+        Opcode::Sh2adduw,
+        Opcode::Sh3adduw,
+        Opcode::Slliuw,
+        Opcode::Andn,
+        Opcode::Orn,
+        Opcode::Xnor,
+        Opcode::Clz,
+        Opcode::Clzw,
+        Opcode::Ctz,
+        Opcode::Ctzw,
+        Opcode::Cpop,
+        Opcode::Cpopw,
+        Opcode::Max,
+        Opcode::Maxu,
+        Opcode::Min,
+        Opcode::Minu,
+        Opcode::Sextb,
+        Opcode::Sexth,
+        Opcode::Zexth,
+        Opcode::Rol,
+        Opcode::Rolw,
+        Opcode::Ror,
+        Opcode::Rorw,
+        Opcode::Rori,
+        Opcode::Roriw,
+        Opcode::Orcb,
+        Opcode::Rev8,
+        Opcode::Clmul,
+        Opcode::Clmulh,
+        Opcode::Clmulr,
+        Opcode::Bclr,
+        Opcode::Bclri,
+        Opcode::Bext,
+        Opcode::Bexti,
+        Opcode::Binv,
+        Opcode::Binvi,
+        Opcode::Bset,
+        Opcode::Bseti,
+    ];
+
+    /// The mnemonic in GNU assembler syntax, e.g. `addi` or `fadd.s`.
+    pub fn mnemonic(&self) -> &'static str {
+        use Opcode::*;
+
+        match self {
+            Lui => "lui",
+            Auipc => "auipc",
+            Jal => "jal",
+            Jalr => "jalr",
+            Beq => "beq",
+            Bne => "bne",
+            Blt => "blt",
+            Bge => "bge",
+            Bltu => "bltu",
+            Bgeu => "bgeu",
+            Lb => "lb",
+            Lh => "lh",
+            Lw => "lw",
+            Ld => "ld",
+            Lbu => "lbu",
+            Lhu => "lhu",
+            Lwu => "lwu",
+            Sb => "sb",
+            Sh => "sh",
+            Sw => "sw",
+            Sd => "sd",
+            Fence => "fence",
+            Addi => "addi",
+            Slti => "slti",
+            Sltiu => "sltiu",
+            Xori => "xori",
+            Ori => "ori",
+            Andi => "andi",
+            Slli => "slli",
+            Srli => "srli",
+            Srai => "srai",
+            Addiw => "addiw",
+            Slliw => "slliw",
+            Srliw => "srliw",
+            Sraiw => "sraiw",
+            Add => "add",
+            Sub => "sub",
+            Sll => "sll",
+            Slt => "slt",
+            Sltu => "sltu",
+            Xor => "xor",
+            Srl => "srl",
+            Sra => "sra",
+            Or => "or",
+            And => "and",
+            Mul => "mul",
+            Mulh => "mulh",
+            Mulhsu => "mulhsu",
+            Mulhu => "mulhu",
+            Div => "div",
+            Divu => "divu",
+            Rem => "rem",
+            Remu => "remu",
+            Addw => "addw",
+            Subw => "subw",
+            Sllw => "sllw",
+            Srlw => "srlw",
+            Sraw => "sraw",
+            Mulw => "mulw",
+            Divw => "divw",
+            Divuw => "divuw",
+            Remw => "remw",
+            Remuw => "remuw",
+            Ecall => "ecall",
+            Ebreak => "ebreak",
+            Sret => "sret",
+            Mret => "mret",
+            Wfi => "wfi",
+            SfenceVma => "sfence.vma",
+            FenceI => "fence.i",
+            Csrrw => "csrrw",
+            Csrrs => "csrrs",
+            Csrrc => "csrrc",
+            Csrrwi => "csrrwi",
+            Csrrsi => "csrrsi",
+            Csrrci => "csrrci",
+            Lrw => "lr.w",
+            Scw => "sc.w",
+            Amoswapw => "amoswap.w",
+            Amoaddw => "amoadd.w",
+            Amoxorw => "amoxor.w",
+            Amoandw => "amoand.w",
+            Amoorw => "amoor.w",
+            Amominw => "amomin.w",
+            Amomaxw => "amomax.w",
+            Amominuw => "amominu.w",
+            Amomaxuw => "amomaxu.w",
+            Lrd => "lr.d",
+            Scd => "sc.d",
+            Amoswapd => "amoswap.d",
+            Amoaddd => "amoadd.d",
+            Amoxord => "amoxor.d",
+            Amoandd => "amoand.d",
+            Amoord => "amoor.d",
+            Amomind => "amomin.d",
+            Amomaxd => "amomax.d",
+            Amominud => "amominu.d",
+            Amomaxud => "amomaxu.d",
+            Flw => "flw",
+            Fsw => "fsw",
+            Fmadds => "fmadd.s",
+            Fmsubs => "fmsub.s",
+            Fnmsubs => "fnmsub.s",
+            Fnmadds => "fnmadd.s",
+            Fadds => "fadd.s",
+            Fsubs => "fsub.s",
+            Fmuls => "fmul.s",
+            Fdivs => "fdiv.s",
+            Fsqrts => "fsqrt.s",
+            Fsgnjs => "fsgnj.s",
+            Fsgnjns => "fsgnjn.s",
+            Fsgnjxs => "fsgnjx.s",
+            Fmins => "fmin.s",
+            Fmaxs => "fmax.s",
+            Fcvtws => "fcvt.w.s",
+            Fcvtwus => "fcvt.wu.s",
+            Fcvtls => "fcvt.l.s",
+            Fcvtlus => "fcvt.lu.s",
+            Fcvtsw => "fcvt.s.w",
+            Fcvtswu => "fcvt.s.wu",
+            Fcvtsl => "fcvt.s.l",
+            Fcvtslu => "fcvt.s.lu",
+            Feqs => "feq.s",
+            Flts => "flt.s",
+            Fles => "fle.s",
+            Fclasss => "fclass.s",
+            Fmvxw => "fmv.x.w",
+            Fmvwx => "fmv.w.x",
+            Fld => "fld",
+            Fsd => "fsd",
+            Fmaddd => "fmadd.d",
+            Fmsubd => "fmsub.d",
+            Fnmsubd => "fnmsub.d",
+            Fnmaddd => "fnmadd.d",
+            Faddd => "fadd.d",
+            Fsubd => "fsub.d",
+            Fmuld => "fmul.d",
+            Fdivd => "fdiv.d",
+            Fsqrtd => "fsqrt.d",
+            Fsgnjd => "fsgnj.d",
+            Fsgnjnd => "fsgnjn.d",
+            Fsgnjxd => "fsgnjx.d",
+            Fmind => "fmin.d",
+            Fmaxd => "fmax.d",
+            Fcvtwd => "fcvt.w.d",
+            Fcvtwud => "fcvt.wu.d",
+            Fcvtld => "fcvt.l.d",
+            Fcvtlud => "fcvt.lu.d",
+            Fcvtdw => "fcvt.d.w",
+            Fcvtdwu => "fcvt.d.wu",
+            Fcvtdl => "fcvt.d.l",
+            Fcvtdlu => "fcvt.d.lu",
+            Feqd => "feq.d",
+            Fltd => "flt.d",
+            Fled => "fle.d",
+            Fclassd => "fclass.d",
+            Fmvxd => "fmv.x.d",
+            Fmvdx => "fmv.d.x",
+            Fcvtsd => "fcvt.s.d",
+            Fcvtds => "fcvt.d.s",
+            Adduw => "add.uw",
+            Sh1add => "sh1add",
+            Sh2add => "sh2add",
+            Sh3add => "sh3add",
+            Sh1adduw => "sh1add.uw",
+            Sh2adduw => "sh2add.uw",
+            Sh3adduw => "sh3add.uw",
+            Slliuw => "slli.uw",
+            Andn => "andn",
+            Orn => "orn",
+            Xnor => "xnor",
+            Clz => "clz",
+            Clzw => "clzw",
+            Ctz => "ctz",
+            Ctzw => "ctzw",
+            Cpop => "cpop",
+            Cpopw => "cpopw",
+            Max => "max",
+            Maxu => "maxu",
+            Min => "min",
+            Minu => "minu",
+            Sextb => "sext.b",
+            Sexth => "sext.h",
+            Zexth => "zext.h",
+            Rol => "rol",
+            Rolw => "rolw",
+            Ror => "ror",
+            Rorw => "rorw",
+            Rori => "rori",
+            Roriw => "roriw",
+            Orcb => "orc.b",
+            Rev8 => "rev8",
+            Clmul => "clmul",
+            Clmulh => "clmulh",
+            Clmulr => "clmulr",
+            Bclr => "bclr",
+            Bclri => "bclri",
+            Bext => "bext",
+            Bexti => "bexti",
+            Binv => "binv",
+            Binvi => "binvi",
+            Bset => "bset",
+            Bseti => "bseti",
+        }
+    }
+
+    /// The encoding format of instructions with this opcode.
+    pub fn format(&self) -> Format {
+        use Opcode::*;
+
+        match self {
+            Lui | Auipc => Format::U,
+            Jal => Format::J,
+            Jalr | Lb | Lh | Lw | Ld | Lbu | Lhu | Lwu | Fence | Addi | Slti | Sltiu | Xori
+            | Ori | Andi | Slli | Srli | Srai | Addiw | Slliw | Srliw | Sraiw | Ecall | Ebreak
+            | Sret | Mret | Wfi | FenceI | Csrrw | Csrrs | Csrrc | Csrrwi | Csrrsi | Csrrci
+            | Flw | Fld | Slliuw | Clz | Clzw | Ctz | Ctzw | Cpop | Cpopw | Sextb | Sexth
+            | Rori | Roriw | Orcb | Rev8 | Bclri | Bexti | Binvi | Bseti => Format::I,
+            Beq | Bne | Blt | Bge | Bltu | Bgeu => Format::B,
+            Sb | Sh | Sw | Sd | Fsw | Fsd => Format::S,
+            Add | Sub | Sll | Slt | Sltu | Xor | Srl | Sra | Or | And | Mul | Mulh | Mulhsu
+            | Mulhu | Div | Divu | Rem | Remu | Addw | Subw | Sllw | Srlw | Sraw | Mulw | Divw
+            | Divuw | Remw | Remuw | SfenceVma | Lrw | Scw | Amoswapw | Amoaddw | Amoxorw
+            | Amoandw | Amoorw | Amominw | Amomaxw | Amominuw | Amomaxuw | Lrd | Scd | Amoswapd
+            | Amoaddd | Amoxord | Amoandd | Amoord | Amomind | Amomaxd | Amominud | Amomaxud
+            | Fadds | Fsubs | Fmuls | Fdivs | Fsqrts | Fsgnjs | Fsgnjns | Fsgnjxs | Fmins
+            | Fmaxs | Fcvtws | Fcvtwus | Fcvtls | Fcvtlus | Fcvtsw | Fcvtswu | Fcvtsl | Fcvtslu
+            | Feqs | Flts | Fles | Fclasss | Fmvxw | Fmvwx | Faddd | Fsubd | Fmuld | Fdivd
+            | Fsqrtd | Fsgnjd | Fsgnjnd | Fsgnjxd | Fmind | Fmaxd | Fcvtwd | Fcvtwud | Fcvtld
+            | Fcvtlud | Fcvtdw | Fcvtdwu | Fcvtdl | Fcvtdlu | Feqd | Fltd | Fled | Fclassd
+            | Fmvxd | Fmvdx | Fcvtsd | Fcvtds | Adduw | Sh1add | Sh2add | Sh3add | Sh1adduw
+            | Sh2adduw | Sh3adduw | Andn | Orn | Xnor | Max | Maxu | Min | Minu | Zexth | Rol
+            | Rolw | Ror | Rorw | Clmul | Clmulh | Clmulr | Bclr | Bext | Binv | Bset => Format::R,
+            Fmadds | Fmsubs | Fnmsubs | Fnmadds | Fmaddd | Fmsubd | Fnmsubd | Fnmaddd => Format::R4,
+        }
+    }
+
+    pub(crate) fn constructor(self) -> Constructor {
+        use Constructor::*;
+
+        match self {
+            Opcode::Lui => Upper(Instruction::new_lui, Range::Unsigned(20)),
+            Opcode::Auipc => Upper(Instruction::new_auipc, Range::Unsigned(20)),
+            Opcode::Jal => Upper(Instruction::new_jal, Range::Offset(21)),
+            Opcode::Jalr => Immediate(Instruction::new_jalr, Range::Signed(12)),
+            Opcode::Beq => SourcesImmediate(Instruction::new_beq, Range::Offset(13)),
+            Opcode::Bne => SourcesImmediate(Instruction::new_bne, Range::Offset(13)),
+            Opcode::Blt => SourcesImmediate(Instruction::new_blt, Range::Offset(13)),
+            Opcode::Bge => SourcesImmediate(Instruction::new_bge, Range::Offset(13)),
+            Opcode::Bltu => SourcesImmediate(Instruction::new_bltu, Range::Offset(13)),
+            Opcode::Bgeu => SourcesImmediate(Instruction::new_bgeu, Range::Offset(13)),
+            Opcode::Lb => Immediate(Instruction::new_lb, Range::Signed(12)),
+            Opcode::Lh => Immediate(Instruction::new_lh, Range::Signed(12)),
+            Opcode::Lw => Immediate(Instruction::new_lw, Range::Signed(12)),
+            Opcode::Ld => Immediate(Instruction::new_ld, Range::Signed(12)),
+            Opcode::Lbu => Immediate(Instruction::new_lbu, Range::Signed(12)),
+            Opcode::Lhu => Immediate(Instruction::new_lhu, Range::Signed(12)),
+            Opcode::Lwu => Immediate(Instruction::new_lwu, Range::Signed(12)),
+            Opcode::Sb => SourcesImmediate(Instruction::new_sb, Range::Signed(12)),
+            Opcode::Sh => SourcesImmediate(Instruction::new_sh, Range::Signed(12)),
+            Opcode::Sw => SourcesImmediate(Instruction::new_sw, Range::Signed(12)),
+            Opcode::Sd => SourcesImmediate(Instruction::new_sd, Range::Signed(12)),
+            Opcode::Fence => Immediate(Instruction::new_fence, Range::Signed(12)),
+            Opcode::Addi => Immediate(Instruction::new_addi, Range::Signed(12)),
+            Opcode::Slti => Immediate(Instruction::new_slti, Range::Signed(12)),
+            Opcode::Sltiu => Immediate(Instruction::new_sltiu, Range::Signed(12)),
+            Opcode::Xori => Immediate(Instruction::new_xori, Range::Signed(12)),
+            Opcode::Ori => Immediate(Instruction::new_ori, Range::Signed(12)),
+            Opcode::Andi => Immediate(Instruction::new_andi, Range::Signed(12)),
+            Opcode::Slli => Immediate(Instruction::new_slli, Range::Unsigned(6)),
+            Opcode::Srli => Immediate(Instruction::new_srli, Range::Unsigned(6)),
+            Opcode::Srai => Immediate(Instruction::new_srai, Range::Unsigned(6)),
+            Opcode::Addiw => Immediate(Instruction::new_addiw, Range::Signed(12)),
+            Opcode::Slliw => Immediate(Instruction::new_slliw, Range::Unsigned(5)),
+            Opcode::Srliw => Immediate(Instruction::new_srliw, Range::Unsigned(5)),
+            Opcode::Sraiw => Immediate(Instruction::new_sraiw, Range::Unsigned(5)),
+            Opcode::Add => Register(Instruction::new_add),
+            Opcode::Sub => Register(Instruction::new_sub),
+            Opcode::Sll => Register(Instruction::new_sll),
+            Opcode::Slt => Register(Instruction::new_slt),
+            Opcode::Sltu => Register(Instruction::new_sltu),
+            Opcode::Xor => Register(Instruction::new_xor),
+            Opcode::Srl => Register(Instruction::new_srl),
+            Opcode::Sra => Register(Instruction::new_sra),
+            Opcode::Or => Register(Instruction::new_or),
+            Opcode::And => Register(Instruction::new_and),
+            Opcode::Mul => Register(Instruction::new_mul),
+            Opcode::Mulh => Register(Instruction::new_mulh),
+            Opcode::Mulhsu => Register(Instruction::new_mulhsu),
+            Opcode::Mulhu => Register(Instruction::new_mulhu),
+            Opcode::Div => Register(Instruction::new_div),
+            Opcode::Divu => Register(Instruction::new_divu),
+            Opcode::Rem => Register(Instruction::new_rem),
+            Opcode::Remu => Register(Instruction::new_remu),
+            Opcode::Addw => Register(Instruction::new_addw),
+            Opcode::Subw => Register(Instruction::new_subw),
+            Opcode::Sllw => Register(Instruction::new_sllw),
+            Opcode::Srlw => Register(Instruction::new_srlw),
+            Opcode::Sraw => Register(Instruction::new_sraw),
+            Opcode::Mulw => Register(Instruction::new_mulw),
+            Opcode::Divw => Register(Instruction::new_divw),
+            Opcode::Divuw => Register(Instruction::new_divuw),
+            Opcode::Remw => Register(Instruction::new_remw),
+            Opcode::Remuw => Register(Instruction::new_remuw),
+            Opcode::Ecall => Nullary(Instruction::new_ecall),
+            Opcode::Ebreak => Nullary(Instruction::new_ebreak),
+            Opcode::Sret => Nullary(Instruction::new_sret),
+            Opcode::Mret => Nullary(Instruction::new_mret),
+            Opcode::Wfi => Nullary(Instruction::new_wfi),
+            Opcode::SfenceVma => Sources(Instruction::new_sfence_vma),
+            Opcode::FenceI => Nullary(Instruction::new_fence_i),
+            Opcode::Csrrw => CsrRegister(Instruction::new_csrrw),
+            Opcode::Csrrs => CsrRegister(Instruction::new_csrrs),
+            Opcode::Csrrc => CsrRegister(Instruction::new_csrrc),
+            Opcode::Csrrwi => CsrImmediate(Instruction::new_csrrwi),
+            Opcode::Csrrsi => CsrImmediate(Instruction::new_csrrsi),
+            Opcode::Csrrci => CsrImmediate(Instruction::new_csrrci),
+            Opcode::Lrw => Register(Instruction::new_lrw),
+            Opcode::Scw => Register(Instruction::new_scw),
+            Opcode::Amoswapw => Register(Instruction::new_amoswapw),
+            Opcode::Amoaddw => Register(Instruction::new_amoaddw),
+            Opcode::Amoxorw => Register(Instruction::new_amoxorw),
+            Opcode::Amoandw => Register(Instruction::new_amoandw),
+            Opcode::Amoorw => Register(Instruction::new_amoorw),
+            Opcode::Amominw => Register(Instruction::new_amominw),
+            Opcode::Amomaxw => Register(Instruction::new_amomaxw),
+            Opcode::Amominuw => Register(Instruction::new_amominuw),
+            Opcode::Amomaxuw => Register(Instruction::new_amomaxuw),
+            Opcode::Lrd => Register(Instruction::new_lrd),
+            Opcode::Scd => Register(Instruction::new_scd),
+            Opcode::Amoswapd => Register(Instruction::new_amoswapd),
+            Opcode::Amoaddd => Register(Instruction::new_amoaddd),
+            Opcode::Amoxord => Register(Instruction::new_amoxord),
+            Opcode::Amoandd => Register(Instruction::new_amoandd),
+            Opcode::Amoord => Register(Instruction::new_amoord),
+            Opcode::Amomind => Register(Instruction::new_amomind),
+            Opcode::Amomaxd => Register(Instruction::new_amomaxd),
+            Opcode::Amominud => Register(Instruction::new_amominud),
+            Opcode::Amomaxud => Register(Instruction::new_amomaxud),
+            Opcode::Flw => FloatLoad(Instruction::new_flw),
+            Opcode::Fsw => FloatStore(Instruction::new_fsw),
+            Opcode::Fmadds => FusedMultiplyAdd(Instruction::new_fmadds),
+            Opcode::Fmsubs => FusedMultiplyAdd(Instruction::new_fmsubs),
+            Opcode::Fnmsubs => FusedMultiplyAdd(Instruction::new_fnmsubs),
+            Opcode::Fnmadds => FusedMultiplyAdd(Instruction::new_fnmadds),
+            Opcode::Fadds => FloatRounded(Instruction::new_fadds),
+            Opcode::Fsubs => FloatRounded(Instruction::new_fsubs),
+            Opcode::Fmuls => FloatRounded(Instruction::new_fmuls),
+            Opcode::Fdivs => FloatRounded(Instruction::new_fdivs),
+            Opcode::Fsqrts => FloatUnaryRounded(Instruction::new_fsqrts),
+            Opcode::Fsgnjs => Float(Instruction::new_fsgnjs),
+            Opcode::Fsgnjns => Float(Instruction::new_fsgnjns),
+            Opcode::Fsgnjxs => Float(Instruction::new_fsgnjxs),
+            Opcode::Fmins => Float(Instruction::new_fmins),
+            Opcode::Fmaxs => Float(Instruction::new_fmaxs),
+            Opcode::Fcvtws => FloatToIntRounded(Instruction::new_fcvtws),
+            Opcode::Fcvtwus => FloatToIntRounded(Instruction::new_fcvtwus),
+            Opcode::Fcvtls => FloatToIntRounded(Instruction::new_fcvtls),
+            Opcode::Fcvtlus => FloatToIntRounded(Instruction::new_fcvtlus),
+            Opcode::Fcvtsw => IntToFloatRounded(Instruction::new_fcvtsw),
+            Opcode::Fcvtswu => IntToFloatRounded(Instruction::new_fcvtswu),
+            Opcode::Fcvtsl => IntToFloatRounded(Instruction::new_fcvtsl),
+            Opcode::Fcvtslu => IntToFloatRounded(Instruction::new_fcvtslu),
+            Opcode::Feqs => FloatCompare(Instruction::new_feqs),
+            Opcode::Flts => FloatCompare(Instruction::new_flts),
+            Opcode::Fles => FloatCompare(Instruction::new_fles),
+            Opcode::Fclasss => FloatToInt(Instruction::new_fclasss),
+            Opcode::Fmvxw => FloatToInt(Instruction::new_fmvxw),
+            Opcode::Fmvwx => IntToFloat(Instruction::new_fmvwx),
+            Opcode::Fld => FloatLoad(Instruction::new_fld),
+            Opcode::Fsd => FloatStore(Instruction::new_fsd),
+            Opcode::Fmaddd => FusedMultiplyAdd(Instruction::new_fmaddd),
+            Opcode::Fmsubd => FusedMultiplyAdd(Instruction::new_fmsubd),
+            Opcode::Fnmsubd => FusedMultiplyAdd(Instruction::new_fnmsubd),
+            Opcode::Fnmaddd => FusedMultiplyAdd(Instruction::new_fnmaddd),
+            Opcode::Faddd => FloatRounded(Instruction::new_faddd),
+            Opcode::Fsubd => FloatRounded(Instruction::new_fsubd),
+            Opcode::Fmuld => FloatRounded(Instruction::new_fmuld),
+            Opcode::Fdivd => FloatRounded(Instruction::new_fdivd),
+            Opcode::Fsqrtd => FloatUnaryRounded(Instruction::new_fsqrtd),
+            Opcode::Fsgnjd => Float(Instruction::new_fsgnjd),
+            Opcode::Fsgnjnd => Float(Instruction::new_fsgnjnd),
+            Opcode::Fsgnjxd => Float(Instruction::new_fsgnjxd),
+            Opcode::Fmind => Float(Instruction::new_fmind),
+            Opcode::Fmaxd => Float(Instruction::new_fmaxd),
+            Opcode::Fcvtwd => FloatToIntRounded(Instruction::new_fcvtwd),
+            Opcode::Fcvtwud => FloatToIntRounded(Instruction::new_fcvtwud),
+            Opcode::Fcvtld => FloatToIntRounded(Instruction::new_fcvtld),
+            Opcode::Fcvtlud => FloatToIntRounded(Instruction::new_fcvtlud),
+            Opcode::Fcvtdw => IntToFloat(Instruction::new_fcvtdw),
+            Opcode::Fcvtdwu => IntToFloat(Instruction::new_fcvtdwu),
+            Opcode::Fcvtdl => IntToFloatRounded(Instruction::new_fcvtdl),
+            Opcode::Fcvtdlu => IntToFloatRounded(Instruction::new_fcvtdlu),
+            Opcode::Feqd => FloatCompare(Instruction::new_feqd),
+            Opcode::Fltd => FloatCompare(Instruction::new_fltd),
+            Opcode::Fled => FloatCompare(Instruction::new_fled),
+            Opcode::Fclassd => FloatToInt(Instruction::new_fclassd),
+            Opcode::Fmvxd => FloatToInt(Instruction::new_fmvxd),
+            Opcode::Fmvdx => IntToFloat(Instruction::new_fmvdx),
+            Opcode::Fcvtsd => FloatUnaryRounded(Instruction::new_fcvtsd),
+            Opcode::Fcvtds => FloatUnary(Instruction::new_fcvtds),
+            Opcode::Adduw => Register(Instruction::new_adduw),
+            Opcode::Sh1add => Register(Instruction::new_sh1add),
+            Opcode::Sh2add => Register(Instruction::new_sh2add),
+            Opcode::Sh3add => Register(Instruction::new_sh3add),
+            Opcode::Sh1adduw => Register(Instruction::new_sh1adduw),
+            Opcode::Sh2adduw => Register(Instruction::new_sh2adduw),
+            Opcode::Sh3adduw => Register(Instruction::new_sh3adduw),
+            Opcode::Slliuw => Immediate(Instruction::new_slliuw, Range::Unsigned(6)),
+            Opcode::Andn => Register(Instruction::new_andn),
+            Opcode::Orn => Register(Instruction::new_orn),
+            Opcode::Xnor => Register(Instruction::new_xnor),
+            Opcode::Clz => Unary(Instruction::new_clz),
+            Opcode::Clzw => Unary(Instruction::new_clzw),
+            Opcode::Ctz => Unary(Instruction::new_ctz),
+            Opcode::Ctzw => Unary(Instruction::new_ctzw),
+            Opcode::Cpop => Unary(Instruction::new_cpop),
+            Opcode::Cpopw => Unary(Instruction::new_cpopw),
+            Opcode::Max => Register(Instruction::new_max),
+            Opcode::Maxu => Register(Instruction::new_maxu),
+            Opcode::Min => Register(Instruction::new_min),
+            Opcode::Minu => Register(Instruction::new_minu),
+            Opcode::Sextb => Unary(Instruction::new_sextb),
+            Opcode::Sexth => Unary(Instruction::new_sexth),
+            Opcode::Zexth => Unary(Instruction::new_zexth),
+            Opcode::Rol => Register(Instruction::new_rol),
+            Opcode::Rolw => Register(Instruction::new_rolw),
+            Opcode::Ror => Register(Instruction::new_ror),
+            Opcode::Rorw => Register(Instruction::new_rorw),
+            Opcode::Rori => Immediate(Instruction::new_rori, Range::Unsigned(6)),
+            Opcode::Roriw => Immediate(Instruction::new_roriw, Range::Unsigned(5)),
+            Opcode::Orcb => Unary(Instruction::new_orcb),
+            Opcode::Rev8 => Unary(Instruction::new_rev8),
+            Opcode::Clmul => Register(Instruction::new_clmul),
+            Opcode::Clmulh => Register(Instruction::new_clmulh),
+            Opcode::Clmulr => Register(Instruction::new_clmulr),
+            Opcode::Bclr => Register(Instruction::new_bclr),
+            Opcode::Bclri => Immediate(Instruction::new_bclri, Range::Unsigned(6)),
+            Opcode::Bext => Register(Instruction::new_bext),
+            Opcode::Bexti => Immediate(Instruction::new_bexti, Range::Unsigned(6)),
+            Opcode::Binv => Register(Instruction::new_binv),
+            Opcode::Binvi => Immediate(Instruction::new_binvi, Range::Unsigned(6)),
+            Opcode::Bset => Register(Instruction::new_bset),
+            Opcode::Bseti => Immediate(Instruction::new_bseti, Range::Unsigned(6)),
+        }
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+#[error("unknown mnemonic \"{0}\"")]
+pub struct UnknownMnemonic(pub String);
+
+impl FromStr for Opcode {
+    type Err = UnknownMnemonic;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Opcode::ALL
+            .iter()
+            .find(|opcode| opcode.mnemonic() == s)
+            .copied()
+            .ok_or_else(|| UnknownMnemonic(s.to_string()))
+    }
+}
+
+/// The instruction formats of the base ISA and the R4 format of the fused multiply-add
+/// instructions.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Format {
+    R,
+    I,
+    S,
+    B,
+    U,
+    J,
+    R4,
+}
+
+/// The operands of an instruction as used by [`Instruction::from_parts`]. Registers are given by
+/// their number, as they name integer or floating-point registers depending on the opcode.
+/// Fields which are not used by an opcode are ignored.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Operands {
+    pub rd: u8,
+    /// The first source register, or the unsigned immediate of `csrrwi`, `csrrsi` and `csrrci`.
+    pub rs1: u8,
+    pub rs2: u8,
+    /// The third source register of the fused multiply-add instructions.
+    pub rs3: u8,
+    /// The offset of loads, stores, branches and jumps, the shift amount of shifts, the upper 20
+    /// bits of `lui` and `auipc`, the CSR number of CSR instructions or the operand of other
+    /// instructions with an immediate.
+    pub imm: i32,
+    /// The rounding mode of floating-point instructions which round.
+    pub rm: RoundingMode,
+    /// The acquire bit of atomic instructions.
+    pub aq: bool,
+    /// The release bit of atomic instructions.
+    pub rl: bool,
+}
+
+impl Default for Operands {
+    fn default() -> Self {
+        Self {
+            rd: 0,
+            rs1: 0,
+            rs2: 0,
+            rs3: 0,
+            imm: 0,
+            rm: RoundingMode::Dyn,
+            aq: false,
+            rl: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Error)]
+pub enum OperandError {
+    #[error("register number {0} is out of range")]
+    InvalidRegister(u8),
+
+    #[error("immediate {imm} is out of range for `{opcode}`")]
+    ImmediateOutOfRange { opcode: Opcode, imm: i32 },
+
+    #[error("`{0}` does not have acquire or release bits")]
+    UnexpectedOrdering(Opcode),
+}
+
+/// The valid values of an immediate operand.
+#[derive(Clone, Copy)]
+pub(crate) enum Range {
+    /// A signed immediate of the given width
+    Signed(u32),
+    /// A signed, even offset of the given width
+    Offset(u32),
+    /// An unsigned immediate of the given width
+    Unsigned(u32),
+}
+
+impl Range {
+    fn contains(self, imm: i32) -> bool {
+        match self {
+            Range::Signed(bits) => -(1 << (bits - 1)) <= imm && imm < 1 << (bits - 1),
+            Range::Offset(bits) => imm & 1 == 0 && Range::Signed(bits).contains(imm),
+            Range::Unsigned(bits) => 0 <= imm && imm < 1 << bits,
+        }
+    }
+
+    /// Extract the immediate from the immediate field of the encoding.
+    fn extract(self, imm: i32) -> i32 {
+        match self {
+            Range::Unsigned(bits) => imm & ((1 << bits) - 1),
+            _ => imm,
+        }
+    }
+}
+
+type X = Register;
+type F = FRegister;
+type Rm = RoundingMode;
+
+/// The operands of an instruction by their kind together with its constructor.
+pub(crate) enum Constructor {
+    /// `rd, rs1, rs2`
+    Register(fn(X, X, X) -> Instruction),
+    /// `rd, rs1, imm`
+    Immediate(fn(X, X, i32) -> Instruction, Range),
+    /// `rd, rs1`
+    Unary(fn(X, X) -> Instruction),
+    /// `rs1, rs2, imm` of stores and branches
+    SourcesImmediate(fn(X, X, i32) -> Instruction, Range),
+    /// `rs1, rs2`
+    Sources(fn(X, X) -> Instruction),
+    /// `rd, imm`
+    Upper(fn(X, i32) -> Instruction, Range),
+    /// no operands
+    Nullary(fn() -> Instruction),
+    /// `rd, csr, rs1`
+    CsrRegister(fn(X, Csr, X) -> Instruction),
+    /// `rd, csr, zimm`
+    CsrImmediate(fn(X, Csr, u32) -> Instruction),
+    /// `frd, imm(rs1)`
+    FloatLoad(fn(F, X, i32) -> Instruction),
+    /// `frs2, imm(rs1)`
+    FloatStore(fn(X, F, i32) -> Instruction),
+    /// `frd, frs1, frs2, frs3, rm`
+    FusedMultiplyAdd(fn(F, F, F, F, Rm) -> Instruction),
+    /// `frd, frs1, frs2, rm`
+    FloatRounded(fn(F, F, F, Rm) -> Instruction),
+    /// `frd, frs1, frs2`
+    Float(fn(F, F, F) -> Instruction),
+    /// `frd, frs1, rm`
+    FloatUnaryRounded(fn(F, F, Rm) -> Instruction),
+    /// `frd, frs1`
+    FloatUnary(fn(F, F) -> Instruction),
+    /// `rd, frs1, rm`
+    FloatToIntRounded(fn(X, F, Rm) -> Instruction),
+    /// `frd, rs1, rm`
+    IntToFloatRounded(fn(F, X, Rm) -> Instruction),
+    /// `rd, frs1, frs2`
+    FloatCompare(fn(X, F, F) -> Instruction),
+    /// `rd, frs1`
+    FloatToInt(fn(X, F) -> Instruction),
+    /// `frd, rs1`
+    IntToFloat(fn(F, X) -> Instruction),
+}
+
+impl Instruction {
+    /// The operation of this instruction.
+    pub fn opcode(&self) -> Opcode {
+        use Instruction::*;
+
+        match self {
+            Lui(_) => Opcode::Lui,
+            Auipc(_) => Opcode::Auipc,
+            Jal(_) => Opcode::Jal,
+            Jalr(_) => Opcode::Jalr,
+            Beq(_) => Opcode::Beq,
+            Bne(_) => Opcode::Bne,
+            Blt(_) => Opcode::Blt,
+            Bge(_) => Opcode::Bge,
+            Bltu(_) => Opcode::Bltu,
+            Bgeu(_) => Opcode::Bgeu,
+            Lb(_) => Opcode::Lb,
+            Lh(_) => Opcode::Lh,
+            Lw(_) => Opcode::Lw,
+            Ld(_) => Opcode::Ld,
+            Lbu(_) => Opcode::Lbu,
+            Lhu(_) => Opcode::Lhu,
+            Lwu(_) => Opcode::Lwu,
+            Sb(_) => Opcode::Sb,
+            Sh(_) => Opcode::Sh,
+            Sw(_) => Opcode::Sw,
+            Sd(_) => Opcode::Sd,
+            Fence(_) => Opcode::Fence,
+            Addi(_) => Opcode::Addi,
+            Slti(_) => Opcode::Slti,
+            Sltiu(_) => Opcode::Sltiu,
+            Xori(_) => Opcode::Xori,
+            Ori(_) => Opcode::Ori,
+            Andi(_) => Opcode::Andi,
+            Slli(_) => Opcode::Slli,
+            Srli(_) => Opcode::Srli,
+            Srai(_) => Opcode::Srai,
+            Addiw(_) => Opcode::Addiw,
+            Slliw(_) => Opcode::Slliw,
+            Srliw(_) => Opcode::Srliw,
+            Sraiw(_) => Opcode::Sraiw,
+            Add(_) => Opcode::Add,
+            Sub(_) => Opcode::Sub,
+            Sll(_) => Opcode::Sll,
+            Slt(_) => Opcode::Slt,
+            Sltu(_) => Opcode::Sltu,
+            Xor(_) => Opcode::Xor,
+            Srl(_) => Opcode::Srl,
+            Sra(_) => Opcode::Sra,
+            Or(_) => Opcode::Or,
+            And(_) => Opcode::And,
+            Mul(_) => Opcode::Mul,
+            Mulh(_) => Opcode::Mulh,
+            Mulhsu(_) => Opcode::Mulhsu,
+            Mulhu(_) => Opcode::Mulhu,
+            Div(_) => Opcode::Div,
+            Divu(_) => Opcode::Divu,
+            Rem(_) => Opcode::Rem,
+            Remu(_) => Opcode::Remu,
+            Addw(_) => Opcode::Addw,
+            Subw(_) => Opcode::Subw,
+            Sllw(_) => Opcode::Sllw,
+            Srlw(_) => Opcode::Srlw,
+            Sraw(_) => Opcode::Sraw,
+            Mulw(_) => Opcode::Mulw,
+            Divw(_) => Opcode::Divw,
+            Divuw(_) => Opcode::Divuw,
+            Remw(_) => Opcode::Remw,
+            Remuw(_) => Opcode::Remuw,
+            Ecall(_) => Opcode::Ecall,
+            Ebreak(_) => Opcode::Ebreak,
+            Sret(_) => Opcode::Sret,
+            Mret(_) => Opcode::Mret,
+            Wfi(_) => Opcode::Wfi,
+            SfenceVma(_) => Opcode::SfenceVma,
+            FenceI(_) => Opcode::FenceI,
+            Csrrw(_) => Opcode::Csrrw,
+            Csrrs(_) => Opcode::Csrrs,
+            Csrrc(_) => Opcode::Csrrc,
+            Csrrwi(_) => Opcode::Csrrwi,
+            Csrrsi(_) => Opcode::Csrrsi,
+            Csrrci(_) => Opcode::Csrrci,
+            Lrw(_) => Opcode::Lrw,
+            Scw(_) => Opcode::Scw,
+            Amoswapw(_) => Opcode::Amoswapw,
+            Amoaddw(_) => Opcode::Amoaddw,
+            Amoxorw(_) => Opcode::Amoxorw,
+            Amoandw(_) => Opcode::Amoandw,
+            Amoorw(_) => Opcode::Amoorw,
+            Amominw(_) => Opcode::Amominw,
+            Amomaxw(_) => Opcode::Amomaxw,
+            Amominuw(_) => Opcode::Amominuw,
+            Amomaxuw(_) => Opcode::Amomaxuw,
+            Lrd(_) => Opcode::Lrd,
+            Scd(_) => Opcode::Scd,
+            Amoswapd(_) => Opcode::Amoswapd,
+            Amoaddd(_) => Opcode::Amoaddd,
+            Amoxord(_) => Opcode::Amoxord,
+            Amoandd(_) => Opcode::Amoandd,
+            Amoord(_) => Opcode::Amoord,
+            Amomind(_) => Opcode::Amomind,
+            Amomaxd(_) => Opcode::Amomaxd,
+            Amominud(_) => Opcode::Amominud,
+            Amomaxud(_) => Opcode::Amomaxud,
+            Flw(_) => Opcode::Flw,
+            Fsw(_) => Opcode::Fsw,
+            Fmadds(_) => Opcode::Fmadds,
+            Fmsubs(_) => Opcode::Fmsubs,
+            Fnmsubs(_) => Opcode::Fnmsubs,
+            Fnmadds(_) => Opcode::Fnmadds,
+            Fadds(_) => Opcode::Fadds,
+            Fsubs(_) => Opcode::Fsubs,
+            Fmuls(_) => Opcode::Fmuls,
+            Fdivs(_) => Opcode::Fdivs,
+            Fsqrts(_) => Opcode::Fsqrts,
+            Fsgnjs(_) => Opcode::Fsgnjs,
+            Fsgnjns(_) => Opcode::Fsgnjns,
+            Fsgnjxs(_) => Opcode::Fsgnjxs,
+            Fmins(_) => Opcode::Fmins,
+            Fmaxs(_) => Opcode::Fmaxs,
+            Fcvtws(_) => Opcode::Fcvtws,
+            Fcvtwus(_) => Opcode::Fcvtwus,
+            Fcvtls(_) => Opcode::Fcvtls,
+            Fcvtlus(_) => Opcode::Fcvtlus,
+            Fcvtsw(_) => Opcode::Fcvtsw,
+            Fcvtswu(_) => Opcode::Fcvtswu,
+            Fcvtsl(_) => Opcode::Fcvtsl,
+            Fcvtslu(_) => Opcode::Fcvtslu,
+            Feqs(_) => Opcode::Feqs,
+            Flts(_) => Opcode::Flts,
+            Fles(_) => Opcode::Fles,
+            Fclasss(_) => Opcode::Fclasss,
+            Fmvxw(_) => Opcode::Fmvxw,
+            Fmvwx(_) => Opcode::Fmvwx,
+            Fld(_) => Opcode::Fld,
+            Fsd(_) => Opcode::Fsd,
+            Fmaddd(_) => Opcode::Fmaddd,
+            Fmsubd(_) => Opcode::Fmsubd,
+            Fnmsubd(_) => Opcode::Fnmsubd,
+            Fnmaddd(_) => Opcode::Fnmaddd,
+            Faddd(_) => Opcode::Faddd,
+            Fsubd(_) => Opcode::Fsubd,
+            Fmuld(_) => Opcode::Fmuld,
+            Fdivd(_) => Opcode::Fdivd,
+            Fsqrtd(_) => Opcode::Fsqrtd,
+            Fsgnjd(_) => Opcode::Fsgnjd,
+            Fsgnjnd(_) => Opcode::Fsgnjnd,
+            Fsgnjxd(_) => Opcode::Fsgnjxd,
+            Fmind(_) => Opcode::Fmind,
+            Fmaxd(_) => Opcode::Fmaxd,
+            Fcvtwd(_) => Opcode::Fcvtwd,
+            Fcvtwud(_) => Opcode::Fcvtwud,
+            Fcvtld(_) => Opcode::Fcvtld,
+            Fcvtlud(_) => Opcode::Fcvtlud,
+            Fcvtdw(_) => Opcode::Fcvtdw,
+            Fcvtdwu(_) => Opcode::Fcvtdwu,
+            Fcvtdl(_) => Opcode::Fcvtdl,
+            Fcvtdlu(_) => Opcode::Fcvtdlu,
+            Feqd(_) => Opcode::Feqd,
+            Fltd(_) => Opcode::Fltd,
+            Fled(_) => Opcode::Fled,
+            Fclassd(_) => Opcode::Fclassd,
+            Fmvxd(_) => Opcode::Fmvxd,
+            Fmvdx(_) => Opcode::Fmvdx,
+            Fcvtsd(_) => Opcode::Fcvtsd,
+            Fcvtds(_) => Opcode::Fcvtds,
+            Adduw(_) => Opcode::Adduw,
+            Sh1add(_) => Opcode::Sh1add,
+            Sh2add(_) => Opcode::Sh2add,
+            Sh3add(_) => Opcode::Sh3add,
+            Sh1adduw(_) => Opcode::Sh1adduw,
+            Sh2adduw(_) => Opcode::Sh2adduw,
+            Sh3adduw(_) => Opcode::Sh3adduw,
+            Slliuw(_) => Opcode::Slliuw,
+            Andn(_) => Opcode::Andn,
+            Orn(_) => Opcode::Orn,
+            Xnor(_) => Opcode::Xnor,
+            Clz(_) => Opcode::Clz,
+            Clzw(_) => Opcode::Clzw,
+            Ctz(_) => Opcode::Ctz,
+            Ctzw(_) => Opcode::Ctzw,
+            Cpop(_) => Opcode::Cpop,
+            Cpopw(_) => Opcode::Cpopw,
+            Max(_) => Opcode::Max,
+            Maxu(_) => Opcode::Maxu,
+            Min(_) => Opcode::Min,
+            Minu(_) => Opcode::Minu,
+            Sextb(_) => Opcode::Sextb,
+            Sexth(_) => Opcode::Sexth,
+            Zexth(_) => Opcode::Zexth,
+            Rol(_) => Opcode::Rol,
+            Rolw(_) => Opcode::Rolw,
+            Ror(_) => Opcode::Ror,
+            Rorw(_) => Opcode::Rorw,
+            Rori(_) => Opcode::Rori,
+            Roriw(_) => Opcode::Roriw,
+            Orcb(_) => Opcode::Orcb,
+            Rev8(_) => Opcode::Rev8,
+            Clmul(_) => Opcode::Clmul,
+            Clmulh(_) => Opcode::Clmulh,
+            Clmulr(_) => Opcode::Clmulr,
+            Bclr(_) => Opcode::Bclr,
+            Bclri(_) => Opcode::Bclri,
+            Bext(_) => Opcode::Bext,
+            Bexti(_) => Opcode::Bexti,
+            Binv(_) => Opcode::Binv,
+            Binvi(_) => Opcode::Binvi,
+            Bset(_) => Opcode::Bset,
+            Bseti(_) => Opcode::Bseti,
+        }
+    }
+
+    /// The operands of this instruction, from which [`Instruction::from_parts`] builds the same
+    /// instruction again. Fields which are not used by the opcode have their default value.
+    pub fn operands(&self) -> Operands {
+        use Constructor::*;
+
+        let bits = u32::from(*self);
+        let opcode = self.opcode();
+
+        let field = |shift: u32| ((bits >> shift) & 0x1f) as u8;
+        let (rd, rs1, rs2, rs3) = (field(7), field(15), field(20), field(27));
+        // the decoder rejects the reserved rounding modes
+        let rm = || RoundingMode::try_from((bits >> 12) & 0b111).unwrap_or(RoundingMode::Dyn);
+
+        let imm = match opcode.format() {
+            Format::I => IType(bits).imm(),
+            Format::S => SType(bits).imm(),
+            Format::B => BType(bits).imm(),
+            Format::U => UType(bits).imm() as i32,
+            Format::J => JType(bits).imm(),
+            Format::R | Format::R4 => 0,
+        };
+
+        let operands = Operands::default();
+
+        match opcode.constructor() {
+            Register(_) if self.kind() == InstructionClass::Atomic => Operands {
+                rd,
+                rs1,
+                rs2,
+                aq: bits & (1 << 26) != 0,
+                rl: bits & (1 << 25) != 0,
+                ..operands
+            },
+            Register(_) | Float(_) | FloatCompare(_) => Operands {
+                rd,
+                rs1,
+                rs2,
+                ..operands
+            },
+            Immediate(_, range) => Operands {
+                rd,
+                rs1,
+                imm: range.extract(imm),
+                ..operands
+            },
+            SourcesImmediate(_, range) => Operands {
+                rs1,
+                rs2,
+                imm: range.extract(imm),
+                ..operands
+            },
+            Upper(_, range) => Operands {
+                rd,
+                imm: range.extract(imm),
+                ..operands
+            },
+            Unary(_) | FloatUnary(_) | FloatToInt(_) | IntToFloat(_) => Operands {
+                rd,
+                rs1,
+                ..operands
+            },
+            Sources(_) => Operands {
+                rs1,
+                rs2,
+                ..operands
+            },
+            Nullary(_) => operands,
+            CsrRegister(_) | CsrImmediate(_) => Operands {
+                rd,
+                rs1,
+                imm: imm & 0xfff,
+                ..operands
+            },
+            FloatLoad(_) => Operands {
+                rd,
+                rs1,
+                imm,
+                ..operands
+            },
+            FloatStore(_) => Operands {
+                rs1,
+                rs2,
+                imm,
+                ..operands
+            },
+            FusedMultiplyAdd(_) => Operands {
+                rd,
+                rs1,
+                rs2,
+                rs3,
+                rm: rm(),
+                ..operands
+            },
+            FloatRounded(_) => Operands {
+                rd,
+                rs1,
+                rs2,
+                rm: rm(),
+                ..operands
+            },
+            FloatUnaryRounded(_) | FloatToIntRounded(_) | IntToFloatRounded(_) => Operands {
+                rd,
+                rs1,
+                rm: rm(),
+                ..operands
+            },
+        }
+    }
+
+    /// Build an instruction from its opcode and operands, checking the ranges of registers and
+    /// immediates.
+    pub fn from_parts(opcode: Opcode, operands: Operands) -> Result<Instruction, OperandError> {
+        use Constructor::*;
+
+        let check = |n: u8| {
+            if n < 32 {
+                Ok(u32::from(n))
+            } else {
+                Err(OperandError::InvalidRegister(n))
+            }
+        };
+        let x = |n: u8| check(n).map(X::from);
+        let f = |n: u8| check(n).map(F::from);
+        let imm = |range: Range| {
+            if range.contains(operands.imm) {
+                Ok(operands.imm)
+            } else {
+                Err(OperandError::ImmediateOutOfRange {
+                    opcode,
+                    imm: operands.imm,
+                })
+            }
+        };
+        let csr = || imm(Range::Unsigned(12)).map(|csr| Csr::from(csr as u32));
+
+        let Operands {
+            rd,
+            rs1,
+            rs2,
+            rs3,
+            rm,
+            ..
+        } = operands;
+
+        let instruction = match opcode.constructor() {
+            Register(new) => new(x(rd)?, x(rs1)?, x(rs2)?),
+            Immediate(new, range) => new(x(rd)?, x(rs1)?, imm(range)?),
+            Unary(new) => new(x(rd)?, x(rs1)?),
+            SourcesImmediate(new, range) => new(x(rs1)?, x(rs2)?, imm(range)?),
+            Sources(new) => new(x(rs1)?, x(rs2)?),
+            Upper(new, range) => new(x(rd)?, imm(range)?),
+            Nullary(new) => new(),
+            CsrRegister(new) => new(x(rd)?, csr()?, x(rs1)?),
+            CsrImmediate(new) => new(x(rd)?, csr()?, check(rs1)?),
+            FloatLoad(new) => new(f(rd)?, x(rs1)?, imm(Range::Signed(12))?),
+            FloatStore(new) => new(x(rs1)?, f(rs2)?, imm(Range::Signed(12))?),
+            FusedMultiplyAdd(new) => new(f(rd)?, f(rs1)?, f(rs2)?, f(rs3)?, rm),
+            FloatRounded(new) => new(f(rd)?, f(rs1)?, f(rs2)?, rm),
+            Float(new) => new(f(rd)?, f(rs1)?, f(rs2)?),
+            FloatUnaryRounded(new) => new(f(rd)?, f(rs1)?, rm),
+            FloatUnary(new) => new(f(rd)?, f(rs1)?),
+            FloatToIntRounded(new) => new(x(rd)?, f(rs1)?, rm),
+            IntToFloatRounded(new) => new(f(rd)?, x(rs1)?, rm),
+            FloatCompare(new) => new(x(rd)?, f(rs1)?, f(rs2)?),
+            FloatToInt(new) => new(x(rd)?, f(rs1)?),
+            IntToFloat(new) => new(f(rd)?, x(rs1)?),
+        };
+
+        let ordering = (u32::from(operands.aq) << 26) | (u32::from(operands.rl) << 25);
+
+        match ordering {
+            0 => Ok(instruction),
+            _ if instruction.kind() == InstructionClass::Atomic => {
+                Ok(decode(u32::from(instruction) | ordering).expect("valid atomic instruction"))
+            }
+            _ => Err(OperandError::UnexpectedOrdering(opcode)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Register::*;
+
+    #[test]
+    fn mnemonics() {
+        for opcode in Opcode::ALL.iter() {
+            assert_eq!(opcode.mnemonic().parse(), Ok(*opcode));
+        }
+
+        assert_eq!("fmadd.d".parse(), Ok(Opcode::Fmaddd));
+        assert_eq!(
+            "addi.w".parse::<Opcode>(),
+            Err(UnknownMnemonic("addi.w".to_string()))
+        );
+        assert_eq!(Opcode::Amoswapw.to_string(), "amoswap.w");
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(Opcode::Add.format(), Format::R);
+        assert_eq!(Opcode::Ld.format(), Format::I);
+        assert_eq!(Opcode::Sd.format(), Format::S);
+        assert_eq!(Opcode::Bgeu.format(), Format::B);
+        assert_eq!(Opcode::Auipc.format(), Format::U);
+        assert_eq!(Opcode::Jal.format(), Format::J);
+        assert_eq!(Opcode::Fnmaddd.format(), Format::R4);
+        assert_eq!(Opcode::Csrrsi.format(), Format::I);
+    }
+
+    #[test]
+    fn parts_round_trip() {
+        use crate::FRegister::*;
+
+        let instructions = [
+            Instruction::new_lui(Gp, 0xfffff),
+            Instruction::new_jal(Ra, -2048),
+            Instruction::new_jalr(Zero, Ra, 0),
+            Instruction::new_bne(A0, A1, -4096),
+            Instruction::new_sd(Sp, Ra, -8),
+            Instruction::new_srai(A0, A1, 63),
+            Instruction::new_sraiw(A0, A1, 31),
+            Instruction::new_fence(Zero, Zero, 0b1111_1111),
+            Instruction::new_ecall(),
+            Instruction::new_sfence_vma(A0, A1),
+            Instruction::new_csrrw(A0, Csr::MSTATUS, A1),
+            Instruction::new_csrrci(A0, Csr::from(0xfff), 31),
+            Instruction::new_amoaddd(A0, A1, A2),
+            Instruction::new_flw(Fa0, Sp, 4),
+            Instruction::new_fsd(Sp, Fs11, -4),
+            Instruction::new_fmaddd(Fa0, Fa1, Fa2, Fa3, RoundingMode::Rtz),
+            Instruction::new_fadds(Fa0, Fa1, Fa2, RoundingMode::Dyn),
+            Instruction::new_fcvtlud(A0, Fa0, RoundingMode::Rup),
+            Instruction::new_fcvtdw(Fa0, A0),
+            Instruction::new_fmvxd(A0, Fa0),
+            Instruction::new_rev8(A0, A1),
+            Instruction::new_bseti(A0, A1, 63),
+        ];
+
+        for instruction in instructions.iter() {
+            assert_eq!(
+                Instruction::from_parts(instruction.opcode(), instruction.operands()),
+                Ok(*instruction),
+                "{}",
+                instruction
+            );
+        }
+    }
+
+    #[test]
+    fn atomic_ordering() {
+        let operands = Operands {
+            rd: 10,
+            rs1: 11,
+            rs2: 12,
+            aq: true,
+            rl: true,
+            ..Operands::default()
+        };
+
+        let instruction = Instruction::from_parts(Opcode::Amoswapw, operands).unwrap();
+
+        assert_eq!(u32::from(instruction), 0x0ec5a52f);
+        assert_eq!(instruction.operands(), operands);
+        assert_eq!(
+            Instruction::from_parts(Opcode::Add, operands),
+            Err(OperandError::UnexpectedOrdering(Opcode::Add))
+        );
+    }
+
+    #[test]
+    fn invalid_operands() {
+        let operands = |rd, imm| Operands {
+            rd,
+            imm,
+            ..Operands::default()
+        };
+
+        assert_eq!(
+            Instruction::from_parts(Opcode::Addi, operands(32, 0)),
+            Err(OperandError::InvalidRegister(32))
+        );
+        assert_eq!(
+            Instruction::from_parts(Opcode::Addi, operands(1, 2048)),
+            Err(OperandError::ImmediateOutOfRange {
+                opcode: Opcode::Addi,
+                imm: 2048
+            })
+        );
+        assert_eq!(
+            Instruction::from_parts(Opcode::Slliw, operands(1, 32)),
+            Err(OperandError::ImmediateOutOfRange {
+                opcode: Opcode::Slliw,
+                imm: 32
+            })
+        );
+        assert_eq!(
+            Instruction::from_parts(Opcode::Jal, operands(1, 3)),
+            Err(OperandError::ImmediateOutOfRange {
+                opcode: Opcode::Jal,
+                imm: 3
+            })
+        );
+        assert_eq!(
+            Instruction::from_parts(Opcode::Jal, operands(1, 4)),
+            Ok(Instruction::new_jal(Ra, 4))
+        );
+    }
+}
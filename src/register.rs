@@ -1,6 +1,6 @@
 use core::fmt;
 
-#[derive(Clone, Copy, Eq, PartialOrd, PartialEq)]
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialOrd, PartialEq)]
 #[repr(u32)]
 pub enum Register {
     Zero = 0,
@@ -51,6 +51,17 @@ impl fmt::Debug for Register {
     }
 }
 
+/// Registers are displayed by their ABI names, as done by GNU objdump. In contrast to [`fmt::Debug`]
+/// the frame pointer is named `s0`.
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Register::Fp => write!(f, "s0"),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
 impl From<u32> for Register {
     fn from(raw: u32) -> Register {
         unsafe { core::mem::transmute(raw) }
@@ -62,3 +73,607 @@ impl From<Register> for u32 {
         unsafe { core::mem::transmute(reg) }
     }
 }
+
+/// A register of the floating-point register file of the F and D extensions.
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialOrd, PartialEq)]
+#[repr(u32)]
+pub enum FRegister {
+    Ft0 = 0,
+    Ft1,
+    Ft2,
+    Ft3,
+    Ft4,
+    Ft5,
+    Ft6,
+    Ft7,
+    Fs0,
+    Fs1,
+    Fa0,
+    Fa1,
+    Fa2,
+    Fa3,
+    Fa4,
+    Fa5,
+    Fa6,
+    Fa7,
+    Fs2,
+    Fs3,
+    Fs4,
+    Fs5,
+    Fs6,
+    Fs7,
+    Fs8,
+    Fs9,
+    Fs10,
+    Fs11,
+    Ft8,
+    Ft9,
+    Ft10,
+    Ft11,
+}
+
+impl fmt::Debug for FRegister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const LUT: [&str; 32] = [
+            "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1",
+            "fa2", "fa3", "fa4", "fa5", "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7",
+            "fs8", "fs9", "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+        ];
+
+        let idx: u32 = (*self).into();
+
+        write!(f, "{}", LUT[idx as usize])
+    }
+}
+
+impl fmt::Display for FRegister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl From<u32> for FRegister {
+    fn from(raw: u32) -> FRegister {
+        unsafe { core::mem::transmute(raw) }
+    }
+}
+
+impl From<FRegister> for u32 {
+    fn from(reg: FRegister) -> u32 {
+        unsafe { core::mem::transmute(reg) }
+    }
+}
+
+/// A control and status register, identified by its 12-bit address.
+///
+/// Standard CSRs of the unprivileged, supervisor, hypervisor and machine level are available as
+/// associated constants and are displayed by their names, all others by their address.
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialOrd, PartialEq)]
+pub struct Csr(pub u16);
+
+impl Csr {
+    pub const FFLAGS: Csr = Csr(0x001);
+    pub const FRM: Csr = Csr(0x002);
+    pub const FCSR: Csr = Csr(0x003);
+    pub const SSTATUS: Csr = Csr(0x100);
+    pub const SIE: Csr = Csr(0x104);
+    pub const STVEC: Csr = Csr(0x105);
+    pub const SCOUNTEREN: Csr = Csr(0x106);
+    pub const SENVCFG: Csr = Csr(0x10a);
+    pub const SSCRATCH: Csr = Csr(0x140);
+    pub const SEPC: Csr = Csr(0x141);
+    pub const SCAUSE: Csr = Csr(0x142);
+    pub const STVAL: Csr = Csr(0x143);
+    pub const SIP: Csr = Csr(0x144);
+    pub const SATP: Csr = Csr(0x180);
+    pub const VSSTATUS: Csr = Csr(0x200);
+    pub const VSIE: Csr = Csr(0x204);
+    pub const VSTVEC: Csr = Csr(0x205);
+    pub const VSSCRATCH: Csr = Csr(0x240);
+    pub const VSEPC: Csr = Csr(0x241);
+    pub const VSCAUSE: Csr = Csr(0x242);
+    pub const VSTVAL: Csr = Csr(0x243);
+    pub const VSIP: Csr = Csr(0x244);
+    pub const VSATP: Csr = Csr(0x280);
+    pub const MSTATUS: Csr = Csr(0x300);
+    pub const MISA: Csr = Csr(0x301);
+    pub const MEDELEG: Csr = Csr(0x302);
+    pub const MIDELEG: Csr = Csr(0x303);
+    pub const MIE: Csr = Csr(0x304);
+    pub const MTVEC: Csr = Csr(0x305);
+    pub const MCOUNTEREN: Csr = Csr(0x306);
+    pub const MENVCFG: Csr = Csr(0x30a);
+    pub const MCOUNTINHIBIT: Csr = Csr(0x320);
+    pub const MHPMEVENT3: Csr = Csr(0x323);
+    pub const MHPMEVENT4: Csr = Csr(0x324);
+    pub const MHPMEVENT5: Csr = Csr(0x325);
+    pub const MHPMEVENT6: Csr = Csr(0x326);
+    pub const MHPMEVENT7: Csr = Csr(0x327);
+    pub const MHPMEVENT8: Csr = Csr(0x328);
+    pub const MHPMEVENT9: Csr = Csr(0x329);
+    pub const MHPMEVENT10: Csr = Csr(0x32a);
+    pub const MHPMEVENT11: Csr = Csr(0x32b);
+    pub const MHPMEVENT12: Csr = Csr(0x32c);
+    pub const MHPMEVENT13: Csr = Csr(0x32d);
+    pub const MHPMEVENT14: Csr = Csr(0x32e);
+    pub const MHPMEVENT15: Csr = Csr(0x32f);
+    pub const MHPMEVENT16: Csr = Csr(0x330);
+    pub const MHPMEVENT17: Csr = Csr(0x331);
+    pub const MHPMEVENT18: Csr = Csr(0x332);
+    pub const MHPMEVENT19: Csr = Csr(0x333);
+    pub const MHPMEVENT20: Csr = Csr(0x334);
+    pub const MHPMEVENT21: Csr = Csr(0x335);
+    pub const MHPMEVENT22: Csr = Csr(0x336);
+    pub const MHPMEVENT23: Csr = Csr(0x337);
+    pub const MHPMEVENT24: Csr = Csr(0x338);
+    pub const MHPMEVENT25: Csr = Csr(0x339);
+    pub const MHPMEVENT26: Csr = Csr(0x33a);
+    pub const MHPMEVENT27: Csr = Csr(0x33b);
+    pub const MHPMEVENT28: Csr = Csr(0x33c);
+    pub const MHPMEVENT29: Csr = Csr(0x33d);
+    pub const MHPMEVENT30: Csr = Csr(0x33e);
+    pub const MHPMEVENT31: Csr = Csr(0x33f);
+    pub const MSCRATCH: Csr = Csr(0x340);
+    pub const MEPC: Csr = Csr(0x341);
+    pub const MCAUSE: Csr = Csr(0x342);
+    pub const MTVAL: Csr = Csr(0x343);
+    pub const MIP: Csr = Csr(0x344);
+    pub const MTINST: Csr = Csr(0x34a);
+    pub const MTVAL2: Csr = Csr(0x34b);
+    pub const PMPCFG0: Csr = Csr(0x3a0);
+    pub const PMPCFG1: Csr = Csr(0x3a1);
+    pub const PMPCFG2: Csr = Csr(0x3a2);
+    pub const PMPCFG3: Csr = Csr(0x3a3);
+    pub const PMPCFG4: Csr = Csr(0x3a4);
+    pub const PMPCFG5: Csr = Csr(0x3a5);
+    pub const PMPCFG6: Csr = Csr(0x3a6);
+    pub const PMPCFG7: Csr = Csr(0x3a7);
+    pub const PMPCFG8: Csr = Csr(0x3a8);
+    pub const PMPCFG9: Csr = Csr(0x3a9);
+    pub const PMPCFG10: Csr = Csr(0x3aa);
+    pub const PMPCFG11: Csr = Csr(0x3ab);
+    pub const PMPCFG12: Csr = Csr(0x3ac);
+    pub const PMPCFG13: Csr = Csr(0x3ad);
+    pub const PMPCFG14: Csr = Csr(0x3ae);
+    pub const PMPCFG15: Csr = Csr(0x3af);
+    pub const PMPADDR0: Csr = Csr(0x3b0);
+    pub const PMPADDR1: Csr = Csr(0x3b1);
+    pub const PMPADDR2: Csr = Csr(0x3b2);
+    pub const PMPADDR3: Csr = Csr(0x3b3);
+    pub const PMPADDR4: Csr = Csr(0x3b4);
+    pub const PMPADDR5: Csr = Csr(0x3b5);
+    pub const PMPADDR6: Csr = Csr(0x3b6);
+    pub const PMPADDR7: Csr = Csr(0x3b7);
+    pub const PMPADDR8: Csr = Csr(0x3b8);
+    pub const PMPADDR9: Csr = Csr(0x3b9);
+    pub const PMPADDR10: Csr = Csr(0x3ba);
+    pub const PMPADDR11: Csr = Csr(0x3bb);
+    pub const PMPADDR12: Csr = Csr(0x3bc);
+    pub const PMPADDR13: Csr = Csr(0x3bd);
+    pub const PMPADDR14: Csr = Csr(0x3be);
+    pub const PMPADDR15: Csr = Csr(0x3bf);
+    pub const PMPADDR16: Csr = Csr(0x3c0);
+    pub const PMPADDR17: Csr = Csr(0x3c1);
+    pub const PMPADDR18: Csr = Csr(0x3c2);
+    pub const PMPADDR19: Csr = Csr(0x3c3);
+    pub const PMPADDR20: Csr = Csr(0x3c4);
+    pub const PMPADDR21: Csr = Csr(0x3c5);
+    pub const PMPADDR22: Csr = Csr(0x3c6);
+    pub const PMPADDR23: Csr = Csr(0x3c7);
+    pub const PMPADDR24: Csr = Csr(0x3c8);
+    pub const PMPADDR25: Csr = Csr(0x3c9);
+    pub const PMPADDR26: Csr = Csr(0x3ca);
+    pub const PMPADDR27: Csr = Csr(0x3cb);
+    pub const PMPADDR28: Csr = Csr(0x3cc);
+    pub const PMPADDR29: Csr = Csr(0x3cd);
+    pub const PMPADDR30: Csr = Csr(0x3ce);
+    pub const PMPADDR31: Csr = Csr(0x3cf);
+    pub const PMPADDR32: Csr = Csr(0x3d0);
+    pub const PMPADDR33: Csr = Csr(0x3d1);
+    pub const PMPADDR34: Csr = Csr(0x3d2);
+    pub const PMPADDR35: Csr = Csr(0x3d3);
+    pub const PMPADDR36: Csr = Csr(0x3d4);
+    pub const PMPADDR37: Csr = Csr(0x3d5);
+    pub const PMPADDR38: Csr = Csr(0x3d6);
+    pub const PMPADDR39: Csr = Csr(0x3d7);
+    pub const PMPADDR40: Csr = Csr(0x3d8);
+    pub const PMPADDR41: Csr = Csr(0x3d9);
+    pub const PMPADDR42: Csr = Csr(0x3da);
+    pub const PMPADDR43: Csr = Csr(0x3db);
+    pub const PMPADDR44: Csr = Csr(0x3dc);
+    pub const PMPADDR45: Csr = Csr(0x3dd);
+    pub const PMPADDR46: Csr = Csr(0x3de);
+    pub const PMPADDR47: Csr = Csr(0x3df);
+    pub const PMPADDR48: Csr = Csr(0x3e0);
+    pub const PMPADDR49: Csr = Csr(0x3e1);
+    pub const PMPADDR50: Csr = Csr(0x3e2);
+    pub const PMPADDR51: Csr = Csr(0x3e3);
+    pub const PMPADDR52: Csr = Csr(0x3e4);
+    pub const PMPADDR53: Csr = Csr(0x3e5);
+    pub const PMPADDR54: Csr = Csr(0x3e6);
+    pub const PMPADDR55: Csr = Csr(0x3e7);
+    pub const PMPADDR56: Csr = Csr(0x3e8);
+    pub const PMPADDR57: Csr = Csr(0x3e9);
+    pub const PMPADDR58: Csr = Csr(0x3ea);
+    pub const PMPADDR59: Csr = Csr(0x3eb);
+    pub const PMPADDR60: Csr = Csr(0x3ec);
+    pub const PMPADDR61: Csr = Csr(0x3ed);
+    pub const PMPADDR62: Csr = Csr(0x3ee);
+    pub const PMPADDR63: Csr = Csr(0x3ef);
+    pub const HSTATUS: Csr = Csr(0x600);
+    pub const HEDELEG: Csr = Csr(0x602);
+    pub const HIDELEG: Csr = Csr(0x603);
+    pub const HIE: Csr = Csr(0x604);
+    pub const HCOUNTEREN: Csr = Csr(0x606);
+    pub const HGEIE: Csr = Csr(0x607);
+    pub const HENVCFG: Csr = Csr(0x60a);
+    pub const HTVAL: Csr = Csr(0x643);
+    pub const HIP: Csr = Csr(0x644);
+    pub const HVIP: Csr = Csr(0x645);
+    pub const HTINST: Csr = Csr(0x64a);
+    pub const HGATP: Csr = Csr(0x680);
+    pub const TSELECT: Csr = Csr(0x7a0);
+    pub const TDATA1: Csr = Csr(0x7a1);
+    pub const TDATA2: Csr = Csr(0x7a2);
+    pub const TDATA3: Csr = Csr(0x7a3);
+    pub const DCSR: Csr = Csr(0x7b0);
+    pub const DPC: Csr = Csr(0x7b1);
+    pub const DSCRATCH0: Csr = Csr(0x7b2);
+    pub const DSCRATCH1: Csr = Csr(0x7b3);
+    pub const MCYCLE: Csr = Csr(0xb00);
+    pub const MINSTRET: Csr = Csr(0xb02);
+    pub const MHPMCOUNTER3: Csr = Csr(0xb03);
+    pub const MHPMCOUNTER4: Csr = Csr(0xb04);
+    pub const MHPMCOUNTER5: Csr = Csr(0xb05);
+    pub const MHPMCOUNTER6: Csr = Csr(0xb06);
+    pub const MHPMCOUNTER7: Csr = Csr(0xb07);
+    pub const MHPMCOUNTER8: Csr = Csr(0xb08);
+    pub const MHPMCOUNTER9: Csr = Csr(0xb09);
+    pub const MHPMCOUNTER10: Csr = Csr(0xb0a);
+    pub const MHPMCOUNTER11: Csr = Csr(0xb0b);
+    pub const MHPMCOUNTER12: Csr = Csr(0xb0c);
+    pub const MHPMCOUNTER13: Csr = Csr(0xb0d);
+    pub const MHPMCOUNTER14: Csr = Csr(0xb0e);
+    pub const MHPMCOUNTER15: Csr = Csr(0xb0f);
+    pub const MHPMCOUNTER16: Csr = Csr(0xb10);
+    pub const MHPMCOUNTER17: Csr = Csr(0xb11);
+    pub const MHPMCOUNTER18: Csr = Csr(0xb12);
+    pub const MHPMCOUNTER19: Csr = Csr(0xb13);
+    pub const MHPMCOUNTER20: Csr = Csr(0xb14);
+    pub const MHPMCOUNTER21: Csr = Csr(0xb15);
+    pub const MHPMCOUNTER22: Csr = Csr(0xb16);
+    pub const MHPMCOUNTER23: Csr = Csr(0xb17);
+    pub const MHPMCOUNTER24: Csr = Csr(0xb18);
+    pub const MHPMCOUNTER25: Csr = Csr(0xb19);
+    pub const MHPMCOUNTER26: Csr = Csr(0xb1a);
+    pub const MHPMCOUNTER27: Csr = Csr(0xb1b);
+    pub const MHPMCOUNTER28: Csr = Csr(0xb1c);
+    pub const MHPMCOUNTER29: Csr = Csr(0xb1d);
+    pub const MHPMCOUNTER30: Csr = Csr(0xb1e);
+    pub const MHPMCOUNTER31: Csr = Csr(0xb1f);
+    pub const CYCLE: Csr = Csr(0xc00);
+    pub const TIME: Csr = Csr(0xc01);
+    pub const INSTRET: Csr = Csr(0xc02);
+    pub const HPMCOUNTER3: Csr = Csr(0xc03);
+    pub const HPMCOUNTER4: Csr = Csr(0xc04);
+    pub const HPMCOUNTER5: Csr = Csr(0xc05);
+    pub const HPMCOUNTER6: Csr = Csr(0xc06);
+    pub const HPMCOUNTER7: Csr = Csr(0xc07);
+    pub const HPMCOUNTER8: Csr = Csr(0xc08);
+    pub const HPMCOUNTER9: Csr = Csr(0xc09);
+    pub const HPMCOUNTER10: Csr = Csr(0xc0a);
+    pub const HPMCOUNTER11: Csr = Csr(0xc0b);
+    pub const HPMCOUNTER12: Csr = Csr(0xc0c);
+    pub const HPMCOUNTER13: Csr = Csr(0xc0d);
+    pub const HPMCOUNTER14: Csr = Csr(0xc0e);
+    pub const HPMCOUNTER15: Csr = Csr(0xc0f);
+    pub const HPMCOUNTER16: Csr = Csr(0xc10);
+    pub const HPMCOUNTER17: Csr = Csr(0xc11);
+    pub const HPMCOUNTER18: Csr = Csr(0xc12);
+    pub const HPMCOUNTER19: Csr = Csr(0xc13);
+    pub const HPMCOUNTER20: Csr = Csr(0xc14);
+    pub const HPMCOUNTER21: Csr = Csr(0xc15);
+    pub const HPMCOUNTER22: Csr = Csr(0xc16);
+    pub const HPMCOUNTER23: Csr = Csr(0xc17);
+    pub const HPMCOUNTER24: Csr = Csr(0xc18);
+    pub const HPMCOUNTER25: Csr = Csr(0xc19);
+    pub const HPMCOUNTER26: Csr = Csr(0xc1a);
+    pub const HPMCOUNTER27: Csr = Csr(0xc1b);
+    pub const HPMCOUNTER28: Csr = Csr(0xc1c);
+    pub const HPMCOUNTER29: Csr = Csr(0xc1d);
+    pub const HPMCOUNTER30: Csr = Csr(0xc1e);
+    pub const HPMCOUNTER31: Csr = Csr(0xc1f);
+    pub const HGEIP: Csr = Csr(0xe12);
+    pub const MVENDORID: Csr = Csr(0xf11);
+    pub const MARCHID: Csr = Csr(0xf12);
+    pub const MIMPID: Csr = Csr(0xf13);
+    pub const MHARTID: Csr = Csr(0xf14);
+    pub const MCONFIGPTR: Csr = Csr(0xf15);
+
+    /// The name of a standard CSR as used by the GNU assembler.
+    pub fn name(self) -> Option<&'static str> {
+        CSR_NAMES
+            .binary_search_by_key(&self, |(csr, _)| *csr)
+            .ok()
+            .map(|idx| CSR_NAMES[idx].1)
+    }
+
+    /// Looks up a standard CSR by its name.
+    pub fn from_name(name: &str) -> Option<Csr> {
+        CSR_NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(csr, _)| *csr)
+    }
+}
+
+// sorted by address
+const CSR_NAMES: &[(Csr, &str)] = &[
+    (Csr::FFLAGS, "fflags"),
+    (Csr::FRM, "frm"),
+    (Csr::FCSR, "fcsr"),
+    (Csr::SSTATUS, "sstatus"),
+    (Csr::SIE, "sie"),
+    (Csr::STVEC, "stvec"),
+    (Csr::SCOUNTEREN, "scounteren"),
+    (Csr::SENVCFG, "senvcfg"),
+    (Csr::SSCRATCH, "sscratch"),
+    (Csr::SEPC, "sepc"),
+    (Csr::SCAUSE, "scause"),
+    (Csr::STVAL, "stval"),
+    (Csr::SIP, "sip"),
+    (Csr::SATP, "satp"),
+    (Csr::VSSTATUS, "vsstatus"),
+    (Csr::VSIE, "vsie"),
+    (Csr::VSTVEC, "vstvec"),
+    (Csr::VSSCRATCH, "vsscratch"),
+    (Csr::VSEPC, "vsepc"),
+    (Csr::VSCAUSE, "vscause"),
+    (Csr::VSTVAL, "vstval"),
+    (Csr::VSIP, "vsip"),
+    (Csr::VSATP, "vsatp"),
+    (Csr::MSTATUS, "mstatus"),
+    (Csr::MISA, "misa"),
+    (Csr::MEDELEG, "medeleg"),
+    (Csr::MIDELEG, "mideleg"),
+    (Csr::MIE, "mie"),
+    (Csr::MTVEC, "mtvec"),
+    (Csr::MCOUNTEREN, "mcounteren"),
+    (Csr::MENVCFG, "menvcfg"),
+    (Csr::MCOUNTINHIBIT, "mcountinhibit"),
+    (Csr::MHPMEVENT3, "mhpmevent3"),
+    (Csr::MHPMEVENT4, "mhpmevent4"),
+    (Csr::MHPMEVENT5, "mhpmevent5"),
+    (Csr::MHPMEVENT6, "mhpmevent6"),
+    (Csr::MHPMEVENT7, "mhpmevent7"),
+    (Csr::MHPMEVENT8, "mhpmevent8"),
+    (Csr::MHPMEVENT9, "mhpmevent9"),
+    (Csr::MHPMEVENT10, "mhpmevent10"),
+    (Csr::MHPMEVENT11, "mhpmevent11"),
+    (Csr::MHPMEVENT12, "mhpmevent12"),
+    (Csr::MHPMEVENT13, "mhpmevent13"),
+    (Csr::MHPMEVENT14, "mhpmevent14"),
+    (Csr::MHPMEVENT15, "mhpmevent15"),
+    (Csr::MHPMEVENT16, "mhpmevent16"),
+    (Csr::MHPMEVENT17, "mhpmevent17"),
+    (Csr::MHPMEVENT18, "mhpmevent18"),
+    (Csr::MHPMEVENT19, "mhpmevent19"),
+    (Csr::MHPMEVENT20, "mhpmevent20"),
+    (Csr::MHPMEVENT21, "mhpmevent21"),
+    (Csr::MHPMEVENT22, "mhpmevent22"),
+    (Csr::MHPMEVENT23, "mhpmevent23"),
+    (Csr::MHPMEVENT24, "mhpmevent24"),
+    (Csr::MHPMEVENT25, "mhpmevent25"),
+    (Csr::MHPMEVENT26, "mhpmevent26"),
+    (Csr::MHPMEVENT27, "mhpmevent27"),
+    (Csr::MHPMEVENT28, "mhpmevent28"),
+    (Csr::MHPMEVENT29, "mhpmevent29"),
+    (Csr::MHPMEVENT30, "mhpmevent30"),
+    (Csr::MHPMEVENT31, "mhpmevent31"),
+    (Csr::MSCRATCH, "mscratch"),
+    (Csr::MEPC, "mepc"),
+    (Csr::MCAUSE, "mcause"),
+    (Csr::MTVAL, "mtval"),
+    (Csr::MIP, "mip"),
+    (Csr::MTINST, "mtinst"),
+    (Csr::MTVAL2, "mtval2"),
+    (Csr::PMPCFG0, "pmpcfg0"),
+    (Csr::PMPCFG1, "pmpcfg1"),
+    (Csr::PMPCFG2, "pmpcfg2"),
+    (Csr::PMPCFG3, "pmpcfg3"),
+    (Csr::PMPCFG4, "pmpcfg4"),
+    (Csr::PMPCFG5, "pmpcfg5"),
+    (Csr::PMPCFG6, "pmpcfg6"),
+    (Csr::PMPCFG7, "pmpcfg7"),
+    (Csr::PMPCFG8, "pmpcfg8"),
+    (Csr::PMPCFG9, "pmpcfg9"),
+    (Csr::PMPCFG10, "pmpcfg10"),
+    (Csr::PMPCFG11, "pmpcfg11"),
+    (Csr::PMPCFG12, "pmpcfg12"),
+    (Csr::PMPCFG13, "pmpcfg13"),
+    (Csr::PMPCFG14, "pmpcfg14"),
+    (Csr::PMPCFG15, "pmpcfg15"),
+    (Csr::PMPADDR0, "pmpaddr0"),
+    (Csr::PMPADDR1, "pmpaddr1"),
+    (Csr::PMPADDR2, "pmpaddr2"),
+    (Csr::PMPADDR3, "pmpaddr3"),
+    (Csr::PMPADDR4, "pmpaddr4"),
+    (Csr::PMPADDR5, "pmpaddr5"),
+    (Csr::PMPADDR6, "pmpaddr6"),
+    (Csr::PMPADDR7, "pmpaddr7"),
+    (Csr::PMPADDR8, "pmpaddr8"),
+    (Csr::PMPADDR9, "pmpaddr9"),
+    (Csr::PMPADDR10, "pmpaddr10"),
+    (Csr::PMPADDR11, "pmpaddr11"),
+    (Csr::PMPADDR12, "pmpaddr12"),
+    (Csr::PMPADDR13, "pmpaddr13"),
+    (Csr::PMPADDR14, "pmpaddr14"),
+    (Csr::PMPADDR15, "pmpaddr15"),
+    (Csr::PMPADDR16, "pmpaddr16"),
+    (Csr::PMPADDR17, "pmpaddr17"),
+    (Csr::PMPADDR18, "pmpaddr18"),
+    (Csr::PMPADDR19, "pmpaddr19"),
+    (Csr::PMPADDR20, "pmpaddr20"),
+    (Csr::PMPADDR21, "pmpaddr21"),
+    (Csr::PMPADDR22, "pmpaddr22"),
+    (Csr::PMPADDR23, "pmpaddr23"),
+    (Csr::PMPADDR24, "pmpaddr24"),
+    (Csr::PMPADDR25, "pmpaddr25"),
+    (Csr::PMPADDR26, "pmpaddr26"),
+    (Csr::PMPADDR27, "pmpaddr27"),
+    (Csr::PMPADDR28, "pmpaddr28"),
+    (Csr::PMPADDR29, "pmpaddr29"),
+    (Csr::PMPADDR30, "pmpaddr30"),
+    (Csr::PMPADDR31, "pmpaddr31"),
+    (Csr::PMPADDR32, "pmpaddr32"),
+    (Csr::PMPADDR33, "pmpaddr33"),
+    (Csr::PMPADDR34, "pmpaddr34"),
+    (Csr::PMPADDR35, "pmpaddr35"),
+    (Csr::PMPADDR36, "pmpaddr36"),
+    (Csr::PMPADDR37, "pmpaddr37"),
+    (Csr::PMPADDR38, "pmpaddr38"),
+    (Csr::PMPADDR39, "pmpaddr39"),
+    (Csr::PMPADDR40, "pmpaddr40"),
+    (Csr::PMPADDR41, "pmpaddr41"),
+    (Csr::PMPADDR42, "pmpaddr42"),
+    (Csr::PMPADDR43, "pmpaddr43"),
+    (Csr::PMPADDR44, "pmpaddr44"),
+    (Csr::PMPADDR45, "pmpaddr45"),
+    (Csr::PMPADDR46, "pmpaddr46"),
+    (Csr::PMPADDR47, "pmpaddr47"),
+    (Csr::PMPADDR48, "pmpaddr48"),
+    (Csr::PMPADDR49, "pmpaddr49"),
+    (Csr::PMPADDR50, "pmpaddr50"),
+    (Csr::PMPADDR51, "pmpaddr51"),
+    (Csr::PMPADDR52, "pmpaddr52"),
+    (Csr::PMPADDR53, "pmpaddr53"),
+    (Csr::PMPADDR54, "pmpaddr54"),
+    (Csr::PMPADDR55, "pmpaddr55"),
+    (Csr::PMPADDR56, "pmpaddr56"),
+    (Csr::PMPADDR57, "pmpaddr57"),
+    (Csr::PMPADDR58, "pmpaddr58"),
+    (Csr::PMPADDR59, "pmpaddr59"),
+    (Csr::PMPADDR60, "pmpaddr60"),
+    (Csr::PMPADDR61, "pmpaddr61"),
+    (Csr::PMPADDR62, "pmpaddr62"),
+    (Csr::PMPADDR63, "pmpaddr63"),
+    (Csr::HSTATUS, "hstatus"),
+    (Csr::HEDELEG, "hedeleg"),
+    (Csr::HIDELEG, "hideleg"),
+    (Csr::HIE, "hie"),
+    (Csr::HCOUNTEREN, "hcounteren"),
+    (Csr::HGEIE, "hgeie"),
+    (Csr::HENVCFG, "henvcfg"),
+    (Csr::HTVAL, "htval"),
+    (Csr::HIP, "hip"),
+    (Csr::HVIP, "hvip"),
+    (Csr::HTINST, "htinst"),
+    (Csr::HGATP, "hgatp"),
+    (Csr::TSELECT, "tselect"),
+    (Csr::TDATA1, "tdata1"),
+    (Csr::TDATA2, "tdata2"),
+    (Csr::TDATA3, "tdata3"),
+    (Csr::DCSR, "dcsr"),
+    (Csr::DPC, "dpc"),
+    (Csr::DSCRATCH0, "dscratch0"),
+    (Csr::DSCRATCH1, "dscratch1"),
+    (Csr::MCYCLE, "mcycle"),
+    (Csr::MINSTRET, "minstret"),
+    (Csr::MHPMCOUNTER3, "mhpmcounter3"),
+    (Csr::MHPMCOUNTER4, "mhpmcounter4"),
+    (Csr::MHPMCOUNTER5, "mhpmcounter5"),
+    (Csr::MHPMCOUNTER6, "mhpmcounter6"),
+    (Csr::MHPMCOUNTER7, "mhpmcounter7"),
+    (Csr::MHPMCOUNTER8, "mhpmcounter8"),
+    (Csr::MHPMCOUNTER9, "mhpmcounter9"),
+    (Csr::MHPMCOUNTER10, "mhpmcounter10"),
+    (Csr::MHPMCOUNTER11, "mhpmcounter11"),
+    (Csr::MHPMCOUNTER12, "mhpmcounter12"),
+    (Csr::MHPMCOUNTER13, "mhpmcounter13"),
+    (Csr::MHPMCOUNTER14, "mhpmcounter14"),
+    (Csr::MHPMCOUNTER15, "mhpmcounter15"),
+    (Csr::MHPMCOUNTER16, "mhpmcounter16"),
+    (Csr::MHPMCOUNTER17, "mhpmcounter17"),
+    (Csr::MHPMCOUNTER18, "mhpmcounter18"),
+    (Csr::MHPMCOUNTER19, "mhpmcounter19"),
+    (Csr::MHPMCOUNTER20, "mhpmcounter20"),
+    (Csr::MHPMCOUNTER21, "mhpmcounter21"),
+    (Csr::MHPMCOUNTER22, "mhpmcounter22"),
+    (Csr::MHPMCOUNTER23, "mhpmcounter23"),
+    (Csr::MHPMCOUNTER24, "mhpmcounter24"),
+    (Csr::MHPMCOUNTER25, "mhpmcounter25"),
+    (Csr::MHPMCOUNTER26, "mhpmcounter26"),
+    (Csr::MHPMCOUNTER27, "mhpmcounter27"),
+    (Csr::MHPMCOUNTER28, "mhpmcounter28"),
+    (Csr::MHPMCOUNTER29, "mhpmcounter29"),
+    (Csr::MHPMCOUNTER30, "mhpmcounter30"),
+    (Csr::MHPMCOUNTER31, "mhpmcounter31"),
+    (Csr::CYCLE, "cycle"),
+    (Csr::TIME, "time"),
+    (Csr::INSTRET, "instret"),
+    (Csr::HPMCOUNTER3, "hpmcounter3"),
+    (Csr::HPMCOUNTER4, "hpmcounter4"),
+    (Csr::HPMCOUNTER5, "hpmcounter5"),
+    (Csr::HPMCOUNTER6, "hpmcounter6"),
+    (Csr::HPMCOUNTER7, "hpmcounter7"),
+    (Csr::HPMCOUNTER8, "hpmcounter8"),
+    (Csr::HPMCOUNTER9, "hpmcounter9"),
+    (Csr::HPMCOUNTER10, "hpmcounter10"),
+    (Csr::HPMCOUNTER11, "hpmcounter11"),
+    (Csr::HPMCOUNTER12, "hpmcounter12"),
+    (Csr::HPMCOUNTER13, "hpmcounter13"),
+    (Csr::HPMCOUNTER14, "hpmcounter14"),
+    (Csr::HPMCOUNTER15, "hpmcounter15"),
+    (Csr::HPMCOUNTER16, "hpmcounter16"),
+    (Csr::HPMCOUNTER17, "hpmcounter17"),
+    (Csr::HPMCOUNTER18, "hpmcounter18"),
+    (Csr::HPMCOUNTER19, "hpmcounter19"),
+    (Csr::HPMCOUNTER20, "hpmcounter20"),
+    (Csr::HPMCOUNTER21, "hpmcounter21"),
+    (Csr::HPMCOUNTER22, "hpmcounter22"),
+    (Csr::HPMCOUNTER23, "hpmcounter23"),
+    (Csr::HPMCOUNTER24, "hpmcounter24"),
+    (Csr::HPMCOUNTER25, "hpmcounter25"),
+    (Csr::HPMCOUNTER26, "hpmcounter26"),
+    (Csr::HPMCOUNTER27, "hpmcounter27"),
+    (Csr::HPMCOUNTER28, "hpmcounter28"),
+    (Csr::HPMCOUNTER29, "hpmcounter29"),
+    (Csr::HPMCOUNTER30, "hpmcounter30"),
+    (Csr::HPMCOUNTER31, "hpmcounter31"),
+    (Csr::HGEIP, "hgeip"),
+    (Csr::MVENDORID, "mvendorid"),
+    (Csr::MARCHID, "marchid"),
+    (Csr::MIMPID, "mimpid"),
+    (Csr::MHARTID, "mhartid"),
+    (Csr::MCONFIGPTR, "mconfigptr"),
+];
+
+impl fmt::Debug for Csr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{:#x}", self.0),
+        }
+    }
+}
+
+/// CSRs are displayed by their names, unknown CSRs by their address (as done by GNU objdump).
+impl fmt::Display for Csr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl From<u32> for Csr {
+    fn from(raw: u32) -> Csr {
+        assert!(raw < 2_u32.pow(12), "CSR addresses are 12 bits wide");
+
+        Csr(raw as u16)
+    }
+}
+
+impl From<Csr> for u32 {
+    fn from(csr: Csr) -> u32 {
+        csr.0.into()
+    }
+}
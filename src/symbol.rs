@@ -0,0 +1,128 @@
+//! # Symbols of a loaded program
+
+use goblin::elf::{
+    sym::{STT_FILE, STT_FUNC, STT_NOTYPE, STT_OBJECT, STT_SECTION},
+    Elf,
+};
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SymbolKind {
+    /// A function or other executable code (`STT_FUNC`).
+    Function,
+    /// A data object like a variable or an array (`STT_OBJECT`).
+    Object,
+    /// A symbol without a type, e.g. a label in assembly code (`STT_NOTYPE`).
+    NoType,
+    /// Any other symbol type, given by its raw ELF `st_type`.
+    Other(u8),
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub address: u64,
+    pub size: u64,
+    pub kind: SymbolKind,
+}
+
+impl Symbol {
+    /// The address range covered by this symbol, which ends at the end of the address space if
+    /// the size of a (malformed) symbol exceeds it.
+    pub fn range(&self) -> Range<u64> {
+        self.address..self.address.saturating_add(self.size)
+    }
+
+    /// Returns true if `address` lies within this symbol. Symbols without a size
+    /// only contain their own address.
+    pub fn contains(&self, address: u64) -> bool {
+        address == self.address || self.range().contains(&address)
+    }
+}
+
+/// Extract all named symbols from the `.symtab` section, sorted by address.
+///
+/// Section and file symbols are skipped, since they do not name anything in
+/// the address space of the program.
+pub(crate) fn extract_symbols(elf: &Elf) -> Vec<Symbol> {
+    let mut symbols = elf
+        .syms
+        .iter()
+        .filter(|sym| sym.st_type() != STT_SECTION && sym.st_type() != STT_FILE)
+        .filter_map(|sym| {
+            let name = match elf.strtab.get(sym.st_name) {
+                Some(Ok(name)) if !name.is_empty() => name,
+                _ => return None,
+            };
+
+            let kind = match sym.st_type() {
+                STT_FUNC => SymbolKind::Function,
+                STT_OBJECT => SymbolKind::Object,
+                STT_NOTYPE => SymbolKind::NoType,
+                other => SymbolKind::Other(other),
+            };
+
+            Some(Symbol {
+                name: name.to_string(),
+                address: sym.st_value,
+                size: sym.st_size,
+                kind,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    symbols.sort_by_key(|s| s.address);
+
+    symbols
+}
+
+/// Find the symbol containing `address` in a list of symbols sorted by address.
+///
+/// If several symbols contain the address, the one starting closest to it wins.
+pub(crate) fn symbol_at(symbols: &[Symbol], address: u64) -> Option<&Symbol> {
+    let end = symbols.partition_point(|s| s.address <= address);
+
+    symbols[..end].iter().rev().find(|s| s.contains(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, address: u64, size: u64) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            address,
+            size,
+            kind: SymbolKind::Function,
+        }
+    }
+
+    #[test]
+    fn lookup_by_address() {
+        let symbols = vec![
+            symbol("_start", 0x10000, 0x10),
+            symbol("main", 0x10010, 0x40),
+            symbol("loop", 0x10020, 0),
+            symbol("exit", 0x10050, 0x8),
+        ];
+
+        assert_eq!(symbol_at(&symbols, 0xfff0), None);
+        assert_eq!(symbol_at(&symbols, 0x10000).unwrap().name, "_start");
+        assert_eq!(symbol_at(&symbols, 0x1000c).unwrap().name, "_start");
+        assert_eq!(symbol_at(&symbols, 0x10010).unwrap().name, "main");
+        assert_eq!(symbol_at(&symbols, 0x10020).unwrap().name, "loop");
+        assert_eq!(symbol_at(&symbols, 0x10024).unwrap().name, "main");
+        assert_eq!(symbol_at(&symbols, 0x10054).unwrap().name, "exit");
+        assert_eq!(symbol_at(&symbols, 0x10058), None);
+    }
+
+    #[test]
+    fn size_exceeding_address_space() {
+        let symbols = vec![symbol("huge", u64::MAX - 4, u64::MAX)];
+
+        assert_eq!(symbols[0].range(), u64::MAX - 4..u64::MAX);
+        assert_eq!(symbol_at(&symbols, u64::MAX - 1).unwrap().name, "huge");
+        assert_eq!(symbol_at(&symbols, 0x10000), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! # System calls of Selfie
+//!
+//! Selfie programs perform all I/O through `ecall` with the number of the system call in `a7`,
+//! usually set by an `addi a7,zero,N` right before it. [`DecodedProgram::syscall_sites`] lists
+//! every `ecall` of a program with the system call it performs, which is found by propagating
+//! constants (see [`constant_propagation`]) through the control-flow graph with the resolved
+//! targets of indirect jumps (see [`resolve_indirect_jumps`]), so that the system calls of a
+//! program can be checked before it is executed.
+//!
+//! ```
+//! use riscu::{assemble::assemble, SelfieSyscall, Syscall};
+//!
+//! let program = assemble(
+//!     "
+//!         addi a0, zero, 0
+//!         addi a7, zero, 93
+//!         ecall
+//!     ",
+//! )
+//! .unwrap()
+//! .decode()
+//! .unwrap();
+//!
+//! let sites = program.syscall_sites();
+//!
+//! assert_eq!(sites.len(), 1);
+//! assert_eq!(sites[0].address, 0x10008);
+//! assert_eq!(sites[0].syscall, Syscall::Selfie(SelfieSyscall::Exit));
+//! ```
+
+use crate::{
+    dataflow::{constant_propagation, resolve_indirect_jumps},
+    DecodedProgram, Instruction, Register,
+};
+use core::fmt;
+
+/// The system calls supported by Selfie, with the numbers of Linux on RISC-V.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialOrd, PartialEq)]
+pub enum SelfieSyscall {
+    Exit,
+    Read,
+    Write,
+    Openat,
+    Brk,
+}
+
+impl SelfieSyscall {
+    pub const ALL: [SelfieSyscall; 5] = [
+        SelfieSyscall::Exit,
+        SelfieSyscall::Read,
+        SelfieSyscall::Write,
+        SelfieSyscall::Openat,
+        SelfieSyscall::Brk,
+    ];
+
+    /// The number passed in `a7`.
+    pub fn number(self) -> u64 {
+        match self {
+            SelfieSyscall::Exit => 93,
+            SelfieSyscall::Read => 63,
+            SelfieSyscall::Write => 64,
+            SelfieSyscall::Openat => 56,
+            SelfieSyscall::Brk => 214,
+        }
+    }
+
+    pub fn from_number(number: u64) -> Option<SelfieSyscall> {
+        SelfieSyscall::ALL
+            .iter()
+            .copied()
+            .find(|syscall| syscall.number() == number)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SelfieSyscall::Exit => "exit",
+            SelfieSyscall::Read => "read",
+            SelfieSyscall::Write => "write",
+            SelfieSyscall::Openat => "openat",
+            SelfieSyscall::Brk => "brk",
+        }
+    }
+}
+
+impl fmt::Display for SelfieSyscall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The system call performed by an `ecall`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Syscall {
+    Selfie(SelfieSyscall),
+    /// A constant number which is not a system call of Selfie.
+    Unknown(u64),
+    /// The number in `a7` differs between executions or can not be determined.
+    Unresolved,
+}
+
+impl Syscall {
+    fn from_number(number: Option<u64>) -> Syscall {
+        match number {
+            Some(number) => SelfieSyscall::from_number(number)
+                .map(Syscall::Selfie)
+                .unwrap_or(Syscall::Unknown(number)),
+            None => Syscall::Unresolved,
+        }
+    }
+}
+
+/// An `ecall` in the code.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SyscallSite {
+    pub address: u64,
+    pub syscall: Syscall,
+}
+
+impl DecodedProgram {
+    /// All `ecall` instructions of the code ordered by address, with the system call they
+    /// perform. If the target of an indirect jump, call or return can not be resolved, any
+    /// `ecall` may be reached from it and all of them are unresolved.
+    pub fn syscall_sites(&self) -> Vec<SyscallSite> {
+        let cfg = resolve_indirect_jumps(self);
+        let constants = constant_propagation(&cfg, self);
+        let complete = cfg
+            .unresolved()
+            .iter()
+            .all(|jump| jump.flow.target().is_some());
+
+        cfg.blocks()
+            .iter()
+            .flat_map(|block| block.instructions.iter())
+            .filter(|decoded| matches!(decoded.instruction, Instruction::Ecall(_)))
+            .map(|decoded| {
+                let number = constants
+                    .before(decoded.address)
+                    .and_then(|state| state.constant(Register::A7))
+                    .filter(|_| complete);
+
+                SyscallSite {
+                    address: decoded.address,
+                    syscall: Syscall::from_number(number),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assemble::assemble;
+
+    #[test]
+    fn numbers() {
+        for syscall in SelfieSyscall::ALL.iter() {
+            assert_eq!(SelfieSyscall::from_number(syscall.number()), Some(*syscall));
+        }
+
+        assert_eq!(SelfieSyscall::from_number(0), None);
+        assert_eq!(SelfieSyscall::Openat.to_string(), "openat");
+    }
+
+    #[test]
+    fn sites() {
+        let program = assemble(
+            "
+                addi a7, zero, 63
+                ecall
+                addi a7, zero, 64
+                beq a0, zero, write
+                addi a1, zero, 8
+            write:
+                ecall
+                addi a7, zero, 17
+                ecall
+                ld a7, 0(sp)
+                ecall
+                addi a7, zero, 93
+                ecall
+            ",
+        )
+        .unwrap()
+        .decode()
+        .unwrap();
+
+        let syscalls = program
+            .syscall_sites()
+            .iter()
+            .map(|site| (site.address, site.syscall))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            syscalls,
+            vec![
+                (0x10004, Syscall::Selfie(SelfieSyscall::Read)),
+                (0x10014, Syscall::Selfie(SelfieSyscall::Write)),
+                (0x1001c, Syscall::Unknown(17)),
+                (0x10024, Syscall::Unresolved),
+                (0x1002c, Syscall::Selfie(SelfieSyscall::Exit)),
+            ]
+        );
+    }
+
+    #[test]
+    fn sites_reached_by_indirect_jumps() {
+        let syscalls = |source: &str| {
+            assemble(source)
+                .unwrap()
+                .decode()
+                .unwrap()
+                .syscall_sites()
+                .iter()
+                .map(|site| (site.address, site.syscall))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            syscalls(
+                "
+                    addi a7, zero, 64
+                    ecall
+                    addi a7, zero, 221
+                    auipc t0, 0
+                    addi t0, t0, -8
+                    jalr zero, 0(t0)
+                "
+            ),
+            vec![(0x10004, Syscall::Unresolved)]
+        );
+
+        assert_eq!(
+            syscalls(
+                "
+                    addi a7, zero, 64
+                    ecall
+                    addi a7, zero, 93
+                    ecall
+                    jalr zero, 0(a0)
+                "
+            ),
+            vec![
+                (0x10004, Syscall::Unresolved),
+                (0x1000c, Syscall::Unresolved)
+            ]
+        );
+    }
+}
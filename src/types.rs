@@ -14,9 +14,9 @@
 
 // This module was modified by the Selfie authors.
 
-use core::fmt;
+use core::{convert::TryFrom, fmt};
 
-use crate::Register;
+use crate::{Csr, DecodingError, FRegister, Register};
 
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub struct RType(pub u32);
@@ -25,9 +25,9 @@ impl RType {
         funct7: u32,
         funct3: u32,
         opcode: u32,
-        rd: Register,
-        rs1: Register,
-        rs2: Register,
+        rd: impl Into<u32>,
+        rs1: impl Into<u32>,
+        rs2: impl Into<u32>,
     ) -> Self {
         assert!(funct7 < 2_u32.pow(7));
         assert!(funct3 < 2_u32.pow(3));
@@ -48,6 +48,20 @@ impl RType {
     pub fn rd(&self) -> Register {
         Register::from((self.0 >> 7) & 0x1f)
     }
+    pub fn frs2(&self) -> FRegister {
+        FRegister::from((self.0 >> 20) & 0x1f)
+    }
+    pub fn frs1(&self) -> FRegister {
+        FRegister::from((self.0 >> 15) & 0x1f)
+    }
+    pub fn frd(&self) -> FRegister {
+        FRegister::from((self.0 >> 7) & 0x1f)
+    }
+    /// The rounding mode of floating-point instructions, which is stored in the funct3 field.
+    /// `None` for the reserved encodings, which are valid funct3 values of other instructions.
+    pub fn rm(&self) -> Option<RoundingMode> {
+        RoundingMode::try_from((self.0 >> 12) & 0b111).ok()
+    }
 }
 
 impl fmt::Debug for RType {
@@ -69,8 +83,8 @@ impl IType {
         immediate: i32,
         funct3: u32,
         opcode: u32,
-        rd: Register,
-        rs1: Register,
+        rd: impl Into<u32>,
+        rs1: impl Into<u32>,
     ) -> Self {
         assert!(-(2_i32.pow(11)) <= immediate && immediate < 2_i32.pow(11));
         assert!(funct3 < 2_u32.pow(3));
@@ -79,6 +93,8 @@ impl IType {
         let rd: u32 = rd.into();
         let rs1: u32 = rs1.into();
 
+        assert!(rs1 < 2_u32.pow(5));
+
         let immediate = sign_shrink(immediate, 12);
 
         Self((((((((immediate << 5) + rs1) << 3) + funct3) << 5) + rd) << 7) + opcode)
@@ -92,6 +108,16 @@ impl IType {
     pub fn rd(&self) -> Register {
         Register::from((self.0 >> 7) & 0x1f)
     }
+    pub fn frd(&self) -> FRegister {
+        FRegister::from((self.0 >> 7) & 0x1f)
+    }
+    pub fn csr(&self) -> Csr {
+        Csr::from(self.0 >> 20)
+    }
+    /// The unsigned immediate of CSR instructions, which is encoded in place of `rs1`.
+    pub fn zimm(&self) -> u32 {
+        (self.0 >> 15) & 0x1f
+    }
 }
 
 impl fmt::Debug for IType {
@@ -114,7 +140,7 @@ impl SType {
         funct3: u32,
         opcode: u32,
         rs1: Register,
-        rs2: Register,
+        rs2: impl Into<u32>,
     ) -> Self {
         assert!(-(2_i32.pow(11)) <= immediate && immediate < 2_i32.pow(11));
         assert!(funct3 < 2_u32.pow(3));
@@ -139,6 +165,9 @@ impl SType {
     pub fn rs2(&self) -> Register {
         Register::from((self.0 >> 20) & 0x1f)
     }
+    pub fn frs2(&self) -> FRegister {
+        FRegister::from((self.0 >> 20) & 0x1f)
+    }
 }
 
 impl fmt::Debug for SType {
@@ -219,7 +248,7 @@ impl fmt::Debug for BType {
 pub struct UType(pub u32);
 impl UType {
     pub(crate) fn new(immediate: i32, opcode: u32, rd: Register) -> Self {
-        assert!(immediate > 0 && immediate < 2_i32.pow(21));
+        assert!(-(2_i32.pow(19)) <= immediate && immediate < 2_i32.pow(20));
         assert!(opcode < 2_u32.pow(7));
 
         let rd: u32 = rd.into();
@@ -280,6 +309,115 @@ impl fmt::Debug for JType {
     }
 }
 
+/// The format of the fused multiply-add instructions of the F and D extensions, which take three
+/// source registers.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct R4Type(pub u32);
+impl R4Type {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        fmt: u32,
+        opcode: u32,
+        rd: FRegister,
+        rs1: FRegister,
+        rs2: FRegister,
+        rs3: FRegister,
+        rm: RoundingMode,
+    ) -> Self {
+        assert!(fmt < 2_u32.pow(2));
+        assert!(opcode < 2_u32.pow(7));
+
+        let rs3: u32 = rs3.into();
+        let rs2: u32 = rs2.into();
+        let rs1: u32 = rs1.into();
+        let rd: u32 = rd.into();
+        let rm = rm as u32;
+
+        Self(
+            (rs3 << 27) | (fmt << 25) | (rs2 << 20) | (rs1 << 15) | (rm << 12) | (rd << 7) | opcode,
+        )
+    }
+    pub fn rs3(&self) -> FRegister {
+        FRegister::from(self.0 >> 27)
+    }
+    pub fn rs2(&self) -> FRegister {
+        FRegister::from((self.0 >> 20) & 0x1f)
+    }
+    pub fn rs1(&self) -> FRegister {
+        FRegister::from((self.0 >> 15) & 0x1f)
+    }
+    pub fn rd(&self) -> FRegister {
+        FRegister::from((self.0 >> 7) & 0x1f)
+    }
+    pub fn rm(&self) -> Option<RoundingMode> {
+        RoundingMode::try_from((self.0 >> 12) & 0b111).ok()
+    }
+}
+
+impl fmt::Debug for R4Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rd: {:?}, rs1: {:?}, rs2: {:?}, rs3: {:?}, rm: {:?}",
+            self.rd(),
+            self.rs1(),
+            self.rs2(),
+            self.rs3(),
+            self.rm()
+        )
+    }
+}
+
+/// The rounding mode of a floating-point instruction. The encodings `0b101` and `0b110` are
+/// reserved.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u32)]
+pub enum RoundingMode {
+    /// Round to nearest, ties to even
+    Rne = 0b000,
+    /// Round towards zero
+    Rtz = 0b001,
+    /// Round down (towards negative infinity)
+    Rdn = 0b010,
+    /// Round up (towards positive infinity)
+    Rup = 0b011,
+    /// Round to nearest, ties to max magnitude
+    Rmm = 0b100,
+    /// Dynamic rounding mode, as selected in the `frm` register
+    Dyn = 0b111,
+}
+
+impl TryFrom<u32> for RoundingMode {
+    type Error = DecodingError;
+
+    fn try_from(raw: u32) -> Result<RoundingMode, DecodingError> {
+        match raw {
+            0b000 => Ok(RoundingMode::Rne),
+            0b001 => Ok(RoundingMode::Rtz),
+            0b010 => Ok(RoundingMode::Rdn),
+            0b011 => Ok(RoundingMode::Rup),
+            0b100 => Ok(RoundingMode::Rmm),
+            0b111 => Ok(RoundingMode::Dyn),
+            _ => Err(DecodingError::Reserved),
+        }
+    }
+}
+
+impl fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RoundingMode::Rne => "rne",
+            RoundingMode::Rtz => "rtz",
+            RoundingMode::Rdn => "rdn",
+            RoundingMode::Rup => "rup",
+            RoundingMode::Rmm => "rmm",
+            RoundingMode::Dyn => "dyn",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
 fn get_bits(n: u32, i: u32, b: u32) -> u32 {
     assert!(0 < b && b <= i + b && i + b < 32);
 
@@ -297,15 +435,11 @@ fn sign_extend(n: u32, b: u32) -> i32 {
     assert!(n <= 2_u32.pow(b));
     assert!(0 < b && b < 32);
 
-    unsafe {
-        core::mem::transmute({
-            if n < 2_u32.pow(b - 1) {
-                n
-            } else {
-                n.wrapping_sub(2_u32.pow(b))
-            }
-        })
-    }
+    (if n < 2_u32.pow(b - 1) {
+        n
+    } else {
+        n.wrapping_sub(2_u32.pow(b))
+    }) as i32
 }
 
 fn sign_shrink(immediate: i32, sign: u32) -> u32 {
@@ -328,6 +462,16 @@ mod tests {
 
         assert_eq!(RType(0x00c58633).rd(), Register::A2); // add x12,x11,x12
         assert_eq!(RType(0x40b50533).rd(), Register::A0); // sub x10,x10,x11
+
+        assert_eq!(RType(0x02c5f553).rm(), Some(RoundingMode::Dyn)); // fadd.d fa0,fa1,fa2
+        assert_eq!(RType(0x00c5d533).rm(), None); // srl x10,x11,x12
+    }
+
+    #[test]
+    fn rounding_mode() {
+        assert_eq!(RoundingMode::try_from(0b001), Ok(RoundingMode::Rtz));
+        assert_eq!(RoundingMode::try_from(0b101), Err(DecodingError::Reserved));
+        assert_eq!(RoundingMode::try_from(0b110), Err(DecodingError::Reserved));
     }
 
     #[test]
@@ -365,6 +509,29 @@ mod tests {
         assert_eq!(UType(0x212120b7).rd(), Register::Ra); // lui x1,0x21212
     }
 
+    #[test]
+    fn r4type() {
+        let r4 = R4Type(0x6ac5a543); // fmadd.d fa0,fa1,fa2,fa3,rdn
+        assert_eq!(r4.rd(), FRegister::Fa0);
+        assert_eq!(r4.rs1(), FRegister::Fa1);
+        assert_eq!(r4.rs2(), FRegister::Fa2);
+        assert_eq!(r4.rs3(), FRegister::Fa3);
+        assert_eq!(r4.rm(), Some(RoundingMode::Rdn));
+
+        assert_eq!(
+            R4Type::new(
+                0b01,
+                0b1000011,
+                FRegister::Fa0,
+                FRegister::Fa1,
+                FRegister::Fa2,
+                FRegister::Fa3,
+                RoundingMode::Rdn
+            ),
+            r4
+        );
+    }
+
     #[test]
     #[allow(overflowing_literals)]
     fn jtype() {
@@ -1,5 +1,10 @@
-use riscu::load_object_file;
-use std::{env, fs::File, path::PathBuf, process::Command};
+use riscu::{load_object_file, load_object_reader, Program};
+use std::{
+    env,
+    fs::{self, File},
+    path::PathBuf,
+    process::Command,
+};
 use tempfile::{tempdir, TempDir};
 use which::which;
 
@@ -97,3 +102,52 @@ fn decode_selfie_binary() {
         "can load an decode RISC-U binaries from latest Selfie"
     );
 }
+
+#[test]
+fn load_selfie_binary_from_memory() {
+    with_temp_dir(|dir| {
+        let object_file = compile_selfie(dir);
+
+        let from_path = load_object_file(&object_file).unwrap();
+        let from_bytes = Program::from_bytes(&fs::read(&object_file).unwrap()).unwrap();
+        let from_reader = load_object_reader(File::open(&object_file).unwrap()).unwrap();
+
+        for program in [from_bytes, from_reader] {
+            assert_eq!(program.code.address, from_path.code.address);
+            assert_eq!(program.code.content, from_path.code.content);
+            assert_eq!(program.data.address, from_path.data.address);
+            assert_eq!(program.data.content, from_path.data.content);
+            assert_eq!(program.instruction_range, from_path.instruction_range);
+        }
+    });
+}
+
+#[test]
+fn selfie_binary_exposes_entry_point_and_segments() {
+    let program = with_temp_dir(|dir| load_object_file(compile_selfie(dir)).unwrap());
+
+    assert_eq!(program.entry_point, program.code.address);
+    assert!(program.segments.len() >= 2);
+    assert!(program
+        .segments
+        .iter()
+        .any(|s| s.permissions.execute && s.address == program.code.address));
+    assert!(program
+        .segments
+        .iter()
+        .all(|s| s.file_size() <= s.memory_size));
+}
+
+#[test]
+fn write_selfie_binary_round_trip() {
+    let program = with_temp_dir(|dir| load_object_file(compile_selfie(dir)).unwrap());
+
+    let mut binary = Vec::new();
+    program.write_elf(&mut binary).unwrap();
+
+    let reloaded = Program::from_bytes(&binary).unwrap();
+
+    assert_eq!(reloaded.code, program.code);
+    assert_eq!(reloaded.data, program.data);
+    assert_eq!(reloaded.entry_point, program.entry_point);
+}